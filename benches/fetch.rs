@@ -0,0 +1,199 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::fs::File;
+use std::hint::black_box;
+use std::io::BufReader;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use uwufetch::assets::Assets;
+use uwufetch::display::render_ascii;
+use uwufetch::info::{self, SystemInfo};
+use uwufetch::uwufy;
+
+/// Wall-clock budget for a whole `uwufetch -r` process. Cached runs that
+/// take longer than this are a regression worth looking at.
+const CACHED_STARTUP_BUDGET: Duration = Duration::from_millis(10);
+
+fn sample_info() -> SystemInfo {
+    SystemInfo {
+        os_name: "arch".to_string(),
+        kernel: "6.9.3-arch1-1".to_string(),
+        model: "Lenovo ThinkPad X1 Carbon Gen 9".to_string(),
+        cpu_model: "11th Gen Intel(R) Core(TM) i7-1165G7 @ 2.80GHz".to_string(),
+        gpu_models: vec!["Intel Corporation TigerLake-LP GT2 [Iris Xe Graphics]".to_string()],
+        pkgman_name: "1204 (pacman), 12 (flatpak)".to_string(),
+        ..Default::default()
+    }
+}
+
+fn bench_render_ascii(c: &mut Criterion) {
+    let logos: Vec<(String, String)> = Assets::iter()
+        .filter(|path| path.starts_with("ascii/") && path.ends_with(".txt"))
+        .filter_map(|path| {
+            let file = Assets::get(&path)?;
+            let content = String::from_utf8(file.data.into_owned()).ok()?;
+            Some((path.into_owned(), content))
+        })
+        .collect();
+
+    c.bench_function("render_ascii/arch", |b| {
+        let arch = &logos
+            .iter()
+            .find(|(path, _)| path == "ascii/arch.txt")
+            .unwrap()
+            .1;
+        b.iter(|| render_ascii(black_box(arch)))
+    });
+    c.bench_function("render_ascii/all", |b| {
+        b.iter(|| {
+            for (_, content) in &logos {
+                black_box(render_ascii(black_box(content)));
+            }
+        })
+    });
+}
+
+fn bench_uwufy(c: &mut Criterion) {
+    let info = sample_info();
+
+    c.bench_function("uwufy/name", |b| {
+        b.iter(|| {
+            let mut name = info.os_name.clone();
+            uwufy::uwu_name(black_box(&mut name));
+            name
+        })
+    });
+    c.bench_function("uwufy/kernel", |b| {
+        b.iter(|| {
+            let mut kernel = info.kernel.clone();
+            uwufy::uwu_kernel(black_box(&mut kernel));
+            kernel
+        })
+    });
+    c.bench_function("uwufy/hw", |b| {
+        b.iter(|| {
+            let mut cpu = info.cpu_model.clone();
+            uwufy::uwu_hw(black_box(&mut cpu));
+            cpu
+        })
+    });
+    c.bench_function("uwufy/all", |b| {
+        b.iter(|| {
+            let mut info = info.clone();
+            uwufy::uwufy_all(black_box(&mut info));
+            info
+        })
+    });
+}
+
+fn bench_probes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("probe");
+    group.sample_size(10);
+
+    group.bench_function("gpus", |b| b.iter(info::detect_gpus));
+    group.bench_function("displays", |b| b.iter(info::detect_displays));
+    group.bench_function("scale", |b| b.iter(info::detect_scale));
+    group.bench_function("color_depth", |b| b.iter(info::detect_color_depth));
+    group.bench_function("monitors", |b| b.iter(info::detect_monitors));
+    group.bench_function("packages", |b| b.iter(info::detect_packages_fast));
+
+    group.finish();
+}
+
+/// A dpkg status file about the size of a full desktop install's.
+fn dpkg_fixture(packages: usize) -> Vec<u8> {
+    let mut status = String::new();
+    for i in 0..packages {
+        let state = if i % 20 == 0 {
+            "deinstall ok config-files"
+        } else {
+            "install ok installed"
+        };
+        status.push_str(&format!(
+            "Package: package-{i}\nStatus: {state}\nPriority: optional\n\
+             Section: libs\nInstalled-Size: {size}\nMaintainer: Someone <someone@example.org>\n\
+             Architecture: amd64\nVersion: 1.{i}-1\nDepends: libc6 (>= 2.34)\n\
+             Description: package number {i}\n a longer description that goes on\n \
+             for a couple of lines, like most of them do\n\n",
+            size = i * 7
+        ));
+    }
+    status.into_bytes()
+}
+
+fn bench_dpkg(c: &mut Criterion) {
+    let path = std::env::temp_dir().join("uwufetch-bench-dpkg-status");
+    std::fs::write(&path, dpkg_fixture(5000)).expect("failed to write the dpkg fixture");
+
+    let mut group = c.benchmark_group("dpkg");
+    // What detect_packages_fast used to do.
+    group.bench_function("read_to_string", |b| {
+        b.iter(|| {
+            let status = std::fs::read_to_string(&path).unwrap();
+            status
+                .lines()
+                .filter(|l| *l == "Status: install ok installed")
+                .count() as u32
+        })
+    });
+    group.bench_function("streaming", |b| {
+        b.iter(|| {
+            let file = File::open(&path).unwrap();
+            info::dpkg_installed(BufReader::with_capacity(64 * 1024, file))
+        })
+    });
+    group.finish();
+
+    let _ = std::fs::remove_file(&path);
+}
+
+fn run(home: &Path, args: &[&str]) {
+    let status = Command::new(env!("CARGO_BIN_EXE_uwufetch"))
+        .args(args)
+        .env("HOME", home)
+        .stdout(Stdio::null())
+        .status()
+        .expect("failed to run uwufetch");
+    assert!(status.success());
+}
+
+/// End to end, through the real binary: the probes are memoized per
+/// process, so timing `populate` in-process would only measure the first
+/// iteration.
+fn bench_startup(c: &mut Criterion) {
+    // Keep the benchmark's cache away from the real one.
+    let home = std::env::temp_dir().join("uwufetch-bench");
+    let _ = std::fs::create_dir_all(&home);
+    run(&home, &["-w"]);
+
+    let mut group = c.benchmark_group("startup");
+    group.sample_size(20);
+
+    group.bench_function("detect", |b| b.iter(|| run(&home, &[])));
+    group.bench_function("cached", |b| b.iter(|| run(&home, &["-r"])));
+
+    group.finish();
+
+    let runs = 20;
+    let start = Instant::now();
+    for _ in 0..runs {
+        run(&home, &["-r"]);
+    }
+    let average = start.elapsed() / runs;
+    if average > CACHED_STARTUP_BUDGET {
+        eprintln!(
+            "warning: cached startup took {:?} on average, over the {:?} budget",
+            average, CACHED_STARTUP_BUDGET
+        );
+    }
+}
+
+criterion_group!(
+    benches,
+    bench_render_ascii,
+    bench_uwufy,
+    bench_probes,
+    bench_dpkg,
+    bench_startup
+);
+criterion_main!(benches);
@@ -1,5 +1,340 @@
-use rust_embed::Embed;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 
-#[derive(Embed)]
+/// Only the sixel images and ASCII logos are embedded; the PNG sources are
+/// not read at runtime. Release builds store them deflated and inflate each
+/// file once, on first access.
+#[cfg(not(feature = "no-embed"))]
+#[derive(rust_embed::Embed)]
 #[folder = "res/"]
+#[cfg_attr(feature = "sixel", include = "*.sixel")]
+#[include = "ascii/*.txt"]
+#[include = "ascii/*.toml"]
 pub struct Assets;
+
+/// With `no-embed`, assets are read at runtime from the share directory,
+/// `/usr/share/uwufetch` unless `UWUFETCH_SHARE_DIR` is set at build time.
+#[cfg(feature = "no-embed")]
+pub struct Assets;
+
+#[cfg(feature = "no-embed")]
+pub struct AssetFile {
+    pub data: std::borrow::Cow<'static, [u8]>,
+}
+
+#[cfg(feature = "no-embed")]
+impl Assets {
+    fn share_dir() -> std::path::PathBuf {
+        std::path::PathBuf::from(option_env!("UWUFETCH_SHARE_DIR").unwrap_or("/usr/share/uwufetch"))
+    }
+
+    pub fn get(path: &str) -> Option<AssetFile> {
+        let data = std::fs::read(Self::share_dir().join(path)).ok()?;
+        Some(AssetFile { data: data.into() })
+    }
+
+    pub fn iter() -> impl Iterator<Item = std::borrow::Cow<'static, str>> {
+        fn walk(dir: &std::path::Path, prefix: &str, out: &mut Vec<String>) {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                return;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let path = entry.path();
+                if path.is_dir() {
+                    walk(&path, &format!("{}{}/", prefix, name), out);
+                } else {
+                    out.push(format!("{}{}", prefix, name));
+                }
+            }
+        }
+
+        let mut files = Vec::new();
+        walk(&Self::share_dir(), "", &mut files);
+        files.into_iter().map(std::borrow::Cow::Owned)
+    }
+}
+
+/// Optional metadata kept next to a logo as `ascii/<name>.toml`. Every
+/// key may be left out; a sidecar can also exist without a logo, to name
+/// and alias a distro that falls back to `unknown`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LogoMeta {
+    /// Shown on the OWOS row. Logos without one show "unknown".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uwu_name: Option<String>,
+    /// Alternate spellings accepted by `-d`, matched after lowercasing, so
+    /// only list names that differ by more than case.
+    pub aliases: Vec<String>,
+    /// A basic color name or `#rrggbb`, used for the header icon.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+    /// A narrower logo to draw when the terminal is under `min_width`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub small: Option<String>,
+    /// Terminal columns the logo needs next to the info rows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_width: Option<usize>,
+}
+
+/// The sidecar for `name`, or empty metadata when it has none or it
+/// doesn't parse.
+pub fn meta(name: &str) -> LogoMeta {
+    Assets::get(&format!("ascii/{}.toml", name))
+        .and_then(|file| toml::from_str(std::str::from_utf8(&file.data).ok()?).ok())
+        .unwrap_or_default()
+}
+
+/// Every sidecar, as `(logo, metadata)`. Read once per process.
+fn all_meta() -> &'static [(String, LogoMeta)] {
+    static ALL: OnceLock<Vec<(String, LogoMeta)>> = OnceLock::new();
+    ALL.get_or_init(|| {
+        Assets::iter()
+            .filter_map(|path| {
+                let name = path.strip_prefix("ascii/")?.strip_suffix(".toml")?;
+                Some((name.to_string(), meta(name)))
+            })
+            .collect()
+    })
+}
+
+/// Nerd Font glyphs for logos that have their own. Others use the glyph of
+/// their OS family.
+const GLYPHS: &[(&str, &str)] = &[
+    ("alpine", "\u{f300}"),
+    ("android", "\u{f17b}"),
+    ("arch", "\u{f303}"),
+    ("arcolinux", "\u{f346}"),
+    ("artix", "\u{f31f}"),
+    ("debian", "\u{f306}"),
+    ("deepin", "\u{f321}"),
+    ("devuan", "\u{f307}"),
+    ("elementary", "\u{f309}"),
+    ("endeavouros", "\u{f322}"),
+    ("fedora", "\u{f30a}"),
+    ("freebsd", "\u{f30c}"),
+    ("garuda", "\u{f337}"),
+    ("gentoo", "\u{f30d}"),
+    ("guix", "\u{f325}"),
+    ("kali", "\u{f327}"),
+    ("linuxmint", "\u{f30e}"),
+    ("manjaro", "\u{f312}"),
+    ("mx", "\u{f33f}"),
+    ("nixos", "\u{f313}"),
+    ("openbsd", "\u{f328}"),
+    ("opensuse-leap", "\u{f314}"),
+    ("opensuse-tumbleweed", "\u{f37d}"),
+    ("pop", "\u{f32a}"),
+    ("raspbian", "\u{f315}"),
+    ("rocky", "\u{f32b}"),
+    ("slackware", "\u{f318}"),
+    ("solus", "\u{f32d}"),
+    ("ubuntu", "\u{f31b}"),
+    ("void", "\u{f32e}"),
+    ("zorin", "\u{f32f}"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Family {
+    Linux,
+    Bsd,
+    Apple,
+    Windows,
+}
+
+impl Family {
+    pub fn of(logo: &str) -> Option<Self> {
+        match logo {
+            "unknown" => None,
+            "freebsd" | "openbsd" | "netbsd" | "dragonfly" | "ghostbsd" | "hardenedbsd" => {
+                Some(Family::Bsd)
+            }
+            "ios" => Some(Family::Apple),
+            _ if logo.starts_with("macos") => Some(Family::Apple),
+            _ if logo.starts_with("windows") => Some(Family::Windows),
+            _ => Some(Family::Linux),
+        }
+    }
+
+    pub fn emoji(self) -> &'static str {
+        match self {
+            Family::Linux => "🐧",
+            Family::Bsd => "😈",
+            Family::Apple => "🍎",
+            Family::Windows => "🪟",
+        }
+    }
+
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Family::Linux => "\u{f31a}",
+            Family::Bsd => "\u{f30c}",
+            Family::Apple => "\u{f302}",
+            Family::Windows => "\u{f17a}",
+        }
+    }
+}
+
+/// The Nerd Font glyph for a resolved logo name, falling back to the
+/// `-arm`-less logo and then to the OS family.
+pub fn glyph(logo: &str) -> Option<&'static str> {
+    let base = logo.split('-').next().unwrap_or(logo);
+    GLYPHS
+        .iter()
+        .find(|(name, _)| *name == logo)
+        .or_else(|| GLYPHS.iter().find(|(name, _)| *name == base))
+        .map(|(_, glyph)| *glyph)
+        .or_else(|| Family::of(logo).map(Family::glyph))
+}
+
+#[derive(Serialize)]
+pub struct Logo {
+    pub name: String,
+    #[serde(flatten)]
+    pub meta: LogoMeta,
+    pub sixel: bool,
+}
+
+/// Every logo with an embedded ASCII asset, sorted by name. Small
+/// variants are left out, being drawn in place of their logo.
+pub fn logos() -> Vec<Logo> {
+    let small = |name: &str| {
+        all_meta()
+            .iter()
+            .any(|(_, meta)| meta.small.as_deref() == Some(name))
+    };
+    let mut logos: Vec<Logo> = Assets::iter()
+        .filter_map(|path| {
+            let name = path
+                .strip_prefix("ascii/")?
+                .strip_suffix(".txt")?
+                .to_string();
+            if small(&name) {
+                return None;
+            }
+            let meta = meta(&name);
+            let sixel = Assets::get(&format!("{}.sixel", name)).is_some();
+
+            Some(Logo { name, meta, sixel })
+        })
+        .collect();
+
+    logos.sort_by(|a, b| a.name.cmp(&b.name));
+    logos
+}
+
+fn has_logo(name: &str) -> bool {
+    Assets::get(&format!("ascii/{}.txt", name)).is_some()
+}
+
+fn alias(name: &str) -> Option<&'static str> {
+    all_meta()
+        .iter()
+        .find(|(_, meta)| meta.aliases.iter().any(|alias| alias == name))
+        .map(|(logo, _)| logo.as_str())
+}
+
+/// Maps a `-d` argument or os-release ID onto an embedded logo name.
+///
+/// Tries an exact match, then the alias table, then the longest logo that
+/// prefixes the name (`ubuntu-22.04`, `fedora-asahi-remix`), then the name
+/// without a trailing version (`macos16`), and finally a small edit distance
+/// to catch typos. Unmatched names are returned
+/// lowercased so the usual `unknown` fallback still applies.
+pub fn resolve_logo(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+
+    if has_logo(&name) {
+        return name;
+    }
+    if let Some(logo) = alias(&name) {
+        return logo.to_string();
+    }
+
+    let names: Vec<String> = logos().into_iter().map(|logo| logo.name).collect();
+
+    let prefixed = names
+        .iter()
+        .map(String::as_str)
+        .chain(
+            all_meta()
+                .iter()
+                .flat_map(|(_, meta)| meta.aliases.iter().map(String::as_str)),
+        )
+        .filter(|candidate| {
+            name.strip_prefix(candidate)
+                .is_some_and(|rest| rest.starts_with(['-', '_', '.', ' ']))
+        })
+        .max_by_key(|candidate| candidate.len());
+    if let Some(candidate) = prefixed {
+        return alias(candidate).unwrap_or(candidate).to_string();
+    }
+
+    let unversioned = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    if unversioned != name && has_logo(unversioned) {
+        return unversioned.to_string();
+    }
+
+    let closest = names
+        .iter()
+        .map(|candidate| (edit_distance(&name, candidate), candidate))
+        .min_by_key(|(distance, _)| *distance);
+    if let Some((distance, candidate)) = closest {
+        if distance <= 2 && distance < candidate.len() / 2 {
+            return candidate.clone();
+        }
+    }
+
+    name
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            row[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, resolve_logo};
+
+    #[test]
+    fn resolves_names_aliases_and_prefixes() {
+        assert_eq!(resolve_logo("arch"), "arch");
+        assert_eq!(resolve_logo(" NixOS\n"), "nixos");
+        assert_eq!(resolve_logo("archlinux"), "arch");
+        assert_eq!(resolve_logo("ubuntu-22.04"), "ubuntu");
+        // The longer alias wins over the `fedora` logo it starts with.
+        assert_eq!(resolve_logo("fedora-asahi-remix"), "asahi");
+        assert_eq!(resolve_logo("macos16"), "macos");
+    }
+
+    #[test]
+    fn resolves_typos_but_not_strangers() {
+        assert_eq!(resolve_logo("debain"), "debian");
+        assert_eq!(resolve_logo("fedroa"), "fedora");
+        assert_eq!(resolve_logo("Plan9"), "plan9");
+        assert_eq!(resolve_logo("templeos"), "templeos");
+    }
+
+    #[test]
+    fn edit_distance_counts_edits() {
+        assert_eq!(edit_distance("arch", "arch"), 0);
+        assert_eq!(edit_distance("", "arch"), 4);
+        assert_eq!(edit_distance("arch", ""), 4);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("debain", "debian"), 2);
+        assert_eq!(edit_distance("ω", "w"), 1);
+    }
+}
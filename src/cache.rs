@@ -1,39 +1,111 @@
-use crate::info::SystemInfo;
+use crate::config::MemoryMode;
+use crate::info::{home_dir, Chassis, Display, Monitor, Packages, Steam, SystemInfo};
 use std::fs::{self, File};
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 
 pub fn write_cache(info: &SystemInfo) {
-    if let Ok(home) = std::env::var("HOME") {
-        let cache_dir = PathBuf::from(home).join(".cache");
-        if !cache_dir.exists() {
-            let _ = fs::create_dir(&cache_dir);
+    if let Some(cache_file) = cache_file() {
+        let _lock = lock_cache();
+        let uptime_record = read_uptime_record().max(info.uptime_record);
+
+        let mut out = Vec::new();
+        let _ = writeln!(out, "written={}", now());
+        let _ = writeln!(out, "user={}", info.user);
+        let _ = writeln!(out, "host={}", info.host);
+        let _ = writeln!(out, "version_name={}", info.os_name);
+        let _ = writeln!(out, "wsl={}", info.wsl);
+        let _ = writeln!(out, "host_model={}", info.model);
+        let _ = writeln!(out, "chassis={}", info.chassis.as_str());
+        let _ = writeln!(out, "kernel={}", info.kernel);
+        let _ = writeln!(out, "cpu={}", info.cpu_model);
+        let _ = writeln!(out, "microcode={}", info.microcode);
+        let _ = writeln!(
+            out,
+            "cpu_topology={}/{}/{}",
+            info.cpu_cores, info.cpu_threads, info.cpu_max_mhz
+        );
+        let _ = writeln!(out, "cpu_vulns={}", info.cpu_vulns);
+        let _ = writeln!(out, "kernel_params={}", info.kernel_params);
+        let _ = writeln!(out, "bootloader={}", info.bootloader);
+        let _ = writeln!(out, "sched={}", info.sched);
+        let _ = writeln!(out, "virt_support={}", info.virt_support);
+        let _ = writeln!(out, "gpu_driver={}", info.gpu_driver);
+        let _ = writeln!(out, "cursor={}", info.cursor);
+        let _ = writeln!(out, "rust={}", info.rust);
+        if let Some(steam) = &info.steam {
+            let _ = writeln!(out, "steam={}/{}/{}", steam.games, steam.size, steam.proton);
+        }
+        let _ = writeln!(out, "ram_total={}", info.ram_total);
+        let _ = writeln!(out, "screen_scale={}", info.screen_scale);
+        let _ = writeln!(out, "color_depth={}", info.color_depth);
+        let _ = writeln!(out, "hdr={}", info.hdr);
+        let _ = writeln!(out, "shell={}", info.shell);
+        if info.packages.is_empty() {
+            let _ = writeln!(out, "pkgs={}", info.pkgs);
+            let _ = writeln!(out, "pkgman_name={}", info.pkgman_name);
         }
+        for packages in &info.packages {
+            let _ = writeln!(out, "pkg.{}={}", packages.manager, packages.count);
+        }
+        let _ = writeln!(out, "uptime_record={}", uptime_record);
 
-        let cache_file = cache_dir.join("uwufetch.cache");
-
-        if let Ok(mut file) = File::create(cache_file) {
-            let _ = writeln!(file, "user={}", info.user);
-            let _ = writeln!(file, "host={}", info.host);
-            let _ = writeln!(file, "version_name={}", info.os_name);
-            let _ = writeln!(file, "host_model={}", info.model);
-            let _ = writeln!(file, "kernel={}", info.kernel);
-            let _ = writeln!(file, "cpu={}", info.cpu_model);
-            let _ = writeln!(file, "screen_width={}", info.screen_width);
-            let _ = writeln!(file, "screen_height={}", info.screen_height);
-            let _ = writeln!(file, "shell={}", info.shell);
-            let _ = writeln!(file, "pkgs={}", info.pkgs);
-            let _ = writeln!(file, "pkgman_name={}", info.pkgman_name);
-
-            for gpu in &info.gpu_models {
-                let _ = writeln!(file, "gpu={}", gpu);
-            }
+        // Numbered the way GPUWU rows are, so `gpu = N` picks the same GPU
+        // from the cache as from a fresh run.
+        for (i, gpu) in info.gpu_models.iter().enumerate() {
+            let _ = writeln!(out, "gpu.{}={}", i + 1, gpu);
+        }
+
+        for display in &info.displays {
+            let _ = writeln!(
+                out,
+                "display={}x{}@{}",
+                display.width, display.height, display.refresh
+            );
+        }
+
+        for monitor in &info.monitors {
+            let _ = writeln!(
+                out,
+                "monitor={}x{}:{}",
+                monitor.width, monitor.height, monitor.name
+            );
         }
+
+        let _ = write_atomic(&cache_file, &out);
     }
 }
 
-pub fn read_cache() -> Option<SystemInfo> {
-    if let Ok(home) = std::env::var("HOME") {
+/// Seconds since `write_cache` last ran, or `None` without a cache.
+/// The file's mtime can't be used, since the uptime record is written
+/// into the same file on every run.
+pub fn cache_age() -> Option<u64> {
+    let content = fs::read_to_string(cache_file()?).ok()?;
+    let written: u64 = content
+        .lines()
+        .find_map(|line| line.strip_prefix("written="))?
+        .parse()
+        .ok()?;
+    Some(now().saturating_sub(written))
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+pub fn read_cache(mode: MemoryMode) -> Option<SystemInfo> {
+    let mut info = read_snapshot()?;
+
+    (info.ram_total, info.ram_used) = get_mem(mode);
+    info.uptime = get_uptime();
+
+    Some(info)
+}
+
+pub fn read_snapshot() -> Option<SystemInfo> {
+    if let Some(home) = home_dir() {
         let cache_file = PathBuf::from(home).join(".cache/uwufetch.cache");
 
         if let Ok(file) = File::open(cache_file) {
@@ -43,43 +115,163 @@ pub fn read_cache() -> Option<SystemInfo> {
                 host: String::new(),
                 os_name: String::new(),
                 kernel: String::new(),
+                kernel_status: String::new(),
                 model: String::new(),
                 cpu_model: String::new(),
+                cpu_cores: 0,
+                cpu_threads: 0,
+                cpu_max_mhz: 0,
                 gpu_models: Vec::new(),
+                gpu_driver: String::new(),
                 ram_total: 0,
                 ram_used: 0,
-                screen_width: 0,
-                screen_height: 0,
+                displays: Vec::new(),
+                screen_scale: 0.0,
+                color_depth: 0,
+                hdr: false,
                 shell: String::new(),
+                de: String::new(),
+                terminal: String::new(),
+                multiplexer: String::new(),
                 pkgs: 0,
                 pkgman_name: String::new(),
+                packages: Vec::new(),
                 uptime: 0,
+                session_uptime: 0,
+                fans: Vec::new(),
+                batteries: Vec::new(),
+                lid: String::new(),
+                updates: Vec::new(),
+                mux_session: String::new(),
+                local_ip: String::new(),
+                wifi: None,
+                audio: String::new(),
+                now_playing: None,
+                steam: None,
+                dotfiles: String::new(),
+                cursor: String::new(),
+                rust: String::new(),
+                display_server: String::new(),
+                virtualization: String::new(),
+                wsl: String::new(),
                 image_name: None,
+                monitors: Vec::new(),
+                chassis: Chassis::Unknown,
+                microcode: String::new(),
+                cpu_vulns: String::new(),
+                kernel_params: String::new(),
+                bootloader: String::new(),
+                sched: String::new(),
+                virt_support: String::new(),
+                serial: String::new(),
+                asset_tag: String::new(),
+                uptime_record: 0,
             };
+            let mut has_snapshot = false;
+            let mut packages = Vec::new();
+            let mut legacy_size = (0, 0);
 
             for line in reader.lines().map_while(Result::ok) {
                 if let Some((key, value)) = line.split_once('=') {
                     match key {
                         "user" => info.user = value.to_string(),
                         "host" => info.host = value.to_string(),
-                        "version_name" => info.os_name = value.to_string(),
+                        "version_name" => {
+                            info.os_name = value.to_string();
+                            has_snapshot = true;
+                        }
+                        "wsl" => info.wsl = value.to_string(),
                         "host_model" => info.model = value.to_string(),
+                        "chassis" => info.chassis = Chassis::parse(value),
                         "kernel" => info.kernel = value.to_string(),
                         "cpu" => info.cpu_model = value.to_string(),
+                        "microcode" => info.microcode = value.to_string(),
+                        "cpu_topology" => {
+                            let mut parts = value.split('/').map(|n| n.parse().unwrap_or(0));
+                            info.cpu_cores = parts.next().unwrap_or(0);
+                            info.cpu_threads = parts.next().unwrap_or(0);
+                            info.cpu_max_mhz = parts.next().unwrap_or(0);
+                        }
+                        "cpu_vulns" => info.cpu_vulns = value.to_string(),
+                        "kernel_params" => info.kernel_params = value.to_string(),
+                        "bootloader" => info.bootloader = value.to_string(),
+                        "sched" => info.sched = value.to_string(),
+                        "virt_support" => info.virt_support = value.to_string(),
+                        "gpu_driver" => info.gpu_driver = value.to_string(),
+                        "cursor" => info.cursor = value.to_string(),
+                        "rust" => info.rust = value.to_string(),
+                        "steam" => {
+                            let mut parts = value.splitn(3, '/');
+                            info.steam = Some(Steam {
+                                games: parts.next().and_then(|n| n.parse().ok()).unwrap_or(0),
+                                size: parts.next().and_then(|n| n.parse().ok()).unwrap_or(0),
+                                proton: parts.next().unwrap_or_default().to_string(),
+                            });
+                        }
+                        "ram_total" => info.ram_total = value.parse().unwrap_or(0),
+                        // Written before GPUs were numbered.
                         "gpu" => info.gpu_models.push(value.to_string()),
-                        "screen_width" => info.screen_width = value.parse().unwrap_or(0),
-                        "screen_height" => info.screen_height = value.parse().unwrap_or(0),
+                        _ if key.starts_with("gpu.") => {
+                            if let Some(i) = key[4..].parse::<usize>().ok().filter(|&i| i > 0) {
+                                if info.gpu_models.len() < i {
+                                    info.gpu_models.resize(i, String::new());
+                                }
+                                info.gpu_models[i - 1] = value.to_string();
+                            }
+                        }
+                        _ if key.starts_with("pkg.") => packages.push(Packages {
+                            manager: key[4..].to_string(),
+                            count: value.parse().unwrap_or(0),
+                        }),
+                        "monitor" => {
+                            if let Some((res, name)) = value.split_once(':') {
+                                let (w, h) = res.split_once('x').unwrap_or(("0", "0"));
+                                info.monitors.push(Monitor {
+                                    name: name.to_string(),
+                                    width: w.parse().unwrap_or(0),
+                                    height: h.parse().unwrap_or(0),
+                                });
+                            }
+                        }
+                        "display" => {
+                            let (size, refresh) = value.split_once('@').unwrap_or((value, "0"));
+                            let (w, h) = size.split_once('x').unwrap_or(("0", "0"));
+                            info.displays.push(Display {
+                                width: w.parse().unwrap_or(0),
+                                height: h.parse().unwrap_or(0),
+                                refresh: refresh.parse().unwrap_or(0),
+                            });
+                        }
+                        // Written before there was a line per display.
+                        "screen_width" => legacy_size.0 = value.parse().unwrap_or(0),
+                        "screen_height" => legacy_size.1 = value.parse().unwrap_or(0),
+                        "screen_scale" => info.screen_scale = value.parse().unwrap_or(0.0),
+                        "color_depth" => info.color_depth = value.parse().unwrap_or(0),
+                        "hdr" => info.hdr = value == "true",
                         "shell" => info.shell = value.to_string(),
                         "pkgs" => info.pkgs = value.parse().unwrap_or(0),
                         "pkgman_name" => info.pkgman_name = value.to_string(),
+                        "uptime_record" => info.uptime_record = value.parse().unwrap_or(0),
                         _ => {}
                     }
                 }
             }
 
-            info.ram_total = get_mem().0;
-            info.ram_used = get_mem().1;
-            info.uptime = get_uptime();
+            if !has_snapshot {
+                return None;
+            }
+            // A GPU whose number was missing from the file leaves a gap.
+            info.gpu_models.retain(|gpu| !gpu.is_empty());
+            if !packages.is_empty() {
+                info.set_packages(packages);
+            }
+            if info.displays.is_empty() && legacy_size != (0, 0) {
+                info.displays.push(Display {
+                    width: legacy_size.0,
+                    height: legacy_size.1,
+                    refresh: 0,
+                });
+            }
 
             return Some(info);
         }
@@ -88,158 +280,281 @@ pub fn read_cache() -> Option<SystemInfo> {
     None
 }
 
-fn get_uptime() -> u64 {
-    #[cfg(target_os = "windows")]
-    {
-        unsafe {
-            use windows::Win32::System::SystemInformation::GetTickCount64;
+pub fn cache_file() -> Option<PathBuf> {
+    home_dir().map(|home| PathBuf::from(home).join(".cache/uwufetch.cache"))
+}
 
-            let tick_count = GetTickCount64();
-            tick_count / 1000
-        }
-    }
+/// Takes the lock every cache writer holds while it reads, changes and
+/// replaces a cache file, so uwufetch starting in several terminals at
+/// once can't lose another run's update. Readers don't need it, since
+/// files are only ever replaced whole by `write_atomic`. Released when
+/// the returned file is dropped; `None` if the lock couldn't be had, in
+/// which case writing goes ahead anyway.
+fn lock_cache() -> Option<File> {
+    let dir = cache_file()?.parent()?.to_path_buf();
+    let _ = fs::create_dir_all(&dir);
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join("uwufetch.lock"))
+        .ok()?;
+    file.lock().ok()?;
+    Some(file)
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        if let Ok(content) = fs::read_to_string("/proc/uptime") {
-            if let Some(uptime_str) = content.split_whitespace().next() {
-                if let Ok(uptime_f) = uptime_str.parse::<f64>() {
-                    self.uptime = uptime_f as u64;
-                    return;
-                }
-            }
-        }
+/// Replaces `path` with `contents` by writing a temporary file next to it
+/// and renaming it over the original, so a reader sees either the old
+/// file or the new one and never half of each.
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
     }
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        use std::process::Command;
-
-        if let Ok(output) = Command::new("sysctl").arg("kern.boottime").output() {
-            let boottime = String::from_utf8_lossy(&output.stdout);
-            if let Ok(output) = Command::new("uptime").output() {
-                let uptime_str = String::from_utf8_lossy(&output.stdout);
-                if uptime_str.contains("days") {
-                    self.uptime = 86400;
-                }
-            }
-        }
+fn modify_cache<T>(f: impl FnOnce(&mut Vec<(String, String)>) -> T) -> Option<T> {
+    let path = cache_file()?;
+    let _lock = lock_cache();
+    let mut entries: Vec<(String, String)> = fs::read_to_string(&path)
+        .map(|content| {
+            content
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let result = f(&mut entries);
+
+    let mut out = Vec::new();
+    for (key, value) in &entries {
+        let _ = writeln!(out, "{}={}", key, value);
     }
+    write_atomic(&path, &out).ok()?;
+
+    Some(result)
 }
 
-fn get_mem() -> (u64, u64) {
-    #[cfg(target_os = "windows")]
-    {
-        use std::process::Command;
+fn pkg_cache_file() -> Option<PathBuf> {
+    home_dir().map(|home| PathBuf::from(home).join(".cache/uwufetch.pkgs"))
+}
 
-        unsafe {
-            use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+/// A count remembered by `write_pkg_count`, with the stamp it was stored
+/// with for the caller to check. Kept apart from the main cache, which
+/// `-w` rewrites from scratch.
+pub fn read_pkg_count(key: &str) -> Option<(u64, u32)> {
+    let (stamp, count) = read_stamped(key)?;
+    Some((stamp, count.parse().ok()?))
+}
 
-            let mut memstatus = MEMORYSTATUSEX {
-                dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
-                ..Default::default()
-            };
+pub fn write_pkg_count(key: &str, stamp: u64, count: u32) {
+    write_stamped(key, stamp, &count.to_string());
+}
 
-            if GlobalMemoryStatusEx(&mut memstatus).is_ok() {
-                let ram_total = (memstatus.ullTotalPhys / 1024 / 1024) as u64;
-                let ram_used =
-                    ((memstatus.ullTotalPhys - memstatus.ullAvailPhys) / 1024 / 1024) as u64;
+/// Like `read_pkg_count`, for any single-line value.
+pub fn read_stamped(key: &str) -> Option<(u64, String)> {
+    let content = fs::read_to_string(pkg_cache_file()?).ok()?;
+    let value = content
+        .lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))?;
+    let (stamp, value) = value.split_once(':')?;
+    Some((stamp.parse().ok()?, value.to_string()))
+}
 
-                return (ram_total, ram_used);
+pub fn write_stamped(key: &str, stamp: u64, value: &str) {
+    let Some(path) = pkg_cache_file() else {
+        return;
+    };
+    let _lock = lock_cache();
+    let mut lines: Vec<String> = fs::read_to_string(&path)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| line.split_once('=').map(|(k, _)| k) != Some(key))
+        .map(str::to_string)
+        .collect();
+    lines.push(format!("{}={}:{}", key, stamp, value));
+
+    let _ = write_atomic(&path, (lines.join("\n") + "\n").as_bytes());
+}
+
+fn read_uptime_record() -> u64 {
+    cache_file()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| {
+            content
+                .lines()
+                .find_map(|line| line.strip_prefix("uptime_record="))
+                .and_then(|v| v.parse().ok())
+        })
+        .unwrap_or(0)
+}
+
+pub fn update_uptime_record(uptime: u64) -> u64 {
+    modify_cache(|entries| {
+        let entry = entries.iter_mut().find(|(key, _)| key == "uptime_record");
+        match entry {
+            Some((_, value)) => {
+                let record = value.parse::<u64>().unwrap_or(0).max(uptime);
+                *value = record.to_string();
+                record
+            }
+            None => {
+                entries.push(("uptime_record".to_string(), uptime.to_string()));
+                uptime
             }
         }
+    })
+    .unwrap_or(uptime)
+}
 
-        if let Ok(output) = Command::new("wmic")
-            .args([
-                "OS",
-                "get",
-                "TotalVisibleMemorySize,FreePhysicalMemory",
-                "/format:csv",
-            ])
-            .output()
-        {
-            let mem = String::from_utf8_lossy(&output.stdout);
-            for line in mem.lines().skip(1) {
-                if !line.trim().is_empty() {
-                    let parts: Vec<&str> = line.split(',').collect();
-                    if parts.len() >= 3 {
-                        if let Ok(free) = parts[1].parse::<u64>() {
-                            if let Ok(total) = parts[2].parse::<u64>() {
-                                let ram_total = total / 1024;
-                                let ram_used = (total - free) / 1024;
-
-                                return (ram_total, ram_used);
-                            }
-                        }
-                    }
-                }
-            }
+#[cfg(target_os = "windows")]
+fn get_uptime() -> u64 {
+    crate::info::windows_uptime()
+}
+
+#[cfg(target_os = "linux")]
+fn get_uptime() -> u64 {
+    fs::read_to_string("/proc/uptime")
+        .ok()
+        .and_then(|content| content.split_whitespace().next()?.parse::<f64>().ok())
+        .map_or(0, |uptime| uptime as u64)
+}
+
+#[cfg(target_os = "macos")]
+fn get_uptime() -> u64 {
+    use std::process::Command;
+
+    if let Ok(output) = Command::new("uptime").output() {
+        let uptime_str = String::from_utf8_lossy(&output.stdout);
+        if uptime_str.contains("days") {
+            return 86400;
         }
+    }
+
+    0
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn get_uptime() -> u64 {
+    0
+}
 
-        (0, 0)
+#[cfg(target_os = "windows")]
+fn get_mem(mode: MemoryMode) -> (u64, u64) {
+    use std::process::Command;
+
+    let _ = mode;
+
+    unsafe {
+        use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+        let mut memstatus = MEMORYSTATUSEX {
+            dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
+            ..Default::default()
+        };
+
+        if GlobalMemoryStatusEx(&mut memstatus).is_ok() {
+            let ram_total = (memstatus.ullTotalPhys / 1024 / 1024) as u64;
+            let ram_used = ((memstatus.ullTotalPhys - memstatus.ullAvailPhys) / 1024 / 1024) as u64;
+
+            return (ram_total, ram_used);
+        }
     }
 
-    #[cfg(target_os = "linux")]
+    if let Ok(output) = Command::new("wmic")
+        .args([
+            "OS",
+            "get",
+            "TotalVisibleMemorySize,FreePhysicalMemory",
+            "/format:csv",
+        ])
+        .output()
     {
-        if let Ok(content) = fs::read_to_string("/proc/meminfo") {
-            let mut total = 0u64;
-            let mut available = 0u64;
-
-            for line in content.lines() {
-                if line.starts_with("MemTotal:") {
-                    if let Some(val) = line.split_whitespace().nth(1) {
-                        total = val.parse().unwrap_or(0);
-                    }
-                } else if line.starts_with("MemAvailable:") {
-                    if let Some(val) = line.split_whitespace().nth(1) {
-                        available = val.parse().unwrap_or(0);
+        let mem = String::from_utf8_lossy(&output.stdout);
+        for line in mem.lines().skip(1) {
+            if !line.trim().is_empty() {
+                let parts: Vec<&str> = line.split(',').collect();
+                if parts.len() >= 3 {
+                    if let Ok(free) = parts[1].parse::<u64>() {
+                        if let Ok(total) = parts[2].parse::<u64>() {
+                            let ram_total = total / 1024;
+                            let ram_used = (total - free) / 1024;
+
+                            return (ram_total, ram_used);
+                        }
                     }
                 }
             }
-
-            self.ram_total = total / 1024;
-            self.ram_used = (total - available) / 1024;
-            return;
         }
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        use std::process::Command;
+    (0, 0)
+}
 
-        if let Ok(output) = Command::new("sysctl").arg("hw.memsize").output() {
-            let mem = String::from_utf8_lossy(&output.stdout);
-            if let Some(size) = mem.split(':').nth(1) {
-                if let Ok(bytes) = size.trim().parse::<u64>() {
-                    self.ram_total = bytes / 1024 / 1024;
-                }
+#[cfg(target_os = "linux")]
+fn get_mem(mode: MemoryMode) -> (u64, u64) {
+    fs::read_to_string("/proc/meminfo")
+        .map_or((0, 0), |content| crate::info::parse_meminfo(&content, mode))
+}
+
+#[cfg(target_os = "macos")]
+fn get_mem(mode: MemoryMode) -> (u64, u64) {
+    use std::process::Command;
+
+    let mut ram_total = 0u64;
+    let mut ram_used = 0u64;
+
+    if let Ok(output) = Command::new("sysctl").arg("hw.memsize").output() {
+        let mem = String::from_utf8_lossy(&output.stdout);
+        if let Some(size) = mem.split(':').nth(1) {
+            if let Ok(bytes) = size.trim().parse::<u64>() {
+                ram_total = bytes / 1024 / 1024;
             }
         }
+    }
 
-        if let Ok(output) = Command::new("vm_stat").output() {
-            let vm_output = String::from_utf8_lossy(&output.stdout);
-            let mut active = 0u64;
-            let mut wired = 0u64;
-            let mut compressed = 0u64;
-
-            for line in vm_output.lines() {
-                if let Some(val) = line.split_whitespace().last() {
-                    let val = val.trim_end_matches('.');
-                    if let Ok(pages) = val.parse::<u64>() {
-                        if line.contains("Pages active:") {
-                            active = pages;
-                        } else if line.contains("Pages wired down:") {
-                            wired = pages;
-                        } else if line.contains("Pages occupied by compressor:") {
-                            compressed = pages;
-                        }
+    if let Ok(output) = Command::new("vm_stat").output() {
+        let vm_output = String::from_utf8_lossy(&output.stdout);
+        let mut active = 0u64;
+        let mut wired = 0u64;
+        let mut compressed = 0u64;
+        let mut free = 0u64;
+
+        for line in vm_output.lines() {
+            if let Some(val) = line.split_whitespace().last() {
+                let val = val.trim_end_matches('.');
+                if let Ok(pages) = val.parse::<u64>() {
+                    if line.contains("Pages active:") {
+                        active = pages;
+                    } else if line.contains("Pages wired down:") {
+                        wired = pages;
+                    } else if line.contains("Pages occupied by compressor:") {
+                        compressed = pages;
+                    } else if line.contains("Pages free:") {
+                        free = pages;
                     }
                 }
             }
-
-            let page_size = 4096u64;
-            self.ram_used = (active + wired + compressed) * page_size / 1024 / 1024;
         }
-        return;
+
+        let page_size = 4096u64;
+        ram_used = match mode {
+            MemoryMode::Free => ram_total.saturating_sub(free * page_size / 1024 / 1024),
+            MemoryMode::Available | MemoryMode::Classic => {
+                (active + wired + compressed) * page_size / 1024 / 1024
+            }
+        };
     }
+    (ram_total, ram_used)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn get_mem(_mode: MemoryMode) -> (u64, u64) {
+    (0, 0)
 }
@@ -0,0 +1,34 @@
+//! Whether uwufetch writes ANSI colors, decided in one place for the
+//! renderer, the logo previews, `--diff` and `history`. Follows NO_COLOR
+//! (https://no-color.org) and CLICOLOR/CLICOLOR_FORCE
+//! (https://bixense.com/clicolors).
+
+use crate::display::strip_ansi;
+use std::env;
+use std::io::{self, Write};
+
+/// Whether to color output going to a terminal if `tty`. In order:
+/// `--force-color` (`force`) and a CLICOLOR_FORCE other than 0 turn colors
+/// on even when piped, so `uwufetch | lolcat` works; NO_COLOR and
+/// `CLICOLOR=0` turn them off; otherwise they're on exactly on a terminal.
+pub fn enabled(force: bool, tty: bool) -> bool {
+    let var = |name| env::var(name).ok().filter(|value| !value.is_empty());
+    if force || var("CLICOLOR_FORCE").is_some_and(|value| value != "0") {
+        return true;
+    }
+    if var("NO_COLOR").is_some() || var("CLICOLOR").as_deref() == Some("0") {
+        return false;
+    }
+    tty
+}
+
+/// Writes `out` to stdout, with its ANSI escapes stripped unless `color`.
+pub fn write_stdout(out: &[u8], color: bool) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    if color {
+        stdout.write_all(out)?;
+    } else {
+        stdout.write_all(strip_ansi(&String::from_utf8_lossy(out)).as_bytes())?;
+    }
+    stdout.flush()
+}
@@ -1,110 +1,1091 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use crate::filter::Filter;
+use crate::info::{home_dir, Chassis};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const DEFAULT_CONFIG: &str = r#"# uwufetch configuration
+#
+# Every key is listed with its default value. Uncomment a line to change it.
+# Booleans accept true/false.
+
+## Logo
+
+# Override the detected distro logo (see `uwufetch --list`)
+#distro = arch
+
+# Print a sixel image instead of the ASCII logo, by name or path.
+# Images named ~/.config/uwufetch/images/<distro>.sixel replace the
+# built-in ones.
+#image = ~/Pictures/logo.sixel
+
+## Fields
+
+# user@host header line
+#user = true
+# Use the pretty hostname (hostnamectl --pretty, or the macOS computer
+# name) instead of the kernel hostname in the header
+#pretty_hostname = false
+#os = true
+# Host model line (MOWODEL)
+#host = true
+# Nerd Font chassis icon (laptop, desktop, server, ...) before the model
+#chassis_icon = false
+# System serial number and asset tag, masked unless reveal_serial is true
+#serial = false
+#reveal_serial = false
+#kernel = true
+# Append "(up to date)" or "(newer available)" to the kernel, compared with
+# kernel.org's stable and longterm releases. Linux only, and needs the
+# network-fields build feature; the release list is fetched with curl at
+# most once a day.
+#kernel_check = false
+# Notable boot parameters from /proc/cmdline (mitigations, zswap, huge
+# pages, IOMMU, CPU isolation)
+#kernel_params = false
+# Bootloader (GRUB, systemd-boot, rEFInd, Limine), from EFI variables or /boot
+#bootloader = false
+# Preemption model, tick rate and scheduler, from /proc/version and the
+# kernel config (/proc/config.gz or /boot/config-*)
+#sched = false
+#cpu = true
+# CPU microcode revision
+#microcode = false
+# Summary of /sys/devices/system/cpu/vulnerabilities
+#cpu_vulns = false
+# Hardware virtualization support (VT-x/AMD-V)
+#virt = false
+# The VM or container uwufetch is running in (KVM, VirtualBox, Docker,
+# LXC), which is also added to the host model (ENVIWON)
+#virtualization = false
+#gpus = true
+# Which GPU is listed first as GPUWU 1: auto (the one the firmware booted
+# with) | igpu | dgpu
+#primary_gpu = auto
+# Only show these GPUs, by their GPUWU number. Repeat the line for several;
+# a single `gpu = 1` shows just the primary GPU.
+#gpu = 1
+# Put several GPUs on one line ("2 GPUwUs: A + B") instead of one line each
+#gpu_summary = false
+# Each GPU's driver and its version (NVIDIA's, or Mesa's for the open drivers)
+#gpu_driver = false
+#ram = true
+# Each display's resolution and refresh rate, e.g. "2560x1440@165Hz,
+# 1920x1080@60Hz"
+#resolution = true
+# A SCWEEN line per display instead of one WESOWUTION line for all of them
+#resolution_per_display = false
+# Append the display scale factor to the resolution, e.g. "@ 2x"
+#scale = false
+# Append color depth and HDR status to the resolution
+#color_depth = false
+# Monitor model names from EDID (DISPWAYS)
+#displays = false
+# Show each monitor's native resolution next to its name
+#displays_resolution = false
+# Speed of each spinning fan, from hwmon or the Mac's SMC (FANWS)
+#fans = false
+# Battery charge and whether it's charging (BATTEWY), and whether the lid
+# is open (WID). Linux only. auto shows them on laptops, tablets and
+# convertibles and hides them on desktops, servers and VMs.
+#battery = auto
+#lid = auto
+#shell = true
+# Desktop environment, from XDG_CURRENT_DESKTOP or the running session
+#de = false
+# Wayland, X11 or TTY, after the desktop environment (or on its own line
+# when there isn't one)
+#display_server = false
+# Terminal emulator, found by walking the process tree, and any
+# multiplexer (tmux, screen, zellij) in between
+#terminal = false
+# The tmux, screen or zellij session and its window count (MUWUX)
+#mux = false
+# The primary network interface's IPv4 and IPv6 addresses (WOCAW IP).
+# Bridges, VPN tunnels and other virtual interfaces are skipped unless
+# local_ip_virtual is on.
+#local_ip = false
+#local_ip_virtual = false
+# The connected Wi-Fi network and its signal strength
+#wifi = false
+# Sound server (PipeWire, PulseAudio, ALSA, CoreAudio, WASAPI) and the
+# default output device
+#audio = false
+# The track a media player is playing right now
+#now_playing = false
+# Installed Steam games, their size on disk and the Proton version
+#steam = false
+# The dotfiles manager (chezmoi, yadm, a git repo at ~/.dotfiles) and
+# whether it has uncommitted changes
+#dotfiles = false
+# The X cursor theme and size
+#cursor = false
+# The rustc and cargo versions and rustup's default toolchain
+#rust = false
+#pkgs = true
+# Pending updates from checkupdates, apt or dnf, rechecked every 6 hours.
+# Needs the network-fields build feature.
+#updates = false
+#uptime = true
+# Compare the current uptime against the best one ever seen
+#uptime_record = false
+# How long the current login session has been active (SESSIOWN)
+#session_uptime = false
+#colors = true
+
+# Palette of the colors strip:
+#   classic | trans | lesbian | bi | nonbinary | rainbow
+#colors_style = classic
+# Draw a pride palette as a small flag, one stripe per line
+#colors_flag = false
+# Colors of the classic strip, by name (black, red, green, yellow, blue,
+# magenta, cyan, white) or as #rrggbb
+#colors_palette = black,red,green,yellow,blue,magenta,cyan,white
+# What each color is drawn with
+#colors_block = ██
+# 1 or 2. With 2 rows the palette is split in half, or repeated in its
+# bright variants when colors_bright is on
+#colors_rows = 1
+# Use the bright variants of the palette
+#colors_bright = false
+
+## Formatting
+
+# Terminal background: auto | dark | light. Light swaps black and white in
+# the logos and darkens their pale colors. auto asks the terminal and
+# falls back to dark when it doesn't answer.
+#theme = auto
+
+# How used memory is computed: available | free | classic
+#   available: total - MemAvailable
+#   free:      total - MemFree
+#   classic:   total - (MemFree + Buffers + Cached)
+#memory_mode = available
+
+# compact ("3d, 4h, 12m") | long ("3 days, 4 hours, 12 minutes")
+# | seconds | clock (boot timestamp)
+#uptime_format = compact
+
+# Nerd Font glyphs for the field labels: false | true (glyphs only) | both.
+# Falls back to text on the Linux console and in non-UTF-8 locales.
+#icons = false
+
+# Icon before user@host: false | emoji (OS family, e.g. 🐧) | glyph
+# (the distro's Nerd Font logo)
+#header_icon = false
+
+# Box around the info rows, with user@host as its title:
+# none | rounded | ascii | double
+#border = none
+
+# Uwufy the user@host line too (r/l -> w, n + vowel -> ny)
+#uwu_userhost = false
+
+# Strip (R), (TM), "CPU", clock speed and core-count suffixes from the CPU name
+#clean_cpu = false
+
+# Append the core/thread count ("(8c/16t)") and the highest clock speed
+# ("@ 4.7GHz") to the CPU line
+#cpu_cores = false
+#cpu_freq = false
+
+# Draw the logo and fast fields at once and fill in GPUs and packages as
+# they're detected. Only applies to fresh runs in a terminal.
+#progressive = false
+
+# sed-style substitutions applied to a field before uwufication.
+# Fields: user, hostname, host, kernel, cpu, gpu, shell, pkgs. Flags: g, i
+#filter.cpu = "s/ with Radeon Graphics//"
+
+## Profiles
+#
+# Sections named [profile.<name>] override any of the keys above when
+# selected with `uwufetch -p <name>`.
+#
+#[profile.minimal]
+#gpus = false
+#pkgs = false
+#colors = false
+#
+# Sections named [when.<condition>] are applied automatically when the
+# condition holds, before any selected profile. Conditions:
+#   term=<name>  $TERM, its suffix (xterm-kitty -> kitty) or $TERM_PROGRAM
+#   ssh=true     running over SSH
+#
+#[when.term=linux]
+#chassis_icon = false
+
+## Misc
+
+# Append each run's kernel, package count and memory to
+# ~/.cache/uwufetch.history (see `uwufetch history`)
+#history = false
+
+# off: only -r and -w touch ~/.cache/uwufetch.cache. auto: read it when
+# it's younger than cache_ttl seconds, otherwise detect everything and
+# write it, as if -r or -w had been given.
+#cache = off
+#cache_ttl = 86400
+
+# Give up on a probe that takes longer than this many milliseconds and
+# leave its field empty, to bound startup on slow disks or NFS homes. 0 is
+# no limit. probe_timeout_ms.<field> overrides it for one field: user, os,
+# kernel, kernel_check, host, serial, cpu, cpu_vulns, kernel_params,
+# bootloader, sched, virt, virtualization, gpus, gpu_driver, resolution,
+# scale, color_depth, displays, ram, pkgs, updates, shell, de, terminal,
+# uptime, session_uptime, fans, battery, lid, mux, local_ip, wifi, audio,
+# now_playing, steam, dotfiles, cursor or rust.
+#probe_timeout_ms = 0
+#probe_timeout_ms.pkgs = 500
+
+# After the output, name the tools an enabled field needed and couldn't
+# find (iw, pactl, xrandr, ...), on stderr
+#tool_hints = true
+"#;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MemoryMode {
+    // used = total - MemAvailable, matching `free`'s "used" column
+    #[default]
+    Available,
+    // used = total - MemFree, counting page cache as used
+    Free,
+    // used = total - (MemFree + Buffers + Cached), like older fetch tools
+    Classic,
+}
+
+impl MemoryMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "available" => Some(MemoryMode::Available),
+            "free" => Some(MemoryMode::Free),
+            "classic" => Some(MemoryMode::Classic),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UptimeFormat {
+    #[default]
+    Compact,
+    Long,
+    Seconds,
+    Clock,
+}
+
+impl UptimeFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "compact" => Some(UptimeFormat::Compact),
+            "long" => Some(UptimeFormat::Long),
+            "seconds" => Some(UptimeFormat::Seconds),
+            "clock" => Some(UptimeFormat::Clock),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorsStyle {
+    #[default]
+    Classic,
+    Trans,
+    Lesbian,
+    Bi,
+    Nonbinary,
+    Rainbow,
+}
+
+impl ColorsStyle {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "classic" => Some(ColorsStyle::Classic),
+            "trans" => Some(ColorsStyle::Trans),
+            "lesbian" => Some(ColorsStyle::Lesbian),
+            "bi" => Some(ColorsStyle::Bi),
+            "nonbinary" => Some(ColorsStyle::Nonbinary),
+            "rainbow" => Some(ColorsStyle::Rainbow),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IconMode {
+    #[default]
+    Off,
+    // Nerd Font glyphs instead of the text labels
+    Icons,
+    // Glyphs followed by the text labels
+    Both,
+}
+
+impl IconMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "false" => Some(IconMode::Off),
+            "true" => Some(IconMode::Icons),
+            "both" => Some(IconMode::Both),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrimaryGpu {
+    // The GPU the firmware booted with
+    #[default]
+    Auto,
+    Igpu,
+    Dgpu,
+}
+
+impl PrimaryGpu {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(PrimaryGpu::Auto),
+            "igpu" => Some(PrimaryGpu::Igpu),
+            "dgpu" => Some(PrimaryGpu::Dgpu),
+            _ => None,
+        }
+    }
+}
+
+/// A field that's on, off, or left to the chassis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Toggle {
+    // On laptops and other portables, off on desktops and servers
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+impl Toggle {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(Toggle::Auto),
+            "true" => Some(Toggle::On),
+            "false" => Some(Toggle::Off),
+            _ => None,
+        }
+    }
+
+    /// Whether the field is shown on `chassis`. An unknown chassis shows
+    /// it, since it's left out anyway when nothing was found.
+    pub fn shows(self, chassis: Chassis) -> bool {
+        match self {
+            Toggle::Auto => chassis.portable().unwrap_or(true),
+            Toggle::On => true,
+            Toggle::Off => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Theme {
+    // Ask the terminal for its background color
+    #[default]
+    Auto,
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(Theme::Auto),
+            "dark" => Some(Theme::Dark),
+            "light" => Some(Theme::Light),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Border {
+    #[default]
+    None,
+    Rounded,
+    Ascii,
+    Double,
+}
+
+impl Border {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "none" | "false" => Some(Border::None),
+            "rounded" => Some(Border::Rounded),
+            "ascii" => Some(Border::Ascii),
+            "double" => Some(Border::Double),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeaderIcon {
+    #[default]
+    Off,
+    Emoji,
+    Glyph,
+}
+
+impl HeaderIcon {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "false" => Some(HeaderIcon::Off),
+            "emoji" => Some(HeaderIcon::Emoji),
+            "glyph" => Some(HeaderIcon::Glyph),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteColor {
+    // One of the 8 basic terminal colors, by index
+    Ansi(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl PaletteColor {
+    const NAMES: [&'static str; 8] = [
+        "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Some(hex) = value.strip_prefix('#') {
+            if hex.len() != 6 {
+                return None;
+            }
+            let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+            return Some(PaletteColor::Rgb(channel(0)?, channel(2)?, channel(4)?));
+        }
+        Self::NAMES
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(value))
+            .map(|i| PaletteColor::Ansi(i as u8))
+    }
+
+    pub fn classic() -> Vec<Self> {
+        (0..8).map(PaletteColor::Ansi).collect()
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Configuration {
     pub show_user: bool,
+    pub pretty_hostname: bool,
     pub show_os: bool,
     pub show_host: bool,
+    pub show_chassis_icon: bool,
+    pub show_serial: bool,
+    pub reveal_serial: bool,
     pub show_kernel: bool,
+    pub show_kernel_params: bool,
+    pub kernel_check: bool,
+    pub show_bootloader: bool,
+    pub show_sched: bool,
     pub show_cpu: bool,
+    pub show_microcode: bool,
+    pub show_cpu_vulns: bool,
+    pub show_virt: bool,
+    pub show_virtualization: bool,
     pub show_gpu: bool,
+    pub show_gpu_driver: bool,
     pub show_ram: bool,
     pub show_resolution: bool,
+    pub show_scale: bool,
+    pub show_color_depth: bool,
+    pub show_displays: bool,
+    pub show_displays_resolution: bool,
+    pub show_fans: bool,
+    pub show_battery: Toggle,
+    pub show_lid: Toggle,
     pub show_shell: bool,
+    pub show_de: bool,
+    pub show_display_server: bool,
+    pub show_terminal: bool,
+    pub show_mux: bool,
+    pub show_local_ip: bool,
+    pub show_wifi: bool,
+    pub show_audio: bool,
+    pub show_now_playing: bool,
+    pub show_steam: bool,
+    pub show_dotfiles: bool,
+    pub show_cursor: bool,
+    pub show_rust: bool,
+    pub local_ip_virtual: bool,
     pub show_pkgs: bool,
+    pub show_updates: bool,
     pub show_uptime: bool,
+    pub show_uptime_record: bool,
+    pub show_session_uptime: bool,
     pub show_colors: bool,
+    pub colors_style: ColorsStyle,
+    pub colors_flag: bool,
+    pub colors_palette: Vec<PaletteColor>,
+    pub colors_block: String,
+    pub colors_rows: usize,
+    pub colors_bright: bool,
     pub show_image: bool,
+    pub plain: bool,
+    pub color: bool,
+    pub theme: Theme,
+    pub light: bool,
+    pub icons: IconMode,
+    pub header_icon: HeaderIcon,
+    pub border: Border,
+    pub uwu_userhost: bool,
+    pub clean_cpu: bool,
+    pub cpu_cores: bool,
+    pub cpu_freq: bool,
+    pub record_history: bool,
+    pub auto_cache: bool,
+    pub cache_ttl: u64,
+    pub probe_timeout: Option<Duration>,
+    pub probe_timeouts: Vec<(String, Option<Duration>)>,
+    pub tool_hints: bool,
+    pub progressive: bool,
+    pub screenshot: bool,
+    pub memory_mode: MemoryMode,
+    pub uptime_format: UptimeFormat,
     pub gpu_indexes: Vec<usize>,
+    pub gpu_summary: bool,
+    pub resolution_per_display: bool,
+    pub primary_gpu: PrimaryGpu,
+    pub filters: Vec<(String, Filter)>,
+    pub asset_dir: Option<PathBuf>,
 }
 
 impl Default for Configuration {
     fn default() -> Self {
         Configuration {
             show_user: true,
+            pretty_hostname: false,
             show_os: true,
             show_host: true,
+            show_chassis_icon: false,
+            show_serial: false,
+            reveal_serial: false,
             show_kernel: true,
+            show_kernel_params: false,
+            kernel_check: false,
+            show_bootloader: false,
+            show_sched: false,
             show_cpu: true,
+            show_microcode: false,
+            show_cpu_vulns: false,
+            show_virt: false,
+            show_virtualization: false,
             show_gpu: true,
+            show_gpu_driver: false,
             show_ram: true,
             show_resolution: true,
+            show_scale: false,
+            show_color_depth: false,
+            show_displays: false,
+            show_displays_resolution: false,
+            show_fans: false,
+            show_battery: Toggle::Auto,
+            show_lid: Toggle::Auto,
             show_shell: true,
+            show_de: false,
+            show_display_server: false,
+            show_terminal: false,
+            show_mux: false,
+            show_local_ip: false,
+            show_wifi: false,
+            show_audio: false,
+            show_now_playing: false,
+            show_steam: false,
+            show_dotfiles: false,
+            show_cursor: false,
+            show_rust: false,
+            local_ip_virtual: false,
             show_pkgs: true,
+            show_updates: false,
             show_uptime: true,
+            show_uptime_record: false,
+            show_session_uptime: false,
             show_colors: true,
+            colors_style: ColorsStyle::default(),
+            colors_flag: false,
+            colors_palette: PaletteColor::classic(),
+            colors_block: "██".to_string(),
+            colors_rows: 1,
+            colors_bright: false,
             show_image: false,
+            plain: false,
+            color: true,
+            theme: Theme::default(),
+            light: false,
+            icons: IconMode::default(),
+            header_icon: HeaderIcon::default(),
+            border: Border::default(),
+            uwu_userhost: false,
+            clean_cpu: false,
+            cpu_cores: false,
+            cpu_freq: false,
+            record_history: false,
+            auto_cache: false,
+            cache_ttl: 86400,
+            probe_timeout: None,
+            probe_timeouts: vec![],
+            tool_hints: true,
+            progressive: false,
+            screenshot: false,
+            memory_mode: MemoryMode::default(),
+            uptime_format: UptimeFormat::default(),
             gpu_indexes: vec![],
+            gpu_summary: false,
+            resolution_per_display: false,
+            primary_gpu: PrimaryGpu::default(),
+            filters: vec![],
+            asset_dir: None,
         }
     }
 }
 
 impl Configuration {
-    pub fn parse_config() -> (Self, Option<String>, Option<String>) {
+    /// Reads the config from `path` (a file, a directory holding `config`
+    /// and custom assets, or `-` for stdin), or from the usual search
+    /// locations when no path is given. Only an explicit path that doesn't
+    /// exist is an error.
+    pub fn parse_config(
+        path: Option<&str>,
+        profile: Option<&str>,
+    ) -> io::Result<(Self, Option<String>, Option<String>)> {
         let mut config = Configuration::default();
         let mut distro_override: Option<String> = None;
         let mut image_override: Option<String> = None;
 
-        let config_path = Self::find_config_file();
-        if let Some(path) = config_path {
-            if let Ok(file) = File::open(path) {
-                let reader = BufReader::new(file);
+        let content = match path {
+            Some("-") => {
+                let mut content = String::new();
+                io::stdin().read_to_string(&mut content)?;
+                Some(content)
+            }
+            Some(path) => {
+                let path = Path::new(path);
+                if path.is_dir() {
+                    config.asset_dir = Some(path.to_path_buf());
+                    fs::read_to_string(path.join("config")).ok()
+                } else {
+                    Some(fs::read_to_string(path).map_err(|e| {
+                        io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+                    })?)
+                }
+            }
+            None => Self::find_config_file().and_then(|path| fs::read_to_string(path).ok()),
+        };
 
-                for line in reader.lines().map_while(Result::ok) {
-                    let line = line.trim();
-                    if line.is_empty() || line.starts_with('#') {
-                        continue;
-                    }
+        if let Some(content) = content {
+            let profile_section = profile.map(|name| format!("profile.{}", name));
+            let mut section: Option<String> = None;
+            let mut profile_found = false;
+            let mut base = Vec::new();
+            let mut conditional = Vec::new();
+            let mut selected = Vec::new();
+
+            for line in content.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+
+                if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                    let header = header.trim().to_string();
+                    profile_found |= profile_section.as_ref() == Some(&header);
+                    section = Some(header);
+                    continue;
+                }
+
+                if let Some((key, value)) = line.split_once('=') {
+                    let entry = (
+                        key.trim().to_string(),
+                        value.trim().trim_matches('"').to_string(),
+                    );
 
-                    if let Some((key, value)) = line.split_once('=') {
-                        let key = key.trim();
-                        let value = value.trim().trim_matches('"');
-
-                        match key {
-                            "distro" => {
-                                distro_override = Some(value.to_string());
-                            }
-                            "image" => {
-                                let mut image_path = value.to_string();
-                                if image_path.starts_with('~') {
-                                    if let Ok(home) = std::env::var("HOME") {
-                                        image_path = image_path.replacen('~', &home, 1);
-                                    }
-                                }
-                                image_override = Some(image_path);
-                                config.show_image = true;
-                            }
-                            "user" => config.show_user = value == "true",
-                            "os" => config.show_os = value != "false",
-                            "host" => config.show_host = value != "false",
-                            "kernel" => config.show_kernel = value != "false",
-                            "cpu" => config.show_cpu = value != "false",
-                            "gpu" => {
-                                if let Ok(idx) = value.parse::<usize>() {
-                                    config.gpu_indexes.push(idx);
-                                }
-                            }
-                            "gpus" => config.show_gpu = value != "false",
-                            "ram" => config.show_ram = value != "false",
-                            "resolution" => config.show_resolution = value != "false",
-                            "shell" => config.show_shell = value != "false",
-                            "pkgs" => config.show_pkgs = value != "false",
-                            "uptime" => config.show_uptime = value != "false",
-                            "colors" => config.show_colors = value != "false",
-                            _ => {}
+                    if section.is_none() {
+                        base.push(entry);
+                    } else if section == profile_section {
+                        selected.push(entry);
+                    } else if let Some(cond) =
+                        section.as_deref().and_then(|s| s.strip_prefix("when."))
+                    {
+                        if Self::condition_matches(cond) {
+                            conditional.push(entry);
                         }
                     }
                 }
             }
+
+            for (key, value) in base.iter().chain(&conditional).chain(&selected) {
+                config.apply(key, value, &mut distro_override, &mut image_override);
+            }
+
+            if let Some(name) = profile {
+                if !profile_found {
+                    eprintln!("uwufetch: profile `{}` not found in config", name);
+                }
+            }
+        }
+
+        Ok((config, distro_override, image_override))
+    }
+
+    /// The file `parse_config` reads for the same `path`, if any.
+    pub fn config_file(path: Option<&str>) -> Option<PathBuf> {
+        match path {
+            Some("-") => None,
+            Some(path) if Path::new(path).is_dir() => Some(Path::new(path).join("config")),
+            Some(path) => Some(PathBuf::from(path)),
+            None => Self::find_config_file(),
+        }
+    }
+
+    pub fn apply_minimal_preset(&mut self) {
+        self.show_user = true;
+        self.show_os = true;
+        self.show_host = false;
+        self.show_chassis_icon = false;
+        self.show_serial = false;
+        self.show_kernel = true;
+        self.show_kernel_params = false;
+        self.show_bootloader = false;
+        self.show_sched = false;
+        self.show_cpu = false;
+        self.show_microcode = false;
+        self.show_cpu_vulns = false;
+        self.show_virt = false;
+        self.show_virtualization = false;
+        self.show_gpu = false;
+        self.show_gpu_driver = false;
+        self.show_ram = true;
+        self.show_resolution = false;
+        self.show_displays = false;
+        self.show_fans = false;
+        self.show_battery = Toggle::Off;
+        self.show_lid = Toggle::Off;
+        self.show_shell = false;
+        self.show_de = false;
+        self.show_display_server = false;
+        self.show_terminal = false;
+        self.show_mux = false;
+        self.show_local_ip = false;
+        self.show_wifi = false;
+        self.show_audio = false;
+        self.show_now_playing = false;
+        self.show_steam = false;
+        self.show_dotfiles = false;
+        self.show_cursor = false;
+        self.show_rust = false;
+        self.show_pkgs = false;
+        self.show_updates = false;
+        self.show_uptime = true;
+        self.show_uptime_record = false;
+        self.show_session_uptime = false;
+        self.show_colors = false;
+    }
+
+    pub fn apply_all_preset(&mut self) {
+        self.show_user = true;
+        self.show_os = true;
+        self.show_host = true;
+        self.show_serial = true;
+        self.show_kernel = true;
+        self.show_kernel_params = true;
+        self.show_bootloader = true;
+        self.show_sched = true;
+        self.show_cpu = true;
+        self.show_microcode = true;
+        self.show_cpu_vulns = true;
+        self.show_virt = true;
+        self.show_virtualization = true;
+        self.show_gpu = true;
+        self.show_gpu_driver = true;
+        self.show_ram = true;
+        self.show_resolution = true;
+        self.show_scale = true;
+        self.show_color_depth = true;
+        self.show_displays = true;
+        self.show_displays_resolution = true;
+        self.show_fans = true;
+        self.show_battery = Toggle::On;
+        self.show_lid = Toggle::On;
+        self.show_shell = true;
+        self.show_de = true;
+        self.show_display_server = true;
+        self.show_terminal = true;
+        self.show_mux = true;
+        self.show_local_ip = true;
+        self.show_wifi = true;
+        self.show_audio = true;
+        self.show_now_playing = true;
+        self.show_steam = true;
+        self.show_dotfiles = true;
+        self.show_cursor = true;
+        self.show_rust = true;
+        self.show_pkgs = true;
+        self.show_updates = true;
+        self.show_uptime = true;
+        self.show_uptime_record = true;
+        self.show_session_uptime = true;
+        self.show_colors = true;
+        self.show_chassis_icon = true;
+        self.kernel_check = true;
+    }
+
+    fn condition_matches(cond: &str) -> bool {
+        let (key, expected) = cond.split_once('=').unwrap_or((cond, "true"));
+        let expected = expected.trim().trim_matches('"');
+
+        match key.trim() {
+            "term" => Self::terminal_names().iter().any(|t| t == expected),
+            "ssh" => {
+                let ssh = std::env::var_os("SSH_CONNECTION").is_some()
+                    || std::env::var_os("SSH_TTY").is_some();
+                ssh == (expected == "true")
+            }
+            _ => false,
+        }
+    }
+
+    fn terminal_names() -> Vec<String> {
+        let mut names = Vec::new();
+        if let Ok(term) = std::env::var("TERM") {
+            if let Some((_, suffix)) = term.split_once('-') {
+                names.push(suffix.to_string());
+            }
+            names.push(term);
+        }
+        if let Ok(program) = std::env::var("TERM_PROGRAM") {
+            names.push(program.to_lowercase());
+        }
+        names
+    }
+
+    fn apply(
+        &mut self,
+        key: &str,
+        value: &str,
+        distro_override: &mut Option<String>,
+        image_override: &mut Option<String>,
+    ) {
+        match key {
+            "distro" => {
+                *distro_override = Some(value.to_string());
+            }
+            "image" => {
+                let mut image_path = value.to_string();
+                if image_path.starts_with('~') {
+                    if let Some(home) = home_dir() {
+                        image_path = image_path.replacen('~', &home, 1);
+                    }
+                }
+                *image_override = Some(image_path);
+                self.show_image = true;
+            }
+            "user" => self.show_user = value == "true",
+            "pretty_hostname" => self.pretty_hostname = value == "true",
+            "os" => self.show_os = value != "false",
+            "host" => self.show_host = value != "false",
+            "chassis_icon" => self.show_chassis_icon = value == "true",
+            "serial" => self.show_serial = value == "true",
+            "reveal_serial" => self.reveal_serial = value == "true",
+            "kernel" => self.show_kernel = value != "false",
+            "kernel_params" => self.show_kernel_params = value == "true",
+            "kernel_check" => self.kernel_check = value == "true",
+            "bootloader" => self.show_bootloader = value == "true",
+            "sched" => self.show_sched = value == "true",
+            "cpu" => self.show_cpu = value != "false",
+            "microcode" => self.show_microcode = value == "true",
+            "cpu_vulns" => self.show_cpu_vulns = value == "true",
+            "virt" => self.show_virt = value == "true",
+            "virtualization" => self.show_virtualization = value == "true",
+            "gpu" => {
+                if let Ok(idx) = value.parse::<usize>() {
+                    self.gpu_indexes.push(idx);
+                }
+            }
+            "gpus" => self.show_gpu = value != "false",
+            "gpu_driver" => self.show_gpu_driver = value == "true",
+            "gpu_summary" => self.gpu_summary = value == "true",
+            "resolution_per_display" => self.resolution_per_display = value == "true",
+            "primary_gpu" => {
+                if let Some(primary) = PrimaryGpu::parse(value) {
+                    self.primary_gpu = primary;
+                }
+            }
+            "ram" => self.show_ram = value != "false",
+            "resolution" => self.show_resolution = value != "false",
+            "scale" => self.show_scale = value == "true",
+            "color_depth" => self.show_color_depth = value == "true",
+            "displays" => self.show_displays = value == "true",
+            "displays_resolution" => self.show_displays_resolution = value == "true",
+            "fans" => self.show_fans = value == "true",
+            "battery" => {
+                if let Some(battery) = Toggle::parse(value) {
+                    self.show_battery = battery;
+                }
+            }
+            "lid" => {
+                if let Some(lid) = Toggle::parse(value) {
+                    self.show_lid = lid;
+                }
+            }
+            "shell" => self.show_shell = value != "false",
+            "de" => self.show_de = value == "true",
+            "display_server" => self.show_display_server = value == "true",
+            "terminal" => self.show_terminal = value == "true",
+            "mux" => self.show_mux = value == "true",
+            "local_ip" => self.show_local_ip = value == "true",
+            "local_ip_virtual" => self.local_ip_virtual = value == "true",
+            "wifi" => self.show_wifi = value == "true",
+            "audio" => self.show_audio = value == "true",
+            "now_playing" => self.show_now_playing = value == "true",
+            "steam" => self.show_steam = value == "true",
+            "dotfiles" => self.show_dotfiles = value == "true",
+            "cursor" => self.show_cursor = value == "true",
+            "rust" => self.show_rust = value == "true",
+            "pkgs" => self.show_pkgs = value != "false",
+            "updates" => self.show_updates = value == "true",
+            "uptime" => self.show_uptime = value != "false",
+            "uptime_record" => self.show_uptime_record = value == "true",
+            "session_uptime" => self.show_session_uptime = value == "true",
+            "colors" => self.show_colors = value != "false",
+            "colors_style" => {
+                if let Some(style) = ColorsStyle::parse(value) {
+                    self.colors_style = style;
+                }
+            }
+            "colors_flag" => self.colors_flag = value == "true",
+            "colors_palette" => {
+                let palette: Option<Vec<_>> = value.split(',').map(PaletteColor::parse).collect();
+                if let Some(palette) = palette.filter(|p| !p.is_empty()) {
+                    self.colors_palette = palette;
+                }
+            }
+            "colors_block" => {
+                if !value.is_empty() {
+                    self.colors_block = value.to_string();
+                }
+            }
+            "colors_rows" => {
+                if let Ok(rows @ 1..=2) = value.parse() {
+                    self.colors_rows = rows;
+                }
+            }
+            "colors_bright" => self.colors_bright = value == "true",
+            "icons" => {
+                if let Some(mode) = IconMode::parse(value) {
+                    self.icons = mode;
+                }
+            }
+            "uwu_userhost" => self.uwu_userhost = value == "true",
+            "header_icon" => {
+                if let Some(icon) = HeaderIcon::parse(value) {
+                    self.header_icon = icon;
+                }
+            }
+            "border" => {
+                if let Some(border) = Border::parse(value) {
+                    self.border = border;
+                }
+            }
+            "theme" => {
+                if let Some(theme) = Theme::parse(value) {
+                    self.theme = theme;
+                }
+            }
+            "clean_cpu" => self.clean_cpu = value == "true",
+            "cpu_cores" => self.cpu_cores = value == "true",
+            "cpu_freq" => self.cpu_freq = value == "true",
+            "history" => self.record_history = value == "true",
+            "cache" => self.auto_cache = value == "auto",
+            "cache_ttl" => {
+                if let Ok(ttl) = value.parse() {
+                    self.cache_ttl = ttl;
+                }
+            }
+            "probe_timeout_ms" => {
+                if let Ok(ms) = value.parse() {
+                    self.probe_timeout = timeout_ms(ms);
+                }
+            }
+            "tool_hints" => self.tool_hints = value != "false",
+            "progressive" => self.progressive = value == "true",
+            "uptime_format" => {
+                if let Some(format) = UptimeFormat::parse(value) {
+                    self.uptime_format = format;
+                }
+            }
+            "memory_mode" => {
+                if let Some(mode) = MemoryMode::parse(value) {
+                    self.memory_mode = mode;
+                }
+            }
+            _ => {
+                if let Some(field) = key.strip_prefix("filter.") {
+                    if let Some(filter) = Filter::parse(value) {
+                        self.filters.push((field.trim().to_string(), filter));
+                    }
+                } else if let Some(field) = key.strip_prefix("probe_timeout_ms.") {
+                    let field = field.trim();
+                    if !PROBE_FIELDS.contains(&field) {
+                        eprintln!("uwufetch: unknown field `{}` in probe_timeout_ms", field);
+                    } else if let Ok(ms) = value.parse() {
+                        self.probe_timeouts
+                            .push((field.to_string(), timeout_ms(ms)));
+                    }
+                }
+            }
         }
+    }
 
-        (config, distro_override, image_override)
+    /// How long the probe behind a field may take before it's given up on:
+    /// its own `probe_timeout_ms.<field>`, else `probe_timeout_ms`. `None`
+    /// is no limit.
+    pub fn probe_timeout(&self, field: &str) -> Option<Duration> {
+        self.probe_timeouts
+            .iter()
+            .rev()
+            .find(|(name, _)| name == field)
+            .map_or(self.probe_timeout, |(_, timeout)| *timeout)
+    }
+
+    pub fn user_config_path() -> Option<PathBuf> {
+        home_dir().map(|home| PathBuf::from(home).join(".config/uwufetch/config"))
+    }
+
+    #[cfg(feature = "sixel")]
+    pub fn user_image_dir() -> Option<PathBuf> {
+        home_dir().map(|home| PathBuf::from(home).join(".config/uwufetch/images"))
+    }
+
+    pub fn write_default_config(path: &Path, force: bool) -> io::Result<()> {
+        if path.exists() && !force {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} already exists, use --force to overwrite it",
+                    path.display()
+                ),
+            ));
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, DEFAULT_CONFIG)
     }
 
     fn find_config_file() -> Option<PathBuf> {
-        if let Ok(home) = std::env::var("HOME") {
+        if let Some(home) = home_dir() {
             let user_config = PathBuf::from(home).join(".config/uwufetch/config");
             if user_config.exists() {
                 return Some(user_config);
@@ -126,3 +1107,78 @@ impl Configuration {
         None
     }
 }
+
+/// The fields `probe_timeout_ms.<field>` can name, one per probe.
+const PROBE_FIELDS: &[&str] = &[
+    "user",
+    "os",
+    "kernel",
+    "kernel_check",
+    "host",
+    "serial",
+    "cpu",
+    "cpu_vulns",
+    "kernel_params",
+    "bootloader",
+    "sched",
+    "virt",
+    "virtualization",
+    "gpus",
+    "gpu_driver",
+    "resolution",
+    "scale",
+    "color_depth",
+    "displays",
+    "ram",
+    "pkgs",
+    "updates",
+    "shell",
+    "de",
+    "terminal",
+    "uptime",
+    "session_uptime",
+    "fans",
+    "battery",
+    "lid",
+    "mux",
+    "local_ip",
+    "wifi",
+    "audio",
+    "now_playing",
+    "steam",
+    "dotfiles",
+    "cursor",
+    "rust",
+];
+
+/// A `probe_timeout_ms` value, where 0 means no limit.
+fn timeout_ms(ms: u64) -> Option<Duration> {
+    (ms > 0).then(|| Duration::from_millis(ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Configuration;
+
+    #[test]
+    fn all_preset_shows_every_field() {
+        let mut config = Configuration::default();
+        config.apply_all_preset();
+        assert!(config.kernel_check);
+
+        // Every show_* field, so one added without a line in the preset
+        // fails here. The image replaces the logo rather than adding a row.
+        let debug = format!("{:?}", config);
+        let hidden: Vec<&str> = debug
+            .split([',', '{'])
+            .map(str::trim)
+            .filter(|field| field.starts_with("show_") && !field.starts_with("show_image:"))
+            .filter(|field| {
+                [": false", ": Auto", ": Off"]
+                    .iter()
+                    .any(|v| field.ends_with(v))
+            })
+            .collect();
+        assert!(hidden.is_empty(), "not shown by --all: {:?}", hidden);
+    }
+}
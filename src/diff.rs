@@ -0,0 +1,51 @@
+use crate::color;
+use crate::info::SystemInfo;
+use owo_colors::OwoColorize;
+use std::io::{self, Write};
+
+fn fields(info: &SystemInfo) -> Vec<(&'static str, String)> {
+    vec![
+        ("USER     ", format!("{}@{}", info.user, info.host)),
+        ("OWOS     ", info.os_name.clone()),
+        ("MOWODEL  ", info.model.clone()),
+        ("KEWNEL   ", info.kernel.clone()),
+        ("CPUWU    ", info.cpu_model.clone()),
+        ("GPUWU    ", info.gpu_models.join(", ")),
+        ("MEMOWY   ", format!("{} MiB", info.ram_total)),
+        (
+            "WESOWUTION",
+            info.displays
+                .iter()
+                .map(|d| format!("{}x{}", d.width, d.height))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        ("SHEWW    ", info.shell.clone()),
+        ("PKGS     ", format!("{}: {}", info.pkgs, info.pkgman_name)),
+    ]
+}
+
+pub fn print_diff(cached: &SystemInfo, current: &SystemInfo, color: bool) -> io::Result<()> {
+    let mut out = Vec::new();
+    let mut changes = 0;
+
+    for ((label, old), (_, new)) in fields(cached).into_iter().zip(fields(current)) {
+        if old == new {
+            continue;
+        }
+        changes += 1;
+        writeln!(
+            &mut out,
+            "{} {} -> {}",
+            label.bold(),
+            old.red(),
+            new.green().bold()
+        )?;
+    }
+
+    if changes == 0 {
+        writeln!(&mut out, "Nothing changed since the cached snapshot")?;
+    }
+
+    color::write_stdout(&out, color)
+}
@@ -1,9 +1,14 @@
-use crate::assets::Assets;
-use crate::config::Configuration;
+use crate::assets::{self, Assets, Family};
+use crate::color;
+use crate::config::{
+    Border, ColorsStyle, Configuration, HeaderIcon, IconMode, PaletteColor, UptimeFormat,
+};
 use crate::info::SystemInfo;
+use crate::theme;
 use crate::uwufy;
 use owo_colors::{AnsiColors, OwoColorize, Rgb, Style};
-use std::io::{self, BufWriter, Write};
+use std::fmt;
+use std::io::{self, Write};
 
 const BLOCK_CHAR: &str = "█";
 
@@ -26,6 +31,35 @@ const TOK_BG_GREEN: &str = "BACKGROUND_GREEN";
 const TOK_BG_RED: &str = "BACKGROUND_RED";
 const TOK_BG_WHITE: &str = "BACKGROUND_WHITE";
 
+/// Every token `write_ascii` understands.
+const TOKENS: &[&str] = &[
+    TOK_NORMAL,
+    TOK_BOLD,
+    TOK_BLACK,
+    TOK_RED,
+    TOK_GREEN,
+    TOK_SPRING_GREEN,
+    TOK_YELLOW,
+    TOK_BLUE,
+    TOK_MAGENTA,
+    TOK_CYAN,
+    TOK_WHITE,
+    TOK_PINK,
+    TOK_LPINK,
+    TOK_BLOCK,
+    TOK_BLOCK_VERT,
+    TOK_BG_GREEN,
+    TOK_BG_RED,
+    TOK_BG_WHITE,
+];
+
+/// The info block starts this many columns in, so a wider logo runs into
+/// it.
+pub const LOGO_MAX_WIDTH: usize = 18;
+/// About as many rows as the default info block; a taller logo leaves a
+/// gap under it.
+pub const LOGO_MAX_HEIGHT: usize = 10;
+
 #[derive(Clone, Copy)]
 enum ColorSpec {
     Ansi(AnsiColors),
@@ -37,305 +71,1514 @@ struct StyleState {
     bold: bool,
     fg: Option<ColorSpec>,
     bg: Option<ColorSpec>,
+    light: bool,
+}
+
+/// Keeps logo colors readable on a light background: black and white
+/// trade places and pale colors get darker.
+fn for_light_background(color: ColorSpec) -> ColorSpec {
+    match color {
+        ColorSpec::Ansi(AnsiColors::White) => ColorSpec::Ansi(AnsiColors::Black),
+        ColorSpec::Ansi(AnsiColors::Black) => ColorSpec::Ansi(AnsiColors::White),
+        ColorSpec::Ansi(AnsiColors::Yellow) => ColorSpec::Rgb(184, 134, 11),
+        ColorSpec::Ansi(AnsiColors::Cyan) => ColorSpec::Rgb(0, 139, 139),
+        ColorSpec::Rgb(r, g, b) => {
+            let luminance = 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32;
+            if luminance > 160.0 {
+                let darken = |c: u8| (c as f32 * 0.6) as u8;
+                ColorSpec::Rgb(darken(r), darken(g), darken(b))
+            } else {
+                color
+            }
+        }
+        other => other,
+    }
 }
 
-fn apply_style(s: &str, st: StyleState) -> String {
+/// Writes `s` wrapped in the escape codes for `st`, straight into `out`.
+fn write_styled(out: &mut impl fmt::Write, s: &str, st: StyleState) -> fmt::Result {
     if s.is_empty() {
-        return String::new();
+        return Ok(());
     }
+    let themed = |color: ColorSpec| {
+        if st.light {
+            for_light_background(color)
+        } else {
+            color
+        }
+    };
     let mut style = Style::new();
     if st.bold {
         style = style.bold();
     }
-    if let Some(fg) = st.fg {
+    if let Some(fg) = st.fg.map(themed) {
         style = match fg {
             ColorSpec::Ansi(c) => style.color(c),
             ColorSpec::Rgb(r, g, b) => style.color(Rgb(r, g, b)),
         };
     }
-    if let Some(bg) = st.bg {
+    if let Some(bg) = st.bg.map(themed) {
         style = match bg {
             ColorSpec::Ansi(c) => style.on_color(c),
             ColorSpec::Rgb(r, g, b) => style.on_color(Rgb(r, g, b)),
         };
     }
-    format!("{}", s.style(style))
+    write!(out, "{}", s.style(style))
 }
 
-fn render_ascii(content: &str) -> String {
-    let mut out = String::new();
-    let mut st = StyleState::default();
+pub fn render_ascii(content: &str) -> String {
+    render_ascii_themed(content, false)
+}
+
+/// Renders a logo for a light (`light = true`) or dark background.
+pub fn render_ascii_themed(content: &str, light: bool) -> String {
+    let mut out = String::with_capacity(content.len() * 2);
+    // Writing to a String can't fail.
+    let _ = write_ascii(&mut out, content, light);
+    out
+}
+
+/// Renders a logo into `out` as it's parsed, one styled segment at a
+/// time, without building any intermediate strings.
+pub fn write_ascii(out: &mut impl fmt::Write, content: &str, light: bool) -> fmt::Result {
+    let mut st = StyleState {
+        light,
+        ..Default::default()
+    };
     let mut rest = content;
 
     while let Some(start) = rest.find('{') {
-        let before = &rest[..start];
-        out.push_str(&apply_style(before, st));
+        write_styled(out, &rest[..start], st)?;
 
         let after_brace = &rest[start + 1..];
-        if let Some(end_rel) = after_brace.find('}') {
-            let token = &after_brace[..end_rel];
-            rest = &after_brace[end_rel + 1..];
+        let Some(end_rel) = after_brace.find('}') else {
+            return write_styled(out, &rest[start..], st);
+        };
+        let token = &after_brace[..end_rel];
+        rest = &after_brace[end_rel + 1..];
 
-            match token {
-                TOK_NORMAL => {
-                    st = StyleState::default();
-                }
-                TOK_BOLD => {
-                    st.bold = true;
-                }
-                TOK_BLACK => st.fg = Some(ColorSpec::Ansi(AnsiColors::Black)),
-                TOK_RED => st.fg = Some(ColorSpec::Ansi(AnsiColors::Red)),
-                TOK_GREEN => st.fg = Some(ColorSpec::Ansi(AnsiColors::Green)),
-                TOK_SPRING_GREEN => {
-                    st.fg = Some(ColorSpec::Rgb(0, 255, 127));
-                }
-                TOK_YELLOW => st.fg = Some(ColorSpec::Ansi(AnsiColors::Yellow)),
-                TOK_BLUE => st.fg = Some(ColorSpec::Ansi(AnsiColors::Blue)),
-                TOK_MAGENTA => st.fg = Some(ColorSpec::Ansi(AnsiColors::Magenta)),
-                TOK_CYAN => st.fg = Some(ColorSpec::Ansi(AnsiColors::Cyan)),
-                TOK_WHITE => st.fg = Some(ColorSpec::Ansi(AnsiColors::White)),
-                TOK_PINK => {
-                    st.fg = Some(ColorSpec::Rgb(255, 105, 180));
-                }
-                TOK_LPINK => {
-                    st.fg = Some(ColorSpec::Rgb(255, 182, 193));
-                }
-                TOK_BG_GREEN => {
-                    st.bg = Some(ColorSpec::Ansi(AnsiColors::Green));
-                }
-                TOK_BG_RED => {
-                    st.bg = Some(ColorSpec::Ansi(AnsiColors::Red));
-                }
-                TOK_BG_WHITE => {
-                    st.bg = Some(ColorSpec::Ansi(AnsiColors::White));
-                }
-                TOK_BLOCK | TOK_BLOCK_VERT => {
-                    out.push_str(&apply_style(BLOCK_CHAR, st));
-                }
-                _ => {
-                    out.push('{');
-                    out.push_str(token);
-                    out.push('}');
-                }
+        match token {
+            TOK_NORMAL => {
+                st = StyleState {
+                    light,
+                    ..Default::default()
+                };
+            }
+            TOK_BOLD => {
+                st.bold = true;
+            }
+            TOK_BLACK => st.fg = Some(ColorSpec::Ansi(AnsiColors::Black)),
+            TOK_RED => st.fg = Some(ColorSpec::Ansi(AnsiColors::Red)),
+            TOK_GREEN => st.fg = Some(ColorSpec::Ansi(AnsiColors::Green)),
+            TOK_SPRING_GREEN => {
+                st.fg = Some(ColorSpec::Rgb(0, 255, 127));
+            }
+            TOK_YELLOW => st.fg = Some(ColorSpec::Ansi(AnsiColors::Yellow)),
+            TOK_BLUE => st.fg = Some(ColorSpec::Ansi(AnsiColors::Blue)),
+            TOK_MAGENTA => st.fg = Some(ColorSpec::Ansi(AnsiColors::Magenta)),
+            TOK_CYAN => st.fg = Some(ColorSpec::Ansi(AnsiColors::Cyan)),
+            TOK_WHITE => st.fg = Some(ColorSpec::Ansi(AnsiColors::White)),
+            TOK_PINK => {
+                st.fg = Some(ColorSpec::Rgb(255, 105, 180));
+            }
+            TOK_LPINK => {
+                st.fg = Some(ColorSpec::Rgb(255, 182, 193));
+            }
+            TOK_BG_GREEN => {
+                st.bg = Some(ColorSpec::Ansi(AnsiColors::Green));
             }
+            TOK_BG_RED => {
+                st.bg = Some(ColorSpec::Ansi(AnsiColors::Red));
+            }
+            TOK_BG_WHITE => {
+                st.bg = Some(ColorSpec::Ansi(AnsiColors::White));
+            }
+            TOK_BLOCK | TOK_BLOCK_VERT => write_styled(out, BLOCK_CHAR, st)?,
+            _ => write!(out, "{{{}}}", token)?,
+        }
+    }
+
+    write_styled(out, rest, st)
+}
+
+/// What `validate_ascii` found in a logo.
+#[derive(Debug, Default)]
+pub struct AsciiReport {
+    /// Widest line in columns, as rendered.
+    pub width: usize,
+    pub height: usize,
+    /// (line number from 1, problem)
+    pub problems: Vec<(usize, String)>,
+}
+
+/// Checks a logo the way `write_ascii` will read it: tokens it doesn't
+/// know (which it prints as they are), braces that never close, and
+/// characters that render at unpredictable widths. Also measures it.
+pub fn validate_ascii(content: &str) -> AsciiReport {
+    let mut report = AsciiReport::default();
+    let lines: Vec<&str> = content.trim_end_matches('\n').split('\n').collect();
+    report.height = lines.len();
+
+    for (i, line) in lines.iter().enumerate() {
+        let number = i + 1;
+        if line.ends_with('\r') {
+            report
+                .problems
+                .push((number, "CRLF line ending".to_string()));
+        }
+        if line.contains('\t') {
+            report.problems.push((
+                number,
+                "tab, which terminals render at different widths".to_string(),
+            ));
+        }
+
+        let mut rest = *line;
+        while let Some(start) = rest.find('{') {
+            let after_brace = &rest[start + 1..];
+            let Some(end) = after_brace.find('}') else {
+                report.problems.push((
+                    number,
+                    format!("{{ with no closing }} in {:?}", &rest[start..]),
+                ));
+                break;
+            };
+            let token = &after_brace[..end];
+            if !TOKENS.contains(&token) {
+                report
+                    .problems
+                    .push((number, format!("unknown token {{{}}}", token)));
+            }
+            rest = &after_brace[end + 1..];
+        }
+
+        report.width = report.width.max(visible_width(&render_ascii(line)));
+    }
+    report
+}
+
+/// Builds the logo, info block and cursor movement into one buffer and
+/// writes it with a single locked write, so nothing interleaves with it.
+pub fn print(config: &Configuration, info: &mut SystemInfo) -> io::Result<()> {
+    let out = render(config, info)?;
+    color::write_stdout(&out, config.color)
+}
+
+/// The buffer `print` writes.
+fn render(config: &Configuration, info: &mut SystemInfo) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+
+    if config.plain {
+        render_info(&mut out, config, info)?;
+    } else {
+        let (logo, lines_printed) = if config.show_image {
+            image_logo(config, info)?
         } else {
-            out.push_str(&apply_style(&rest[start..], st));
-            return out;
+            ascii_logo(config, info)?
+        };
+        out.extend_from_slice(logo.as_bytes());
+        write!(&mut out, "\x1b[{}A", lines_printed)?;
+
+        let start = out.len();
+        render_info(&mut out, config, info)?;
+        let rows = out[start..].iter().filter(|&&b| b == b'\n').count();
+        if config.screenshot {
+            // End on the line below both the logo and the rows, however
+            // many there are, so every screenshot frames the same way.
+            out.extend(std::iter::repeat_n(
+                b'\n',
+                lines_printed.saturating_sub(rows),
+            ));
+        } else {
+            move_past_logo(&mut out, lines_printed, rows)?;
         }
     }
 
-    out.push_str(&apply_style(rest, st));
-    out
+    Ok(out)
+}
+
+/// Text shown in place of a field that's still being detected.
+pub const PENDING: &str = "...";
+
+/// A frame drawn by `LiveFrame::print`, whose info rows are redrawn in
+/// place as slow fields arrive. Needs a terminal: it saves the cursor at
+/// the top of the info rows and returns to it for every redraw.
+pub struct LiveFrame {
+    rows: usize,
+    /// Lines below the saved cursor known to be on screen: the logo's,
+    /// or the most rows drawn if that's more.
+    reserved: usize,
+}
+
+impl LiveFrame {
+    pub fn print(config: &Configuration, info: &mut SystemInfo) -> io::Result<Self> {
+        let (logo, logo_lines) = if config.show_image {
+            image_logo(config, info)?
+        } else {
+            ascii_logo(config, info)?
+        };
+        let rows = info_rows(config, info)?;
+
+        let mut out = logo.into_bytes();
+        let mut frame = LiveFrame {
+            rows: 0,
+            reserved: logo_lines,
+        };
+        frame.reserve(&mut out, rows.lines().count())?;
+        frame.write_rows(&mut out, &rows)?;
+
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(&out)?;
+        stdout.flush()?;
+        Ok(frame)
+    }
+
+    pub fn redraw(&mut self, config: &Configuration, info: &mut SystemInfo) -> io::Result<()> {
+        let rows = info_rows(config, info)?;
+        let mut out = b"\x1b8".to_vec();
+        let count = rows.lines().count();
+        if count > self.reserved {
+            write!(&mut out, "\x1b[{}B", self.reserved)?;
+            self.reserve(&mut out, count)?;
+        }
+        self.write_rows(&mut out, &rows)?;
+
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(&out)?;
+        stdout.flush()
+    }
+
+    /// From the bottom of the reserved lines, makes room for `rows` rows
+    /// and saves the cursor at the top of them. The room is made with
+    /// newlines first, since a saved cursor doesn't follow the screen
+    /// when the rows scroll it.
+    fn reserve(&mut self, out: &mut Vec<u8>, rows: usize) -> io::Result<()> {
+        out.extend(std::iter::repeat_n(
+            b'\n',
+            rows.saturating_sub(self.reserved),
+        ));
+        self.reserved = self.reserved.max(rows);
+        write!(out, "\x1b[{}A\x1b7", self.reserved)
+    }
+
+    /// Writes the info rows, clearing what's left of each old row and any
+    /// rows the previous draw had beyond these.
+    fn write_rows(&mut self, out: &mut Vec<u8>, rows: &str) -> io::Result<()> {
+        let count = rows.lines().count();
+        for row in rows.lines() {
+            writeln!(out, "{}\x1b[K", row)?;
+        }
+        let stale = self.rows.saturating_sub(count);
+        for _ in 0..stale {
+            writeln!(out, "\x1b[18C\x1b[K")?;
+        }
+        self.rows = count;
+
+        move_past_logo(out, self.reserved, count + stale)
+    }
+}
+
+/// The info rows as `render_info` writes them.
+fn info_rows(config: &Configuration, info: &mut SystemInfo) -> io::Result<String> {
+    let mut rows = Vec::new();
+    render_info(&mut rows, config, info)?;
+    Ok(String::from_utf8_lossy(&rows).into_owned())
+}
+
+/// Moves from the end of `rows` lines written from the top of the logo to
+/// the line below both, where the next output should start.
+fn move_past_logo(out: &mut Vec<u8>, logo_lines: usize, rows: usize) -> io::Result<()> {
+    if logo_lines > rows {
+        write!(out, "\x1b[{}B", logo_lines - rows)?;
+    }
+    Ok(())
+}
+
+/// Renders several machines' logos and info blocks together, either side
+/// by side or one under the other. Used by `--hosts`, so this never moves
+/// the cursor and never draws images.
+pub fn print_hosts(
+    config: &Configuration,
+    hosts: &mut [SystemInfo],
+    stacked: bool,
+) -> io::Result<()> {
+    let mut blocks = Vec::new();
+    for info in hosts.iter_mut() {
+        blocks.push(render_block(config, info)?);
+    }
+
+    let mut out = String::new();
+    if stacked {
+        for block in &blocks {
+            for line in block {
+                out.push_str(line);
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+    } else {
+        let widths: Vec<usize> = blocks
+            .iter()
+            .map(|block| block.iter().map(|l| visible_width(l)).max().unwrap_or(0))
+            .collect();
+        let rows = blocks.iter().map(Vec::len).max().unwrap_or(0);
+        for row in 0..rows {
+            let mut line = String::new();
+            for (block, width) in blocks.iter().zip(&widths) {
+                let cell = block.get(row).map(String::as_str).unwrap_or("");
+                line.push_str(cell);
+                line.push_str(&" ".repeat(width - visible_width(cell) + 4));
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+    }
+
+    color::write_stdout(out.as_bytes(), config.color)
+}
+
+/// One host's logo with its info rows next to it, as finished lines.
+fn render_block(config: &Configuration, info: &mut SystemInfo) -> io::Result<Vec<String>> {
+    let (logo, _) = ascii_logo(config, info)?;
+    let logo = split_styled_lines(&logo);
+
+    let mut rows = Vec::new();
+    let plain = Configuration {
+        plain: true,
+        ..config.clone()
+    };
+    render_info(&mut rows, &plain, info)?;
+    let rows = String::from_utf8_lossy(&rows);
+    let rows: Vec<&str> = rows.lines().collect();
+
+    let height = logo.len().max(rows.len());
+    Ok((0..height)
+        .map(|i| {
+            let art = logo.get(i).map(String::as_str).unwrap_or("");
+            let pad = 18usize.saturating_sub(visible_width(art));
+            format!("{}{}{}", art, " ".repeat(pad), rows.get(i).unwrap_or(&""))
+        })
+        .collect())
+}
+
+/// Splits rendered ASCII art into lines that each carry their own colors,
+/// since a style opened on one line of a logo is often only reset lines later.
+fn split_styled_lines(s: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut active = String::new();
+
+    for line in s.lines() {
+        lines.push(format!("{}{}\x1b[0m", active, line));
+
+        let mut rest = line;
+        while let Some(start) = rest.find("\x1b[") {
+            let end = rest[start..]
+                .find('m')
+                .map_or(rest.len(), |end| start + end + 1);
+            let seq = &rest[start..end];
+            if seq == "\x1b[0m" {
+                active.clear();
+            } else {
+                active.push_str(seq);
+            }
+            rest = &rest[end..];
+        }
+    }
+
+    lines
+}
+
+/// Columns `s` takes up, counting emoji as two.
+fn visible_width(s: &str) -> usize {
+    strip_ansi(s)
+        .chars()
+        .map(|c| if c >= '\u{1f300}' { 2 } else { 1 })
+        .sum()
+}
+
+/// Writes the info rows, boxed in by `config.border` if it's set. The box
+/// has to fit its widest row, so the rows are rendered first and framed
+/// afterwards, with the user@host line moved into the top edge.
+fn render_info(out: &mut Vec<u8>, config: &Configuration, info: &mut SystemInfo) -> io::Result<()> {
+    if config.border == Border::None {
+        return render_rows(out, config, info);
+    }
+
+    let title = header(config, info);
+    let mut rows = Vec::new();
+    let inner = Configuration {
+        plain: true,
+        show_user: false,
+        ..config.clone()
+    };
+    render_rows(&mut rows, &inner, info)?;
+    let rows = String::from_utf8_lossy(&rows);
+
+    let [tl, tr, bl, br, h, v] = border_chars(config.border);
+    let title_width = title.as_deref().map_or(0, |t| visible_width(t) + 1);
+    let width = rows
+        .lines()
+        .map(visible_width)
+        .max()
+        .unwrap_or(0)
+        .max(title_width);
+    let move_cursor = if config.plain { "" } else { "\x1b[18C" };
+
+    let top = match &title {
+        Some(title) => format!("{h} {} {}", title, h.repeat(width - title_width), h = h),
+        None => h.repeat(width + 2),
+    };
+    writeln!(out, "{}{}{}{}", move_cursor, tl, top, tr)?;
+    for row in rows.lines() {
+        let pad = width - visible_width(row);
+        writeln!(out, "{}{} {}{} {}", move_cursor, v, row, " ".repeat(pad), v)?;
+    }
+    writeln!(out, "{}{}{}{}", move_cursor, bl, h.repeat(width + 2), br)
+}
+
+/// Corners, then the horizontal and vertical edges. Anything but ASCII
+/// falls back to it where box drawing characters won't render.
+fn border_chars(border: Border) -> [&'static str; 6] {
+    match border {
+        _ if !unicode_terminal() => ["+", "+", "+", "+", "-", "|"],
+        Border::Rounded => ["╭", "╮", "╰", "╯", "─", "│"],
+        Border::Double => ["╔", "╗", "╚", "╝", "═", "║"],
+        Border::Ascii | Border::None => ["+", "+", "+", "+", "-", "|"],
+    }
+}
+
+/// The bold user@host line, with its icon, or `None` if it's hidden.
+/// Must run before the OS name is uwufied, since the icon is looked up by it.
+fn header(config: &Configuration, info: &SystemInfo) -> Option<String> {
+    if !config.show_user {
+        return None;
+    }
+
+    let header_icon = match config.header_icon {
+        _ if !unicode_terminal() => None,
+        HeaderIcon::Off => None,
+        HeaderIcon::Emoji => Family::of(&info.os_name).map(|family| family.emoji().to_string()),
+        HeaderIcon::Glyph => assets::glyph(&info.os_name).map(|glyph| {
+            match assets::meta(&info.os_name)
+                .accent
+                .as_deref()
+                .and_then(PaletteColor::parse)
+            {
+                Some(PaletteColor::Ansi(i)) => {
+                    glyph.color(BASIC_COLORS[i as usize % 8]).to_string()
+                }
+                Some(PaletteColor::Rgb(r, g, b)) => glyph.color(Rgb(r, g, b)).to_string(),
+                None => glyph.to_string(),
+            }
+        }),
+    };
+
+    let mut userhost = format!("{}@{}", info.user, info.host);
+    if config.uwu_userhost {
+        uwufy::uwu_generic(&mut userhost);
+    }
+    let icon = header_icon
+        .map(|icon| format!("{} ", icon))
+        .unwrap_or_default();
+    Some(format!("{}{}", icon, userhost.bold()))
 }
 
 #[allow(clippy::write_literal)]
-pub fn print_info(config: &Configuration, info: &mut SystemInfo) -> io::Result<()> {
-    let mut out = BufWriter::new(io::stdout());
+fn render_rows(out: &mut Vec<u8>, config: &Configuration, info: &mut SystemInfo) -> io::Result<()> {
+    let header = header(config, info);
 
     uwufy::uwu_name(&mut info.os_name);
 
-    let move_cursor = "\x1b[18C";
+    let move_cursor = if config.plain { "" } else { "\x1b[18C" };
 
-    if config.show_user {
-        let userhost = format!("{}@{}", info.user, info.host);
-        writeln!(&mut out, "{}{}", move_cursor, userhost.bold())?;
+    if let Some(header) = header {
+        writeln!(out, "{}{}", move_cursor, header)?;
     }
 
     if config.show_os {
+        let wsl = Some(&info.wsl)
+            .filter(|wsl| !wsl.is_empty())
+            .map(|wsl| format!(" on {}", wsl))
+            .unwrap_or_default();
         writeln!(
-            &mut out,
-            "{}{} {}",
+            out,
+            "{}{} {}{}",
             move_cursor,
-            "OWOS     ".bold(),
-            info.os_name
+            label(config, "OWOS     ", "\u{f17c}"),
+            info.os_name,
+            wsl
         )?;
     }
 
     if config.show_host {
+        let icon = match info.chassis.icon() {
+            Some(icon) if config.show_chassis_icon => format!("{} ", icon),
+            _ => String::new(),
+        };
+        // "Standard PC (Q35 + ICH9, 2009) (KVM)", unless the model says it.
+        let guest = &info.virtualization;
+        let model = if guest.is_empty() || info.model.contains(guest.as_str()) {
+            info.model.clone()
+        } else if info.model.is_empty() {
+            guest.clone()
+        } else {
+            format!("{} ({})", info.model, guest)
+        };
         writeln!(
-            &mut out,
+            out,
+            "{}{} {}{}",
+            move_cursor,
+            label(config, "MOWODEL  ", "\u{f109}"),
+            icon,
+            model
+        )?;
+    }
+
+    if config.show_serial && (!info.serial.is_empty() || !info.asset_tag.is_empty()) {
+        let mask = |value: &str| {
+            if config.reveal_serial {
+                value.to_string()
+            } else {
+                mask_serial(value)
+            }
+        };
+        let serial = match (info.serial.is_empty(), info.asset_tag.is_empty()) {
+            (false, false) => format!("{} (asset: {})", mask(&info.serial), mask(&info.asset_tag)),
+            (false, true) => mask(&info.serial),
+            _ => format!("asset: {}", mask(&info.asset_tag)),
+        };
+        writeln!(
+            out,
             "{}{} {}",
             move_cursor,
-            "MOWODEL  ".bold(),
-            info.model
+            label(config, "SEWIAW   ", "\u{f02a}"),
+            serial
         )?;
     }
 
     if config.show_kernel {
+        let status = match info.kernel_status.as_str() {
+            _ if !config.kernel_check => String::new(),
+            "" => String::new(),
+            "up to date" => format!(" ({})", "up to date".green()),
+            status => format!(" ({})", status.yellow()),
+        };
+        writeln!(
+            out,
+            "{}{} {}{}",
+            move_cursor,
+            label(config, "KEWNEL   ", "\u{f013}"),
+            info.kernel,
+            status
+        )?;
+    }
+
+    if config.show_kernel_params && !info.kernel_params.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "KPAWAMS  ", "\u{f1de}"),
+            info.kernel_params
+        )?;
+    }
+
+    if config.show_bootloader && !info.bootloader.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "BOOTWOAD ", "\u{f135}"),
+            info.bootloader
+        )?;
+    }
+
+    if config.show_sched && !info.sched.is_empty() {
         writeln!(
-            &mut out,
+            out,
             "{}{} {}",
             move_cursor,
-            "KEWNEL   ".bold(),
-            info.kernel
+            label(config, "SCHEDUWU ", "\u{f0ae}"),
+            info.sched
         )?;
     }
 
     if config.show_cpu {
         writeln!(
-            &mut out,
+            out,
             "{}{} {}",
             move_cursor,
-            "CPUWU    ".bold(),
-            info.cpu_model
+            label(config, "CPUWU    ", "\u{f2db}"),
+            format_cpu(config, info)
         )?;
     }
 
-    if config.show_gpu {
-        for gpu in &info.gpu_models {
-            writeln!(&mut out, "{}{} {}", move_cursor, "GPUWU    ".bold(), gpu)?;
+    if config.show_microcode && !info.microcode.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "MICWOCODE", "\u{f121}"),
+            info.microcode
+        )?;
+    }
+
+    if config.show_cpu_vulns && !info.cpu_vulns.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "VUWNS    ", "\u{f132}"),
+            info.cpu_vulns
+        )?;
+    }
+
+    if config.show_virt && !info.virt_support.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "VIWT     ", "\u{f1b3}"),
+            info.virt_support
+        )?;
+    }
+
+    if config.show_virtualization && !info.virtualization.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "ENVIWON  ", "\u{f308}"),
+            info.virtualization
+        )?;
+    }
+
+    if config.show_gpu && config.gpu_summary && info.gpu_models.len() > 1 {
+        writeln!(
+            out,
+            "{}{} {} GPUwUs: {}",
+            move_cursor,
+            label(config, "GPUWU    ", "\u{f11b}"),
+            info.gpu_models.len(),
+            info.gpu_models.join(" + ")
+        )?;
+    } else if config.show_gpu {
+        let numbered = info.gpu_models.len() > 1;
+        for (i, gpu) in info.gpu_models.iter().enumerate() {
+            let text = if numbered {
+                format!("GPUWU {:<3}", i + 1)
+            } else {
+                "GPUWU    ".to_string()
+            };
+            writeln!(
+                out,
+                "{}{} {}",
+                move_cursor,
+                label(config, &text, "\u{f11b}"),
+                gpu
+            )?;
         }
     }
 
+    if config.show_gpu_driver && !info.gpu_driver.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "GPU DWIVER", "\u{f013}"),
+            info.gpu_driver
+        )?;
+    }
+
     if config.show_ram {
         writeln!(
-            &mut out,
+            out,
             "{}{} {} MiB/{} MiB",
             move_cursor,
-            "MEMOWY   ".bold(),
+            label(config, "MEMOWY   ", "\u{f1c0}"),
             info.ram_used,
             info.ram_total
         )?;
     }
 
-    if config.show_resolution && (info.screen_width != 0 || info.screen_height != 0) {
+    if config.show_resolution && !info.displays.is_empty() {
+        let scale = if config.show_scale && info.screen_scale > 0.0 {
+            format!(" @ {}", format_scale(info.screen_scale))
+        } else {
+            String::new()
+        };
+        let depth = if config.show_color_depth && info.color_depth > 0 {
+            format!(
+                ", {}-bit{}",
+                info.color_depth,
+                if info.hdr { " HDR" } else { "" }
+            )
+        } else {
+            String::new()
+        };
+        let modes: Vec<String> = info.displays.iter().map(format_display).collect();
+        if config.resolution_per_display && modes.len() > 1 {
+            for (i, mode) in modes.iter().enumerate() {
+                writeln!(
+                    out,
+                    "{}{} {}{}{}",
+                    move_cursor,
+                    label(config, &format!("SCWEEN {:<2}", i + 1), "\u{f108}"),
+                    mode,
+                    scale,
+                    depth
+                )?;
+            }
+        } else {
+            writeln!(
+                out,
+                "{}{} {}{}{}",
+                move_cursor,
+                label(config, "WESOWUTION", "\u{f108}"),
+                modes.join(", "),
+                scale,
+                depth
+            )?;
+        }
+    }
+
+    if config.show_displays && !info.monitors.is_empty() {
+        let monitors: Vec<String> = info
+            .monitors
+            .iter()
+            .map(|m| {
+                if config.show_displays_resolution && m.width != 0 {
+                    format!("{} ({}x{})", m.name, m.width, m.height)
+                } else {
+                    m.name.clone()
+                }
+            })
+            .collect();
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "DISPWAYS ", "\u{f26c}"),
+            monitors.join(", ")
+        )?;
+    }
+
+    if config.show_fans && !info.fans.is_empty() {
+        let fans: Vec<String> = info
+            .fans
+            .iter()
+            .map(|fan| format!("{} {} RPM", fan.name, fan.rpm))
+            .collect();
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "FANWS    ", "\u{f0210}"),
+            fans.join(", ")
+        )?;
+    }
+
+    if config.show_battery.shows(info.chassis) && !info.batteries.is_empty() {
+        let batteries: Vec<String> = info
+            .batteries
+            .iter()
+            .map(|battery| match battery.status.as_str() {
+                "" | "unknown" => format!("{}%", battery.capacity),
+                status => format!("{}% ({})", battery.capacity, status),
+            })
+            .collect();
         writeln!(
-            &mut out,
-            "{}{} {}x{}",
+            out,
+            "{}{} {}",
             move_cursor,
-            "WESOWUTION".bold(),
-            info.screen_width,
-            info.screen_height
+            label(config, "BATTEWY  ", "\u{f240}"),
+            batteries.join(", ")
+        )?;
+    }
+
+    if config.show_lid.shows(info.chassis) && !info.lid.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "WID      ", "\u{f109}"),
+            info.lid
         )?;
     }
 
     if config.show_shell {
         writeln!(
-            &mut out,
+            out,
             "{}{} {}",
             move_cursor,
-            "SHEWW    ".bold(),
+            label(config, "SHEWW    ", "\u{f120}"),
             info.shell
         )?;
     }
 
-    if config.show_pkgs {
+    let server = Some(&info.display_server)
+        .filter(|server| config.show_display_server && !server.is_empty());
+    let de = Some(&info.de).filter(|de| config.show_de && !de.is_empty());
+    let de = match (de, server) {
+        (Some(de), Some(server)) => Some(format!("{} ({})", de, server)),
+        (Some(de), None) => Some(de.clone()),
+        (None, server) => server.cloned(),
+    };
+    if let Some(de) = de {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "DEWE     ", "\u{f108}"),
+            de
+        )?;
+    }
+
+    if config.show_terminal && !info.terminal.is_empty() {
+        let multiplexer = if info.multiplexer.is_empty() {
+            String::new()
+        } else {
+            format!(" (in {})", info.multiplexer)
+        };
+        writeln!(
+            out,
+            "{}{} {}{}",
+            move_cursor,
+            label(config, "TEWMINAW ", "\u{f489}"),
+            info.terminal,
+            multiplexer
+        )?;
+    }
+
+    if config.show_mux && !info.mux_session.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "MUWUX    ", "\u{ebc8}"),
+            info.mux_session
+        )?;
+    }
+
+    if config.show_local_ip && !info.local_ip.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "WOCAW IP ", "\u{f0ac}"),
+            info.local_ip
+        )?;
+    }
+
+    if config.show_audio && !info.audio.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "AUDIOWO  ", "\u{f028}"),
+            info.audio
+        )?;
+    }
+
+    if config.show_rust && !info.rust.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "WUST     ", "\u{e7a8}"),
+            info.rust
+        )?;
+    }
+
+    if config.show_cursor && !info.cursor.is_empty() {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "CUWSOW   ", "\u{f245}"),
+            info.cursor
+        )?;
+    }
+
+    if config.show_dotfiles && !info.dotfiles.is_empty() {
         writeln!(
-            &mut out,
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "DOTFIWES ", "\u{f013}"),
+            info.dotfiles
+        )?;
+    }
+
+    if let Some(steam) = info.steam.as_ref().filter(|_| config.show_steam) {
+        let proton = if steam.proton.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", steam.proton)
+        };
+        writeln!(
+            out,
+            "{}{} {} games, {}{}",
+            move_cursor,
+            label(config, "GAMEWS   ", "\u{f1b6}"),
+            steam.games,
+            format_size(steam.size),
+            proton
+        )?;
+    }
+
+    if let Some(track) = info
+        .now_playing
+        .as_ref()
+        .filter(|_| config.show_now_playing)
+    {
+        let playing = if track.artist.is_empty() {
+            track.title.clone()
+        } else {
+            format!("{} - {}", track.artist, track.title)
+        };
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "PWAYING  ", "\u{f001}"),
+            playing
+        )?;
+    }
+
+    if let Some(wifi) = info.wifi.as_ref().filter(|_| config.show_wifi) {
+        let signal = wifi
+            .signal
+            .map(|signal| format!(" ({}%)", signal))
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "{}{} {}{}",
+            move_cursor,
+            label(config, "WIFI     ", "\u{f1eb}"),
+            wifi.ssid,
+            signal
+        )?;
+    }
+
+    if config.show_pkgs && info.pkgman_name == PENDING {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "PKGS     ", "\u{f187}"),
+            PENDING
+        )?;
+    } else if config.show_pkgs {
+        writeln!(
+            out,
             "{}{} {}: {}",
             move_cursor,
-            "PKGS     ".bold(),
+            label(config, "PKGS     ", "\u{f187}"),
             info.pkgs,
             info.pkgman_name
         )?;
     }
 
+    if config.show_updates && !info.updates.is_empty() {
+        let total: u32 = info.updates.iter().map(|u| u.count).sum();
+        let updates = if total == 0 {
+            "up to date".green().to_string()
+        } else {
+            let managers: Vec<String> = info
+                .updates
+                .iter()
+                .filter(|u| u.count > 0)
+                .map(|u| format!("{} ({})", u.count, u.manager))
+                .collect();
+            format!("{}: {}", total, managers.join(", "))
+                .yellow()
+                .to_string()
+        };
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "UWUPDATES", "\u{f01b}"),
+            updates
+        )?;
+    }
+
     if config.show_uptime {
-        let uptime_str = format_uptime(info.uptime);
+        let uptime_str = format_uptime(info.uptime, config.uptime_format);
         writeln!(
-            &mut out,
+            out,
             "{}{} {}",
             move_cursor,
-            "UWUPTIME ".bold(),
+            label(config, "UWUPTIME ", "\u{f017}"),
             uptime_str
         )?;
     }
 
-    if config.show_colors {
+    if config.show_session_uptime && info.session_uptime > 0 {
+        writeln!(
+            out,
+            "{}{} {}",
+            move_cursor,
+            label(config, "SESSIOWN ", "\u{f2bd}"),
+            format_uptime(info.session_uptime, config.uptime_format)
+        )?;
+    }
+
+    if config.show_uptime_record && info.uptime_record > 0 {
+        let record = if info.uptime >= info.uptime_record {
+            "new wecowd!".to_string()
+        } else {
+            format!(
+                "best: {}",
+                format_uptime(info.uptime_record, config.uptime_format)
+            )
+        };
         writeln!(
-            &mut out,
-            "{}{}{}{}{}{}{}{}",
-            "\x1b[18C",
-            "██".black(),
-            "██".red(),
-            "██".green(),
-            "██".yellow(),
-            "██".blue(),
-            "██".magenta(),
-            "██".cyan()
+            out,
+            "{}{} {} ({})",
+            move_cursor,
+            label(config, "WECOWD UWUPTIME", "\u{f091}"),
+            format_uptime(info.uptime, config.uptime_format),
+            record
         )?;
-        writeln!(&mut out, "{}{}", "\x1b[18C", "██".white())?;
     }
 
-    out.flush()?;
+    if config.show_colors {
+        for row in color_strip(config) {
+            writeln!(out, "{}{}", move_cursor, row)?;
+        }
+    }
+
     Ok(())
 }
 
-fn format_uptime(seconds: u64) -> String {
-    match seconds {
-        0..=3599 => format!("{}m", seconds / 60 % 60),
-        3600..=86399 => format!("{}h, {}m", seconds / 3600, seconds / 60 % 60),
-        _ => format!(
-            "{}d, {}h, {}m",
-            seconds / 86400,
-            seconds / 3600 % 24,
-            seconds / 60 % 60
-        ),
+fn color_strip(config: &Configuration) -> Vec<String> {
+    let block = &config.colors_block;
+
+    if let Some(palette) = pride_palette(config.colors_style) {
+        let paint = |&(r, g, b): &(u8, u8, u8), s: &str| s.color(Rgb(r, g, b)).to_string();
+        if config.colors_flag {
+            let stripe = block.repeat(7);
+            return palette.iter().map(|c| paint(c, &stripe)).collect();
+        }
+        return vec![palette.iter().map(|c| paint(c, block)).collect()];
+    }
+
+    let paint = |color: &PaletteColor, bright: bool| match *color {
+        PaletteColor::Ansi(i) => {
+            let ansi = if bright {
+                BRIGHT_COLORS[i as usize % 8]
+            } else {
+                BASIC_COLORS[i as usize % 8]
+            };
+            block.color(ansi).to_string()
+        }
+        PaletteColor::Rgb(r, g, b) if bright => {
+            let lighten = |c: u8| c + (255 - c) / 3;
+            block
+                .color(Rgb(lighten(r), lighten(g), lighten(b)))
+                .to_string()
+        }
+        PaletteColor::Rgb(r, g, b) => block.color(Rgb(r, g, b)).to_string(),
+    };
+    let row = |colors: &[PaletteColor], bright: bool| -> String {
+        colors.iter().map(|c| paint(c, bright)).collect()
+    };
+
+    let palette = &config.colors_palette;
+    match (config.colors_rows, config.colors_bright) {
+        (2, true) => vec![row(palette, false), row(palette, true)],
+        (2, false) => {
+            let (top, bottom) = palette.split_at(palette.len().div_ceil(2));
+            vec![row(top, false), row(bottom, false)]
+        }
+        (_, bright) => vec![row(palette, bright)],
     }
 }
 
-pub fn print_ascii(info: &SystemInfo) -> io::Result<usize> {
-    let mut out = BufWriter::new(io::stdout());
-    let ascii_filename = format!("ascii/{}.txt", info.os_name);
+const BASIC_COLORS: [AnsiColors; 8] = [
+    AnsiColors::Black,
+    AnsiColors::Red,
+    AnsiColors::Green,
+    AnsiColors::Yellow,
+    AnsiColors::Blue,
+    AnsiColors::Magenta,
+    AnsiColors::Cyan,
+    AnsiColors::White,
+];
 
-    if let Some(file) = Assets::get(&ascii_filename) {
-        let content = std::str::from_utf8(&file.data)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+const BRIGHT_COLORS: [AnsiColors; 8] = [
+    AnsiColors::BrightBlack,
+    AnsiColors::BrightRed,
+    AnsiColors::BrightGreen,
+    AnsiColors::BrightYellow,
+    AnsiColors::BrightBlue,
+    AnsiColors::BrightMagenta,
+    AnsiColors::BrightCyan,
+    AnsiColors::BrightWhite,
+];
 
-        let processed = render_ascii(content);
-        writeln!(&mut out)?;
-        out.write_all(processed.as_bytes())?;
-        out.flush()?;
+/// Stripe colors of the pride flags, top to bottom. `None` for the classic
+/// terminal palette.
+fn pride_palette(style: ColorsStyle) -> Option<&'static [(u8, u8, u8)]> {
+    let palette: &[(u8, u8, u8)] = match style {
+        ColorsStyle::Classic => return None,
+        ColorsStyle::Trans => &[
+            (0x5b, 0xce, 0xfa),
+            (0xf5, 0xa9, 0xb8),
+            (0xff, 0xff, 0xff),
+            (0xf5, 0xa9, 0xb8),
+            (0x5b, 0xce, 0xfa),
+        ],
+        ColorsStyle::Lesbian => &[
+            (0xd5, 0x2d, 0x00),
+            (0xff, 0x9a, 0x56),
+            (0xff, 0xff, 0xff),
+            (0xd3, 0x62, 0xa4),
+            (0xa3, 0x02, 0x62),
+        ],
+        ColorsStyle::Bi => &[
+            (0xd6, 0x02, 0x70),
+            (0xd6, 0x02, 0x70),
+            (0x9b, 0x4f, 0x96),
+            (0x00, 0x38, 0xa8),
+            (0x00, 0x38, 0xa8),
+        ],
+        ColorsStyle::Nonbinary => &[
+            (0xfc, 0xf4, 0x34),
+            (0xff, 0xff, 0xff),
+            (0x9c, 0x59, 0xd1),
+            (0x2c, 0x2c, 0x2c),
+        ],
+        ColorsStyle::Rainbow => &[
+            (0xe4, 0x03, 0x03),
+            (0xff, 0x8c, 0x00),
+            (0xff, 0xed, 0x00),
+            (0x00, 0x80, 0x26),
+            (0x00, 0x4c, 0xff),
+            (0x73, 0x29, 0x82),
+        ],
+    };
+    Some(palette)
+}
 
-        let line_count = processed.lines().count() + 1;
+/// A row's label: its text, its Nerd Font glyph, or both, depending on
+/// the icons setting and whether the glyphs are likely to render.
+fn label(config: &Configuration, text: &str, icon: &str) -> String {
+    let mode = if unicode_terminal() {
+        config.icons
+    } else {
+        IconMode::Off
+    };
+    let label = match mode {
+        IconMode::Off => text.to_string(),
+        IconMode::Icons => icon.to_string(),
+        IconMode::Both => format!("{} {}", icon, text),
+    };
+    label.bold().to_string()
+}
 
-        return Ok(line_count);
+/// There's no way to ask the terminal which font it uses, so only rule
+/// out the cases that certainly can't show glyphs or emoji: the Linux
+/// console and non-UTF-8 locales.
+pub fn unicode_terminal() -> bool {
+    if std::env::var("TERM").is_ok_and(|term| term == "linux") {
+        return false;
     }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()));
+    locale.is_none_or(|locale| {
+        let locale = locale.to_lowercase();
+        locale.contains("utf-8") || locale.contains("utf8")
+    })
+}
 
-    if info.os_name != "unknown" {
-        let fallback_filename = "ascii/unknown.txt";
-        if let Some(file) = Assets::get(fallback_filename) {
-            let content = std::str::from_utf8(&file.data)
-                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-            let processed = render_ascii(content);
-            writeln!(&mut out)?;
-            out.write_all(processed.as_bytes())?;
-            out.flush()?;
+pub fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
 
-            let line_count = processed.lines().count() + 1;
-            return Ok(line_count);
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
         }
     }
 
-    writeln!(&mut out, "No\nascii\nfile\nfound\n\n\n")?;
-    out.flush()?;
+    out
+}
+
+fn mask_serial(value: &str) -> String {
+    let len = value.chars().count();
+    let keep = if len > 8 { 4 } else { len / 4 };
+    value
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if i < len - keep { '*' } else { c })
+        .collect()
+}
 
-    Ok(7)
+/// The CPU name with the core counts and clock speed it's configured to show.
+fn format_cpu(config: &Configuration, info: &SystemInfo) -> String {
+    let mut cpu = info.cpu_model.clone();
+    if config.cpu_cores && info.cpu_threads > 0 {
+        cpu.push_str(&format!(" ({}c/{}t)", info.cpu_cores, info.cpu_threads));
+    }
+    if config.cpu_freq && info.cpu_max_mhz > 0 {
+        if info.cpu_max_mhz >= 1000 {
+            cpu.push_str(&format!(" @ {:.1}GHz", info.cpu_max_mhz as f32 / 1000.0));
+        } else {
+            cpu.push_str(&format!(" @ {}MHz", info.cpu_max_mhz));
+        }
+    }
+    cpu
 }
 
-pub fn print_image(info: &SystemInfo) -> io::Result<usize> {
-    let image_filename = if let Some(ref img) = info.image_name {
-        format!("{}.sixel", img)
+/// `2560x1440@165Hz`, or without the rate when it isn't known.
+fn format_display(display: &crate::info::Display) -> String {
+    if display.refresh > 0 {
+        format!(
+            "{}x{}@{}Hz",
+            display.width,
+            display.height,
+            (display.refresh + 500) / 1000
+        )
     } else {
-        format!("{}.sixel", info.os_name)
-    };
+        format!("{}x{}", display.width, display.height)
+    }
+}
 
-    if let Some(file) = Assets::get(&image_filename) {
-        let sixelstr = std::str::from_utf8(&file.data)
+fn format_scale(scale: f32) -> String {
+    let rounded = (scale * 100.0).round() / 100.0;
+    let formatted = format!("{:.2}", rounded);
+    format!("{}x", formatted.trim_end_matches('0').trim_end_matches('.'))
+}
+
+/// Bytes as GiB, or TiB from 1 TiB up.
+fn format_size(bytes: u64) -> String {
+    let gib = bytes as f64 / (1u64 << 30) as f64;
+    if gib >= 1024.0 {
+        format!("{:.1} TiB", gib / 1024.0)
+    } else {
+        format!("{:.1} GiB", gib)
+    }
+}
+
+fn format_uptime(seconds: u64, format: UptimeFormat) -> String {
+    match format {
+        UptimeFormat::Compact => match seconds {
+            0..=3599 => format!("{}m", seconds / 60 % 60),
+            3600..=86399 => format!("{}h, {}m", seconds / 3600, seconds / 60 % 60),
+            _ => format!(
+                "{}d, {}h, {}m",
+                seconds / 86400,
+                seconds / 3600 % 24,
+                seconds / 60 % 60
+            ),
+        },
+        UptimeFormat::Long => {
+            let units = [
+                (seconds / 86400, "day"),
+                (seconds / 3600 % 24, "hour"),
+                (seconds / 60 % 60, "minute"),
+            ];
+            let parts: Vec<String> = units
+                .iter()
+                .filter(|(n, _)| *n > 0)
+                .map(|(n, unit)| format!("{} {}{}", n, unit, if *n == 1 { "" } else { "s" }))
+                .collect();
+            if parts.is_empty() {
+                "0 minutes".to_string()
+            } else {
+                parts.join(", ")
+            }
+        }
+        UptimeFormat::Seconds => format!("{}s", seconds),
+        UptimeFormat::Clock => {
+            let boot = chrono::Local::now() - chrono::Duration::seconds(seconds as i64);
+            format!("since {}", boot.format("%Y-%m-%d %H:%M"))
+        }
+    }
+}
+
+/// The logo to draw for `info`: its small variant when the terminal is
+/// narrower than the logo's `min_width`.
+fn logo_name(info: &SystemInfo) -> String {
+    let meta = assets::meta(&info.os_name);
+    match (meta.small, meta.min_width) {
+        (Some(small), Some(min_width))
+            if theme::terminal_width().is_some_and(|width| width < min_width) =>
+        {
+            small
+        }
+        _ => info.os_name.clone(),
+    }
+}
+
+fn ascii_logo(config: &Configuration, info: &SystemInfo) -> io::Result<(String, usize)> {
+    let name = logo_name(info);
+    let custom = config
+        .asset_dir
+        .as_ref()
+        .and_then(|dir| std::fs::read(dir.join("ascii").join(format!("{}.txt", name))).ok());
+    let data = custom.or_else(|| {
+        Assets::get(&format!("ascii/{}.txt", name))
+            .or_else(|| {
+                if info.os_name != "unknown" {
+                    Assets::get("ascii/unknown.txt")
+                } else {
+                    None
+                }
+            })
+            .map(|file| file.data.into_owned())
+    });
+
+    if let Some(data) = data {
+        let content = std::str::from_utf8(&data)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        println!("{}", sixelstr);
-        return Ok(9);
+
+        let mut logo = String::with_capacity(content.len() * 2 + 1);
+        logo.push('\n');
+        write_ascii(&mut logo, content, config.light).map_err(io::Error::other)?;
+        // The leading newline is counted along with the logo's own lines.
+        let line_count = logo.lines().count();
+
+        return Ok((logo, line_count));
+    }
+
+    Ok(("No\nascii\nfile\nfound\n\n\n\n".to_string(), 7))
+}
+
+#[cfg(feature = "sixel")]
+fn load_sixel(config: &Configuration, name: &str) -> Option<Vec<u8>> {
+    if name.ends_with(".sixel") {
+        return std::fs::read(name).ok();
+    }
+    let dirs = config.asset_dir.as_ref().map(|dir| dir.join("images"));
+    for dir in dirs.into_iter().chain(Configuration::user_image_dir()) {
+        if let Ok(data) = std::fs::read(dir.join(format!("{}.sixel", name))) {
+            return Some(data);
+        }
     }
 
-    if info.image_name.is_none() && info.os_name != "unknown" {
-        if let Some(file) = Assets::get("unknown.sixel") {
-            let sixelstr = std::str::from_utf8(&file.data)
-                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-            println!("{}", sixelstr);
-            return Ok(9);
+    Assets::get(&format!("{}.sixel", name)).map(|file| file.data.into_owned())
+}
+
+#[cfg(not(feature = "sixel"))]
+fn image_logo(config: &Configuration, info: &SystemInfo) -> io::Result<(String, usize)> {
+    ascii_logo(config, info)
+}
+
+#[cfg(feature = "sixel")]
+fn image_logo(config: &Configuration, info: &SystemInfo) -> io::Result<(String, usize)> {
+    let image_name = info.image_name.as_deref().unwrap_or(&info.os_name);
+
+    let data = load_sixel(config, image_name).or_else(|| {
+        if info.image_name.is_none() && info.os_name != "unknown" {
+            load_sixel(config, "unknown")
+        } else {
+            None
         }
+    });
+
+    if let Some(data) = data {
+        let sixelstr = std::str::from_utf8(&data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        return Ok((format!("{}\n", sixelstr), 9));
+    }
+
+    Ok(("No image found\n".to_string(), 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ascii_logo, render, validate_ascii};
+    use crate::config::Configuration;
+    use crate::info::SystemInfo;
+
+    #[test]
+    fn measures_ragged_logos_without_tokens() {
+        let report = validate_ascii("{RED} /\\\n{BLUE}/  \\ {WHITE}ω\n|\n");
+        assert_eq!(report.width, 6);
+        assert_eq!(report.height, 3);
+        assert!(report.problems.is_empty());
     }
 
-    println!("No image found");
-    Ok(1)
+    #[test]
+    fn reports_tabs_and_crlf() {
+        let report = validate_ascii("ok\n\t/\\\r\n");
+        let lines: Vec<usize> = report.problems.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, [2, 2]);
+        assert!(report.problems.iter().any(|(_, p)| p.starts_with("tab")));
+        assert!(report.problems.iter().any(|(_, p)| p == "CRLF line ending"));
+    }
+
+    #[test]
+    fn reports_unknown_and_unclosed_tokens() {
+        let report = validate_ascii("{RED}ok{PURPLE}\n{BLUE /\\\n{}");
+        assert_eq!(
+            report.problems,
+            [
+                (1, "unknown token {PURPLE}".to_string()),
+                (2, "{ with no closing } in \"{BLUE /\\\\\"".to_string()),
+                (3, "unknown token {}".to_string()),
+            ]
+        );
+    }
+
+    /// Renders `config` next to the Arch logo and returns the logo's
+    /// height, the rows written and what follows the last row.
+    fn render_arch(config: &Configuration) -> (usize, usize, String) {
+        let field = || "x".to_string();
+        let mut info = SystemInfo {
+            os_name: "arch".to_string(),
+            de: field(),
+            audio: field(),
+            dotfiles: field(),
+            cursor: field(),
+            rust: field(),
+            bootloader: field(),
+            sched: field(),
+            ..SystemInfo::default()
+        };
+        let (logo, logo_lines) = ascii_logo(config, &info).unwrap();
+        let out = String::from_utf8(render(config, &mut info).unwrap()).unwrap();
+        let rows = out
+            .strip_prefix(&format!("{}\x1b[{}A", logo, logo_lines))
+            .unwrap();
+        let tail = &rows[rows.rfind('\n').unwrap() + 1..];
+        (logo_lines, rows.matches('\n').count(), tail.to_string())
+    }
+
+    #[test]
+    fn moves_below_a_logo_taller_than_the_rows() {
+        let mut config = Configuration {
+            color: false,
+            ..Configuration::default()
+        };
+        config.apply_minimal_preset();
+        let (logo_lines, rows, tail) = render_arch(&config);
+        assert!(rows < logo_lines);
+        assert_eq!(tail, format!("\x1b[{}B", logo_lines - rows));
+    }
+
+    #[test]
+    fn stays_below_rows_longer_than_the_logo() {
+        let mut config = Configuration {
+            color: false,
+            ..Configuration::default()
+        };
+        config.apply_all_preset();
+        let (logo_lines, rows, tail) = render_arch(&config);
+        assert!(rows > logo_lines);
+        assert_eq!(tail, "");
+    }
 }
@@ -0,0 +1,419 @@
+//! `uwufetch doctor`: runs every probe on its own and reports how it went,
+//! for working out why a field is empty or slow, and for bug reports.
+
+use crate::config::Configuration;
+use crate::info::{self, SystemInfo};
+use crate::{cache, display, filter, history, theme};
+use std::env;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// The external programs some probe runs when it finds them.
+#[cfg(target_os = "linux")]
+const TOOLS: &[&str] = &[
+    "lspci",
+    "glxinfo",
+    "eglinfo",
+    "xrandr",
+    "wlr-randr",
+    "swaymsg",
+    "xrdb",
+    "gsettings",
+    "iw",
+    "nmcli",
+    "pactl",
+    "playerctl",
+    "dbus-send",
+    "efibootmgr",
+    "checkupdates",
+    "apt",
+    "dnf",
+    "flatpak",
+    "snap",
+    "rpm",
+    "tmux",
+    "git",
+    "rustc",
+    "cargo",
+    "curl",
+];
+
+#[cfg(target_os = "macos")]
+const TOOLS: &[&str] = &[
+    "sysctl",
+    "sw_vers",
+    "system_profiler",
+    "ioreg",
+    "vm_stat",
+    "osascript",
+    "ipconfig",
+    "tmux",
+    "git",
+    "rustc",
+    "cargo",
+    "curl",
+];
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+const TOOLS: &[&str] = &["wmic", "git", "rustc", "cargo", "curl"];
+
+/// Prints the config, cache and history paths, what was found out about
+/// the terminal, which tools are on $PATH, and then every probe with how
+/// long it took, what it found and which backend answered. Probes run
+/// whether or not `config` enables their field, each within its
+/// `probe_timeout_ms` or else `DEFAULT_TIMEOUT`, so one that hangs is
+/// reported instead of waited on. The SSID, local IP, tmux session and
+/// track playing are masked as `--screenshot-mode` does, since the output
+/// ends up in bug reports.
+pub fn print_doctor(config: &Configuration, config_path: Option<&str>) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout());
+
+    writeln!(out, "Paths")?;
+    let config_file = Configuration::config_file(config_path);
+    let config_file = match (config_path, &config_file) {
+        (Some("-"), _) => "stdin".to_string(),
+        (_, Some(path)) if path.is_file() => path.display().to_string(),
+        (_, Some(path)) => format!("{} (not found)", path.display()),
+        (_, None) => "none found, using defaults".to_string(),
+    };
+    writeln!(out, "  {:<15} {}", "config", config_file)?;
+    let cache_state = match cache::cache_age() {
+        Some(age) => format!("written {}s ago", age),
+        None => "not written".to_string(),
+    };
+    writeln!(
+        out,
+        "  {:<15} {}",
+        "cache",
+        path_state(cache::cache_file(), &cache_state)
+    )?;
+    let runs = format!("{} runs", history::read_history().len());
+    writeln!(
+        out,
+        "  {:<15} {}",
+        "history",
+        path_state(history::history_file(), &runs)
+    )?;
+
+    writeln!(out, "\nTerminal")?;
+    let var = |name: &str| env::var(name).unwrap_or_else(|_| "unset".to_string());
+    let yes_no = |b: bool| if b { "yes" } else { "no" };
+    writeln!(
+        out,
+        "  {:<15} {}",
+        "tty",
+        yes_no(io::stdout().is_terminal())
+    )?;
+    writeln!(out, "  {:<15} {}", "TERM", var("TERM"))?;
+    writeln!(out, "  {:<15} {}", "COLORTERM", var("COLORTERM"))?;
+    writeln!(out, "  {:<15} {}", "NO_COLOR", var("NO_COLOR"))?;
+    writeln!(out, "  {:<15} {}", "CLICOLOR", var("CLICOLOR"))?;
+    writeln!(out, "  {:<15} {}", "CLICOLOR_FORCE", var("CLICOLOR_FORCE"))?;
+    writeln!(out, "  {:<15} {}", "color", yes_no(config.color))?;
+    writeln!(
+        out,
+        "  {:<15} {}",
+        "unicode",
+        yes_no(display::unicode_terminal())
+    )?;
+    let background = match theme::background_is_light() {
+        Some(true) => "light",
+        Some(false) => "dark",
+        None => "unknown",
+    };
+    writeln!(out, "  {:<15} {}", "background", background)?;
+    let width = theme::terminal_width().map_or("unknown".to_string(), |w| w.to_string());
+    writeln!(out, "  {:<15} {}", "width", width)?;
+
+    writeln!(out, "\nTools")?;
+    for tool in TOOLS {
+        let path =
+            info::tool_path(tool).map_or("not found".to_string(), |p| p.display().to_string());
+        writeln!(out, "  {:<15} {}", tool, path)?;
+    }
+
+    writeln!(out, "\nProbes")?;
+    let mut core = config.clone();
+    core.apply_minimal_preset();
+    let start = Instant::now();
+    let mut sys = SystemInfo::default();
+    sys.populate_fast(&core);
+    let found = [
+        &sys.os_name,
+        &sys.kernel,
+        &sys.model,
+        &sys.cpu_model,
+        &sys.shell,
+    ]
+    .into_iter()
+    .filter(|s| !s.is_empty())
+    .cloned()
+    .collect::<Vec<_>>()
+    .join(", ");
+    writeln!(
+        out,
+        "  {:<15} {:>6} ms  {}",
+        "core",
+        start.elapsed().as_millis(),
+        found
+    )?;
+
+    let kernel = sys.kernel.clone();
+    probe(
+        &mut out,
+        config,
+        "kernel_check",
+        move || info::detect_kernel_status(&kernel),
+        |s| s.clone(),
+    )?;
+    probe(&mut out, config, "gpus", info::detect_gpus, |gpus| {
+        gpus.join(", ")
+    })?;
+    probe(
+        &mut out,
+        config,
+        "gpu_driver",
+        info::detect_gpu_driver,
+        |s| s.clone(),
+    )?;
+    probe(
+        &mut out,
+        config,
+        "resolution",
+        info::detect_displays,
+        |displays| {
+            displays
+                .iter()
+                .map(|d| format!("{}x{}@{}mHz", d.width, d.height, d.refresh))
+                .collect::<Vec<_>>()
+                .join(", ")
+        },
+    )?;
+    probe(&mut out, config, "scale", info::detect_scale, |scale| {
+        if *scale > 0.0 {
+            scale.to_string()
+        } else {
+            String::new()
+        }
+    })?;
+    probe(
+        &mut out,
+        config,
+        "color_depth",
+        info::detect_color_depth,
+        |(depth, hdr)| match depth {
+            0 => String::new(),
+            _ => format!("{} bit{}", depth, if *hdr { ", HDR" } else { "" }),
+        },
+    )?;
+    probe(
+        &mut out,
+        config,
+        "displays",
+        info::detect_monitors,
+        |monitors| {
+            monitors
+                .iter()
+                .map(|m| format!("{} {}x{}", m.name, m.width, m.height))
+                .collect::<Vec<_>>()
+                .join(", ")
+        },
+    )?;
+    probe(
+        &mut out,
+        config,
+        "pkgs",
+        info::detect_packages_fast,
+        |packages| {
+            packages
+                .iter()
+                .map(|p| format!("{} ({})", p.count, p.manager))
+                .collect::<Vec<_>>()
+                .join(", ")
+        },
+    )?;
+    probe(
+        &mut out,
+        config,
+        "updates",
+        info::detect_updates,
+        |updates| {
+            updates
+                .iter()
+                .map(|u| format!("{} ({})", u.count, u.manager))
+                .collect::<Vec<_>>()
+                .join(", ")
+        },
+    )?;
+    probe(&mut out, config, "wsl", info::detect_wsl, |s| s.clone())?;
+    probe(
+        &mut out,
+        config,
+        "virtualization",
+        info::detect_virtualization,
+        |s| s.clone(),
+    )?;
+    probe(&mut out, config, "de", info::detect_de, |s| s.clone())?;
+    probe(
+        &mut out,
+        config,
+        "display_server",
+        info::detect_display_server,
+        |s| s.clone(),
+    )?;
+    probe(
+        &mut out,
+        config,
+        "terminal",
+        info::detect_terminal,
+        |(terminal, mux)| {
+            [terminal.as_str(), mux.as_str()]
+                .iter()
+                .filter(|s| !s.is_empty())
+                .copied()
+                .collect::<Vec<_>>()
+                .join(", ")
+        },
+    )?;
+    probe(
+        &mut out,
+        config,
+        "session_uptime",
+        info::detect_session_uptime,
+        |secs| {
+            if *secs == 0 {
+                String::new()
+            } else {
+                format!("{}s", secs)
+            }
+        },
+    )?;
+    probe(&mut out, config, "fans", info::detect_fans, |fans| {
+        fans.iter()
+            .map(|f| format!("{} {} RPM", f.name, f.rpm))
+            .collect::<Vec<_>>()
+            .join(", ")
+    })?;
+    probe(
+        &mut out,
+        config,
+        "battery",
+        info::detect_batteries,
+        |batteries| {
+            batteries
+                .iter()
+                .map(|b| format!("{} {}% {}", b.name, b.capacity, b.status))
+                .collect::<Vec<_>>()
+                .join(", ")
+        },
+    )?;
+    probe(&mut out, config, "lid", info::detect_lid, |s| s.clone())?;
+    probe(&mut out, config, "mux", info::detect_mux_session, |s| {
+        masked(|info| info.mux_session = s.clone()).mux_session
+    })?;
+    let include_virtual = config.local_ip_virtual;
+    probe(
+        &mut out,
+        config,
+        "local_ip",
+        move || info::detect_local_ip(include_virtual),
+        |s| masked(|info| info.local_ip = s.clone()).local_ip,
+    )?;
+    probe(&mut out, config, "wifi", info::detect_wifi, |wifi| {
+        let wifi = masked(|info| info.wifi = wifi.clone()).wifi;
+        wifi.map_or(String::new(), |w| match w.signal {
+            Some(signal) => format!("{} ({}%)", w.ssid, signal),
+            None => w.ssid.clone(),
+        })
+    })?;
+    probe(&mut out, config, "audio", info::detect_audio, |s| s.clone())?;
+    probe(
+        &mut out,
+        config,
+        "now_playing",
+        info::detect_now_playing,
+        |playing| {
+            // Only whether a player answered matters here.
+            playing
+                .as_ref()
+                .map_or(String::new(), |_| "artist - title".to_string())
+        },
+    )?;
+    probe(&mut out, config, "steam", info::detect_steam, |steam| {
+        steam.as_ref().map_or(String::new(), |s| {
+            format!("{} games, {} bytes, {}", s.games, s.size, s.proton)
+        })
+    })?;
+    probe(&mut out, config, "dotfiles", info::detect_dotfiles, |s| {
+        s.clone()
+    })?;
+    probe(&mut out, config, "cursor", info::detect_cursor, |s| {
+        s.clone()
+    })?;
+    probe(&mut out, config, "rust", info::detect_rust, |s| s.clone())?;
+
+    let missing = info::missing_tools();
+    if !missing.is_empty() {
+        writeln!(out, "\nMissing tools")?;
+        for tool in missing {
+            writeln!(out, "  {}", tool)?;
+        }
+    }
+    out.flush()
+}
+
+/// How long a probe with no `probe_timeout_ms` of its own may take.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A `SystemInfo` with what `set` fills in, masked as for screenshots.
+fn masked(set: impl FnOnce(&mut SystemInfo)) -> SystemInfo {
+    let mut info = SystemInfo::default();
+    set(&mut info);
+    filter::hide_private(&mut info);
+    info
+}
+
+fn path_state(path: Option<PathBuf>, state: &str) -> String {
+    match path {
+        Some(path) if path.exists() => format!("{} ({})", path.display(), state),
+        Some(path) => format!("{} (not found)", path.display()),
+        None => "unknown, HOME isn't set".to_string(),
+    }
+}
+
+/// Runs one probe and prints a line for it: its name, how long it took,
+/// what it found (as `show` puts it) and the backend that answered, if it
+/// has a choice of them.
+fn probe<T: Send + 'static>(
+    out: &mut impl Write,
+    config: &Configuration,
+    field: &'static str,
+    probe: impl FnOnce() -> T + Send + 'static,
+    show: impl FnOnce(&T) -> String,
+) -> io::Result<()> {
+    let start = Instant::now();
+    let timeout = config.probe_timeout(field).unwrap_or(DEFAULT_TIMEOUT);
+    let result = info::within(Some(timeout), probe);
+    let elapsed = start.elapsed().as_millis();
+    // Only a backend that found something satisfied the field.
+    let backend = || {
+        info::backends()
+            .into_iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, backend)| format!(" [{}]", backend))
+            .unwrap_or_default()
+    };
+    let (value, backend) = match result.as_ref().map(show) {
+        None => ("timed out".to_string(), String::new()),
+        Some(value) if value.is_empty() => ("nothing found".to_string(), String::new()),
+        Some(value) => (value, backend()),
+    };
+    writeln!(
+        out,
+        "  {:<15} {:>6} ms  {}{}",
+        field, elapsed, value, backend
+    )?;
+    // Shown as they finish, so a slow one can be seen holding things up.
+    out.flush()
+}
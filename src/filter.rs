@@ -0,0 +1,276 @@
+use crate::config::{Configuration, PrimaryGpu};
+use crate::info::SystemInfo;
+use regex::{Regex, RegexBuilder};
+use std::sync::LazyLock;
+
+static CPU_CLEANUP_RULES: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        r"\((?i:r|tm)\)",
+        r"[®™]",
+        r"\s*@\s*[0-9.]+\s*[GM]Hz",
+        r"\b(?:Dual|Quad|Six|Eight|Twelve|Sixteen|[0-9]+)-Core\b",
+        r",\s*[0-9]+ Compute Cores.*",
+        r"\bCPU\b",
+        r"\bProcessor\b",
+    ]
+    .iter()
+    .filter_map(|p| Regex::new(p).ok())
+    .collect()
+});
+
+#[derive(Debug, Clone)]
+pub struct Filter {
+    pattern: Regex,
+    replacement: String,
+    global: bool,
+}
+
+impl Filter {
+    pub fn parse(expr: &str) -> Option<Self> {
+        let mut chars = expr.chars();
+        if chars.next()? != 's' {
+            return None;
+        }
+        let delim = chars.next()?;
+        if delim.is_alphanumeric() || delim == '\\' {
+            return None;
+        }
+
+        let mut parts = vec![String::new()];
+        let mut escaped = false;
+        for c in chars {
+            if escaped {
+                if c != delim {
+                    parts.last_mut()?.push('\\');
+                }
+                parts.last_mut()?.push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == delim {
+                parts.push(String::new());
+            } else {
+                parts.last_mut()?.push(c);
+            }
+        }
+        if escaped || parts.len() != 3 {
+            return None;
+        }
+
+        let flags = parts.pop()?;
+        let replacement = sed_to_regex_replacement(&parts.pop()?);
+        let pattern = parts.pop()?;
+
+        let mut global = false;
+        let mut builder = RegexBuilder::new(&pattern);
+        for flag in flags.chars() {
+            match flag {
+                'g' => global = true,
+                'i' => {
+                    builder.case_insensitive(true);
+                }
+                _ => return None,
+            }
+        }
+
+        Some(Filter {
+            pattern: builder.build().ok()?,
+            replacement,
+            global,
+        })
+    }
+
+    pub fn apply(&self, value: &mut String) {
+        let replaced = if self.global {
+            self.pattern.replace_all(value, self.replacement.as_str())
+        } else {
+            self.pattern.replace(value, self.replacement.as_str())
+        };
+        *value = replaced.trim().to_string();
+    }
+}
+
+fn sed_to_regex_replacement(sed: &str) -> String {
+    let mut out = String::with_capacity(sed.len());
+    let mut chars = sed.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '$' => out.push_str("$$"),
+            '&' => out.push_str("${0}"),
+            '\\' => match chars.next() {
+                Some(d) if d.is_ascii_digit() => {
+                    out.push_str("${");
+                    out.push(d);
+                    out.push('}');
+                }
+                Some('n') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            },
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+pub fn clean_cpu(cpu: &mut String) {
+    let mut cleaned = cpu.clone();
+    for rule in CPU_CLEANUP_RULES.iter() {
+        cleaned = rule.replace_all(&cleaned, "").into_owned();
+    }
+
+    let cleaned = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !cleaned.is_empty() {
+        *cpu = cleaned;
+    }
+}
+
+/// Whether a GPU name looks like one built into the CPU or SoC.
+fn is_integrated_gpu(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let any = |words: &[&str]| words.iter().any(|w| name.contains(w));
+
+    if name.contains("nvidia") {
+        return name.contains("tegra");
+    }
+    if name.contains("intel") {
+        return !any(&["dg1", "dg2", "battlemage", "arc a", "arc b"]);
+    }
+    if any(&["amd", "ati technologies", "radeon"]) {
+        return any(&[
+            "raven",
+            "picasso",
+            "renoir",
+            "lucienne",
+            "cezanne",
+            "barcelo",
+            "rembrandt",
+            "mendocino",
+            "van gogh",
+            "phoenix",
+            "hawk point",
+            "raphael",
+            "granite ridge",
+            "dragon range",
+            "strix",
+            "vega mobile",
+        ]);
+    }
+    any(&["mali", "adreno", "videocore", "apple", "powervr"])
+}
+
+/// Puts the primary GPU first, then keeps only the `gpu = N` selections.
+/// Detection already lists the boot GPU first, which is what `auto` means.
+pub fn select_gpus(config: &Configuration, gpus: &mut Vec<String>) {
+    match config.primary_gpu {
+        PrimaryGpu::Auto => {}
+        PrimaryGpu::Igpu => gpus.sort_by_key(|gpu| !is_integrated_gpu(gpu)),
+        PrimaryGpu::Dgpu => gpus.sort_by_key(|gpu| is_integrated_gpu(gpu)),
+    }
+
+    if !config.gpu_indexes.is_empty() {
+        *gpus = config
+            .gpu_indexes
+            .iter()
+            .filter_map(|&n| gpus.get(n.checked_sub(1)?).cloned())
+            .collect();
+    }
+}
+
+pub fn apply_filters(config: &Configuration, info: &mut SystemInfo) {
+    select_gpus(config, &mut info.gpu_models);
+
+    if config.clean_cpu {
+        clean_cpu(&mut info.cpu_model);
+    }
+
+    for (field, filter) in &config.filters {
+        match field.as_str() {
+            "user" => filter.apply(&mut info.user),
+            "hostname" => filter.apply(&mut info.host),
+            "host" | "model" => filter.apply(&mut info.model),
+            "kernel" => filter.apply(&mut info.kernel),
+            "cpu" => filter.apply(&mut info.cpu_model),
+            "gpu" | "gpus" => {
+                for gpu in &mut info.gpu_models {
+                    filter.apply(gpu);
+                }
+            }
+            "shell" => filter.apply(&mut info.shell),
+            "pkgs" => filter.apply(&mut info.pkgman_name),
+            _ => {}
+        }
+    }
+
+    if config.screenshot {
+        hide_private(info);
+    }
+}
+
+/// Swaps fields that identify the user or machine for fixed placeholders,
+/// for `--screenshot-mode`.
+pub fn hide_private(info: &mut SystemInfo) {
+    info.user = "user".to_string();
+    info.host = "uwufetch".to_string();
+    for id in [&mut info.serial, &mut info.asset_tag] {
+        if !id.is_empty() {
+            *id = "XXXXXXXX".to_string();
+        }
+    }
+    if !info.local_ip.is_empty() {
+        info.local_ip = "192.0.2.1 (eth0)".to_string();
+    }
+    if let Some(wifi) = &mut info.wifi {
+        wifi.ssid = "network".to_string();
+    }
+    // "tmux: name (3 windows)"
+    if let Some((mux, rest)) = info.mux_session.split_once(": ") {
+        let windows = rest.rfind(" (").map_or("", |i| &rest[i..]);
+        info.mux_session = format!("{}: session{}", mux, windows);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Filter;
+
+    fn filter(expr: &str, value: &str) -> String {
+        let mut value = value.to_string();
+        Filter::parse(expr).unwrap().apply(&mut value);
+        value
+    }
+
+    #[test]
+    fn replaces_first_or_all_matches() {
+        assert_eq!(filter("s/o/0/", "foo boo"), "f0o boo");
+        assert_eq!(filter("s/o/0/g", "foo boo"), "f00 b00");
+        assert_eq!(filter("s/FOO/bar/i", "foo"), "bar");
+        assert_eq!(filter("s/ Corporation//", "NVIDIA Corporation "), "NVIDIA");
+    }
+
+    #[test]
+    fn takes_any_delimiter_and_escapes_it() {
+        assert_eq!(filter("s|/usr/bin/||", "/usr/bin/fish"), "fish");
+        assert_eq!(filter(r"s/\/bin\///", "/usr/bin/fish"), "/usrfish");
+        assert_eq!(filter(r"s/\d+/N/g", "i7-1165G7"), "iN-NGN");
+    }
+
+    #[test]
+    fn translates_sed_replacements() {
+        assert_eq!(filter(r"s/(\w+) (\w+)/\2 \1/", "Intel Arc"), "Arc Intel");
+        assert_eq!(filter("s/Arc/[&]/", "Intel Arc"), "Intel [Arc]");
+        assert_eq!(filter("s/Arc/$1/", "Intel Arc"), "Intel $1");
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for expr in [
+            "", "y/a/b/", "s", "sa/b/c/", r"s\a\b\", "s/a/b", "s/a/b/c/", "s/a/b/x", "s/(/b/",
+            r"s/a/b/\",
+        ] {
+            assert!(Filter::parse(expr).is_none(), "{:?}", expr);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::color;
+use crate::info::{home_dir, SystemInfo};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: i64,
+    pub kernel: String,
+    pub pkgs: u32,
+    pub ram_used: u64,
+    pub ram_total: u64,
+}
+
+pub fn history_file() -> Option<PathBuf> {
+    home_dir().map(|home| PathBuf::from(home).join(".cache/uwufetch.history"))
+}
+
+pub fn append_history(info: &SystemInfo) {
+    let Some(path) = history_file() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+
+    let entry = HistoryEntry {
+        timestamp: chrono::Local::now().timestamp(),
+        kernel: info.kernel.clone(),
+        pkgs: info.pkgs,
+        ram_used: info.ram_used,
+        ram_total: info.ram_total,
+    };
+
+    if let Ok(line) = serde_json::to_string(&entry) {
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+pub fn read_history() -> Vec<HistoryEntry> {
+    history_file()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn print_history(color: bool) -> io::Result<()> {
+    let entries = read_history();
+    let mut out = Vec::new();
+
+    if entries.is_empty() {
+        writeln!(
+            &mut out,
+            "No history recorded yet, set `history = true` in your config"
+        )?;
+        return color::write_stdout(&out, color);
+    }
+
+    let mut prev: Option<&HistoryEntry> = None;
+    for (i, entry) in entries.iter().enumerate() {
+        let changed = prev.is_none_or(|p| p.kernel != entry.kernel || p.pkgs != entry.pkgs);
+        if !changed && i != entries.len() - 1 {
+            prev = Some(entry);
+            continue;
+        }
+
+        let time = chrono::DateTime::from_timestamp(entry.timestamp, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default();
+
+        let kernel = match prev {
+            Some(p) if p.kernel != entry.kernel => {
+                format!("{} (was {})", entry.kernel.green().bold(), p.kernel)
+            }
+            _ => entry.kernel.clone(),
+        };
+
+        let pkgs_delta = match prev {
+            Some(p) if entry.pkgs > p.pkgs => {
+                format!(" (+{})", entry.pkgs - p.pkgs).green().to_string()
+            }
+            Some(p) if entry.pkgs < p.pkgs => {
+                format!(" (-{})", p.pkgs - entry.pkgs).red().to_string()
+            }
+            _ => String::new(),
+        };
+
+        writeln!(
+            &mut out,
+            "{}  {} {}  {} {}{}  {} {} MiB/{} MiB",
+            time.bold(),
+            "KEWNEL".bold(),
+            kernel,
+            "PKGS".bold(),
+            entry.pkgs,
+            pkgs_delta,
+            "MEMOWY".bold(),
+            entry.ram_used,
+            entry.ram_total
+        )?;
+
+        prev = Some(entry);
+    }
+
+    color::write_stdout(&out, color)
+}
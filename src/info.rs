@@ -1,83 +1,775 @@
 #![allow(unreachable_code)]
 
-use crate::config::Configuration;
+use crate::config::{Configuration, MemoryMode, Toggle};
+use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs::{self, read_dir};
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
 use std::thread;
+use std::time::Duration;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SystemInfo {
     pub user: String,
     pub host: String,
     pub os_name: String,
     pub kernel: String,
+    pub kernel_status: String,
     pub model: String,
     pub cpu_model: String,
+    pub cpu_cores: u32,
+    pub cpu_threads: u32,
+    pub cpu_max_mhz: u32,
     pub gpu_models: Vec<String>,
+    pub gpu_driver: String,
     pub ram_total: u64,
     pub ram_used: u64,
-    pub screen_width: u32,
-    pub screen_height: u32,
+    pub displays: Vec<Display>,
+    pub screen_scale: f32,
+    pub color_depth: u32,
+    pub hdr: bool,
     pub shell: String,
+    pub de: String,
+    pub terminal: String,
+    pub multiplexer: String,
     pub pkgs: u32,
     pub pkgman_name: String,
+    pub packages: Vec<Packages>,
     pub uptime: u64,
+    pub session_uptime: u64,
+    pub fans: Vec<Fan>,
+    pub batteries: Vec<Battery>,
+    /// "open" or "closed"
+    pub lid: String,
+    pub updates: Vec<Updates>,
+    pub mux_session: String,
+    pub local_ip: String,
+    pub wifi: Option<Wifi>,
+    pub audio: String,
+    pub now_playing: Option<NowPlaying>,
+    pub steam: Option<Steam>,
+    pub dotfiles: String,
+    pub cursor: String,
+    pub rust: String,
+    pub display_server: String,
+    pub virtualization: String,
+    pub wsl: String,
     pub image_name: Option<String>,
+    pub monitors: Vec<Monitor>,
+    pub chassis: Chassis,
+    pub microcode: String,
+    pub cpu_vulns: String,
+    pub kernel_params: String,
+    pub bootloader: String,
+    pub sched: String,
+    pub virt_support: String,
+    pub serial: String,
+    pub asset_tag: String,
+    pub uptime_record: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Chassis {
+    #[default]
+    Unknown,
+    Desktop,
+    Laptop,
+    Convertible,
+    Tablet,
+    Handset,
+    Server,
+    Embedded,
+    Vm,
+    Container,
+}
+
+impl From<String> for Chassis {
+    fn from(value: String) -> Self {
+        Chassis::parse(&value)
+    }
+}
+
+impl From<Chassis> for String {
+    fn from(chassis: Chassis) -> Self {
+        chassis.as_str().to_string()
+    }
+}
+
+impl Chassis {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Chassis::Unknown => "unknown",
+            Chassis::Desktop => "desktop",
+            Chassis::Laptop => "laptop",
+            Chassis::Convertible => "convertible",
+            Chassis::Tablet => "tablet",
+            Chassis::Handset => "handset",
+            Chassis::Server => "server",
+            Chassis::Embedded => "embedded",
+            Chassis::Vm => "vm",
+            Chassis::Container => "container",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "desktop" => Chassis::Desktop,
+            "laptop" => Chassis::Laptop,
+            "convertible" => Chassis::Convertible,
+            "tablet" => Chassis::Tablet,
+            "handset" => Chassis::Handset,
+            "server" => Chassis::Server,
+            "embedded" => Chassis::Embedded,
+            "vm" => Chassis::Vm,
+            "container" => Chassis::Container,
+            _ => Chassis::Unknown,
+        }
+    }
+
+    pub fn icon(&self) -> Option<&'static str> {
+        match self {
+            Chassis::Unknown => None,
+            Chassis::Desktop => Some("\u{f108}"),
+            Chassis::Laptop => Some("\u{f109}"),
+            Chassis::Convertible | Chassis::Tablet => Some("\u{f10a}"),
+            Chassis::Handset => Some("\u{f10b}"),
+            Chassis::Server => Some("\u{f233}"),
+            Chassis::Embedded => Some("\u{f2db}"),
+            Chassis::Vm => Some("\u{f1b3}"),
+            Chassis::Container => Some("\u{f1b2}"),
+        }
+    }
+
+    /// Whether it's carried around, and so likely to have a battery and a
+    /// lid. `None` when the chassis isn't known.
+    pub fn portable(self) -> Option<bool> {
+        match self {
+            Chassis::Unknown => None,
+            Chassis::Laptop | Chassis::Convertible | Chassis::Tablet | Chassis::Handset => {
+                Some(true)
+            }
+            Chassis::Desktop
+            | Chassis::Server
+            | Chassis::Embedded
+            | Chassis::Vm
+            | Chassis::Container => Some(false),
+        }
+    }
+}
+
+/// How many packages one package manager has installed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Packages {
+    pub manager: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Updates {
+    pub manager: String,
+    pub count: u32,
+}
+
+/// The wireless network we're connected to. `signal` is a percentage.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Wifi {
+    pub ssid: String,
+    pub signal: Option<u32>,
+}
+
+/// Installed Steam games. `size` is their total size on disk in bytes, and
+/// `proton` the compatibility tool Steam runs Windows games with.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Steam {
+    pub games: u32,
+    pub size: u64,
+    pub proton: String,
+}
+
+/// The track a media player is currently playing. `artist` may be empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NowPlaying {
+    pub artist: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Fan {
+    pub name: String,
+    pub rpm: u32,
+}
+
+/// A battery's charge as a percentage, and whether it's charging,
+/// discharging, full or not charging.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Battery {
+    pub name: String,
+    pub capacity: u32,
+    pub status: String,
+}
+
+/// A display's current mode. `refresh` is in millihertz, 0 when unknown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Display {
+    pub width: u32,
+    pub height: u32,
+    pub refresh: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Monitor {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A field from one of the probes slow enough to be worth drawing
+/// without, see `spawn_slow`.
+#[derive(Debug, Clone)]
+pub enum SlowField {
+    Gpus(Vec<String>),
+    Packages(Vec<Packages>),
+    Updates(Vec<Updates>),
+}
+
+/// Starts the slow probes the config asks for on their own threads. Each
+/// result is sent as soon as it's ready, and the channel closes once all
+/// of them have reported. A probe that runs past its timeout reports
+/// nothing found.
+pub fn spawn_slow(config: &Configuration) -> mpsc::Receiver<SlowField> {
+    let (tx, rx) = mpsc::channel();
+    if config.show_gpu {
+        let tx = tx.clone();
+        let timeout = config.probe_timeout("gpus");
+        thread::spawn(move || tx.send(SlowField::Gpus(within(timeout, gpus).unwrap_or_default())));
+    }
+    if config.show_pkgs {
+        let tx = tx.clone();
+        let timeout = config.probe_timeout("pkgs");
+        thread::spawn(move || {
+            tx.send(SlowField::Packages(
+                within(timeout, packages).unwrap_or_default(),
+            ))
+        });
+    }
+    if config.show_updates {
+        let tx = tx.clone();
+        let timeout = config.probe_timeout("updates");
+        thread::spawn(move || {
+            tx.send(SlowField::Updates(
+                within(timeout, detect_updates).unwrap_or_default(),
+            ))
+        });
+    }
+    rx
+}
+
+/// A field `SystemInfo::collect` can be asked for. Most fill in the
+/// `SystemInfo` field of the same name; the rest say which in their doc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Field {
+    User,
+    Host,
+    Os,
+    Kernel,
+    KernelStatus,
+    Model,
+    Chassis,
+    Serial,
+    /// `cpu_model`
+    Cpu,
+    /// `cpu_cores`, `cpu_threads` and `cpu_max_mhz`
+    CpuTopology,
+    CpuVulns,
+    KernelParams,
+    Bootloader,
+    Sched,
+    VirtSupport,
+    /// `gpu_models`
+    Gpus,
+    GpuDriver,
+    /// `ram_total` and `ram_used`
+    Memory,
+    Displays,
+    /// `screen_scale`
+    Scale,
+    /// `color_depth` and `hdr`
+    ColorDepth,
+    Monitors,
+    Shell,
+    De,
+    DisplayServer,
+    /// `terminal` and `multiplexer`
+    Terminal,
+    /// `packages`, `pkgs` and `pkgman_name`
+    Packages,
+    Updates,
+    Uptime,
+    SessionUptime,
+    Fans,
+    /// `batteries`
+    Battery,
+    Lid,
+    /// `mux_session`
+    Mux,
+    LocalIp,
+    Wifi,
+    Audio,
+    NowPlaying,
+    Steam,
+    Dotfiles,
+    Cursor,
+    Rust,
+    Virtualization,
+}
+
+/// The fields to `SystemInfo::collect`, built up with `with`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FieldSet(u64);
+
+impl FieldSet {
+    /// No fields.
+    pub const fn new() -> Self {
+        FieldSet(0)
+    }
+
+    /// Every field.
+    pub const fn all() -> Self {
+        FieldSet(u64::MAX)
+    }
+
+    pub const fn with(self, field: Field) -> Self {
+        FieldSet(self.0 | 1 << field as u32)
+    }
+
+    pub const fn without(self, field: Field) -> Self {
+        FieldSet(self.0 & !(1 << field as u32))
+    }
+
+    pub const fn contains(self, field: Field) -> bool {
+        self.0 & 1 << field as u32 != 0
+    }
+
+    const fn union(self, other: FieldSet) -> Self {
+        FieldSet(self.0 | other.0)
+    }
+
+    const fn only(self, other: FieldSet) -> Self {
+        FieldSet(self.0 & other.0)
+    }
+}
+
+/// The fields `populate_live` detects afresh on a cached run.
+const LIVE_FIELDS: FieldSet = FieldSet::new()
+    .with(Field::Serial)
+    .with(Field::KernelStatus)
+    .with(Field::De)
+    .with(Field::DisplayServer)
+    .with(Field::Virtualization)
+    .with(Field::Terminal)
+    .with(Field::SessionUptime)
+    .with(Field::Fans)
+    .with(Field::Battery)
+    .with(Field::Lid)
+    .with(Field::Mux)
+    .with(Field::LocalIp)
+    .with(Field::Wifi)
+    .with(Field::Audio)
+    .with(Field::NowPlaying)
+    .with(Field::Dotfiles);
+
+impl From<&Configuration> for FieldSet {
+    /// The fields `config` shows. The user, host, OS, kernel, model, CPU,
+    /// memory, shell and uptime are always in it, for the cache.
+    fn from(config: &Configuration) -> Self {
+        let fields = [
+            Field::User,
+            Field::Host,
+            Field::Os,
+            Field::Kernel,
+            Field::Model,
+            Field::Chassis,
+            Field::Cpu,
+            Field::Memory,
+            Field::Shell,
+            Field::Uptime,
+        ];
+        let shown = [
+            (Field::KernelStatus, config.kernel_check),
+            (Field::Serial, config.show_serial),
+            (Field::CpuTopology, config.cpu_cores || config.cpu_freq),
+            (Field::CpuVulns, config.show_cpu_vulns),
+            (Field::KernelParams, config.show_kernel_params),
+            (Field::Bootloader, config.show_bootloader),
+            (Field::Sched, config.show_sched),
+            (Field::VirtSupport, config.show_virt),
+            (Field::Gpus, config.show_gpu),
+            (Field::GpuDriver, config.show_gpu_driver),
+            (Field::Displays, config.show_resolution),
+            (Field::Scale, config.show_resolution && config.show_scale),
+            (
+                Field::ColorDepth,
+                config.show_resolution && config.show_color_depth,
+            ),
+            (Field::Monitors, config.show_displays),
+            (Field::De, config.show_de),
+            (Field::DisplayServer, config.show_display_server),
+            (Field::Terminal, config.show_terminal),
+            (Field::Packages, config.show_pkgs),
+            (Field::Updates, config.show_updates),
+            (Field::SessionUptime, config.show_session_uptime),
+            (Field::Fans, config.show_fans),
+            // Whether they're shown on `Toggle::Auto` depends on the
+            // chassis, which isn't known yet.
+            (Field::Battery, config.show_battery != Toggle::Off),
+            (Field::Lid, config.show_lid != Toggle::Off),
+            (Field::Mux, config.show_mux),
+            (Field::LocalIp, config.show_local_ip),
+            (Field::Wifi, config.show_wifi),
+            (Field::Audio, config.show_audio),
+            (Field::NowPlaying, config.show_now_playing),
+            (Field::Steam, config.show_steam),
+            (Field::Dotfiles, config.show_dotfiles),
+            (Field::Cursor, config.show_cursor),
+            (Field::Rust, config.show_rust),
+            // A cache can be shared with containers that mount $HOME.
+            (
+                Field::Virtualization,
+                config.show_virtualization || config.show_host,
+            ),
+        ];
+        fields.into_iter().collect::<FieldSet>().union(
+            shown
+                .into_iter()
+                .filter(|(_, shown)| *shown)
+                .map(|(field, _)| field)
+                .collect(),
+        )
+    }
+}
+
+impl FromIterator<Field> for FieldSet {
+    fn from_iter<I: IntoIterator<Item = Field>>(fields: I) -> Self {
+        fields.into_iter().fold(FieldSet::new(), FieldSet::with)
+    }
+}
+
+/// Runs `probe`, or with a timeout runs it on a thread of its own and
+/// gives up on it once the timeout passes. A probe that's given up on is
+/// left to finish in the background and its result dropped.
+pub fn within<T: Send + 'static>(
+    timeout: Option<Duration>,
+    probe: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    match timeout {
+        Some(timeout) => spawn_probe(probe).recv_timeout(timeout).ok(),
+        None => Some(probe()),
+    }
+}
+
+fn spawn_probe<T: Send + 'static>(probe: impl FnOnce() -> T + Send + 'static) -> mpsc::Receiver<T> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(probe());
+    });
+    rx
+}
+
+/// Waits for a probe started with `spawn_probe`, for at most `timeout`.
+fn wait_probe<T>(probe: mpsc::Receiver<T>, timeout: Option<Duration>) -> Option<T> {
+    match timeout {
+        Some(timeout) => probe.recv_timeout(timeout).ok(),
+        None => probe.recv().ok(),
+    }
 }
 
 impl SystemInfo {
     pub fn populate(&mut self, config: &Configuration) {
-        self.get_user_host_fast();
-        if self.os_name.is_empty() {
-            self.get_os_info();
-        }
-        self.get_kernel_fast();
-        self.get_resolution();
-        self.get_model();
-        self.get_cpu();
-        self.get_memory();
-        self.get_shell();
-        self.get_uptime();
-
-        let gpu_handle = if config.show_gpu {
-            Some(thread::spawn(detect_gpus))
-        } else {
-            None
-        };
-        let res_handle = if config.show_resolution {
-            Some(thread::spawn(detect_resolution))
-        } else {
-            None
-        };
-        let pkgs_handle = if config.show_pkgs {
-            Some(thread::spawn(detect_packages_fast))
-        } else {
-            None
-        };
+        let slow = spawn_slow(config);
+        self.populate_fast(config);
+        for field in slow {
+            self.apply_slow(field);
+        }
+    }
 
-        if let Some(h) = gpu_handle {
-            if let Ok(gpus) = h.join() {
-                self.gpu_models = gpus;
-            }
+    /// Detects just `fields`, for embedders like greeters and bars that
+    /// show a few fields and shouldn't wait on the rest:
+    ///
+    /// ```no_run
+    /// use uwufetch::info::{Field, FieldSet, SystemInfo};
+    ///
+    /// let fields = FieldSet::new().with(Field::Os).with(Field::Memory);
+    /// let info = SystemInfo::collect(fields);
+    /// println!("{}: {} of {} MiB used", info.os_name, info.ram_used, info.ram_total);
+    /// ```
+    ///
+    /// Probes run as a default config would have them, with no timeouts.
+    /// The slow ones run in parallel with the rest. A field another one is
+    /// derived from may be filled in along with it, like the model with
+    /// the chassis.
+    pub fn collect(fields: FieldSet) -> SystemInfo {
+        let mut info = SystemInfo::default();
+        info.collect_into(fields, &Configuration::default());
+        info
+    }
+
+    /// Detects `fields` into `self`, with the probe timeouts, memory mode
+    /// and other probe settings from `config`.
+    fn collect_into(&mut self, fields: FieldSet, config: &Configuration) {
+        let has = |field| fields.contains(field);
+        let timeout = |name| config.probe_timeout(name);
+
+        let gpus_handle = has(Field::Gpus).then(|| spawn_probe(gpus));
+        let packages_handle = has(Field::Packages).then(|| spawn_probe(packages));
+        let updates_handle = has(Field::Updates).then(|| spawn_probe(detect_updates));
+        let displays_handle = has(Field::Displays).then(|| spawn_probe(displays));
+        let scale_handle = has(Field::Scale).then(|| spawn_probe(scale));
+        let depth_handle = has(Field::ColorDepth).then(|| spawn_probe(color_depth));
+        let monitors_handle = has(Field::Monitors).then(|| spawn_probe(monitors));
+
+        if has(Field::User) || has(Field::Host) {
+            let pretty = config.pretty_hostname;
+            self.get_within(timeout("user"), move |info| {
+                info.get_user_host_fast();
+                if pretty {
+                    if let Some(name) = pretty_hostname() {
+                        info.host = name;
+                    }
+                }
+            });
         }
-        if let Some(h) = res_handle {
-            if let Ok((w, hgt)) = h.join() {
-                self.screen_width = w;
-                self.screen_height = hgt;
-            }
+        // A distro override from the config is kept.
+        if has(Field::Os) && self.os_name.is_empty() {
+            self.get_within(timeout("os"), SystemInfo::get_os_info);
         }
-        if let Some(h) = pkgs_handle {
-            if let Ok((total, label)) = h.join() {
-                self.pkgs = total;
-                self.pkgman_name = label;
-            }
+        // The kernel check on a cached run goes by the cached kernel.
+        if has(Field::Kernel) || has(Field::KernelStatus) && self.kernel.is_empty() {
+            self.get_within(timeout("kernel"), SystemInfo::get_kernel_fast);
+        }
+        if has(Field::Model) || has(Field::Chassis) {
+            let chassis = has(Field::Chassis);
+            self.get_within(timeout("host"), move |info| {
+                info.get_model();
+                if chassis {
+                    info.get_chassis();
+                }
+            });
+        }
+        if has(Field::Serial) {
+            self.get_within(timeout("serial"), SystemInfo::get_serial);
+        }
+        if has(Field::Cpu) || has(Field::CpuTopology) {
+            let (cpu, topology) = (has(Field::Cpu), has(Field::CpuTopology));
+            self.get_within(timeout("cpu"), move |info| {
+                if cpu {
+                    info.get_cpu();
+                }
+                if topology {
+                    info.get_cpu_topology();
+                }
+            });
+        }
+        if has(Field::CpuVulns) {
+            self.get_within(timeout("cpu_vulns"), SystemInfo::get_cpu_vulns);
+        }
+        if has(Field::KernelParams) {
+            self.get_within(timeout("kernel_params"), SystemInfo::get_kernel_params);
+        }
+        if has(Field::Bootloader) {
+            self.get_within(timeout("bootloader"), SystemInfo::get_bootloader);
+        }
+        if has(Field::Sched) {
+            self.get_within(timeout("sched"), SystemInfo::get_sched);
+        }
+        if has(Field::VirtSupport) {
+            self.get_within(timeout("virt"), SystemInfo::get_virt_support);
+        }
+        if has(Field::GpuDriver) {
+            self.gpu_driver = within(timeout("gpu_driver"), detect_gpu_driver).unwrap_or_default();
+        }
+        if has(Field::Steam) {
+            self.steam = within(timeout("steam"), detect_steam).flatten();
+        }
+        if has(Field::Cursor) {
+            self.cursor = within(timeout("cursor"), detect_cursor).unwrap_or_default();
+        }
+        if has(Field::Rust) {
+            self.rust = within(timeout("rust"), detect_rust).unwrap_or_default();
+        }
+        if has(Field::Memory) {
+            let memory_mode = config.memory_mode;
+            self.get_within(timeout("ram"), move |info| info.get_memory(memory_mode));
+        }
+        if has(Field::Shell) {
+            self.get_within(timeout("shell"), SystemInfo::get_shell);
+        }
+        if has(Field::Uptime) {
+            self.get_within(timeout("uptime"), SystemInfo::get_uptime);
+        }
+        if has(Field::KernelStatus) {
+            let kernel = self.kernel.clone();
+            self.kernel_status = within(timeout("kernel_check"), move || {
+                detect_kernel_status(&kernel)
+            })
+            .unwrap_or_default();
+        }
+        if has(Field::De) {
+            self.de = within(timeout("de"), detect_de).unwrap_or_default();
+        }
+        if has(Field::DisplayServer) {
+            self.display_server = detect_display_server();
+        }
+        if has(Field::Virtualization) {
+            self.virtualization =
+                within(timeout("virtualization"), detect_virtualization).unwrap_or_default();
+        }
+        if has(Field::Terminal) {
+            (self.terminal, self.multiplexer) =
+                within(timeout("terminal"), detect_terminal).unwrap_or_default();
+        }
+        if has(Field::SessionUptime) {
+            self.session_uptime =
+                within(timeout("session_uptime"), detect_session_uptime).unwrap_or_default();
+        }
+        if has(Field::Fans) {
+            self.fans = within(timeout("fans"), detect_fans).unwrap_or_default();
+        }
+        if has(Field::Battery) {
+            self.batteries = within(timeout("battery"), detect_batteries).unwrap_or_default();
+        }
+        if has(Field::Lid) {
+            self.lid = within(timeout("lid"), detect_lid).unwrap_or_default();
+        }
+        if has(Field::Mux) {
+            self.mux_session = within(timeout("mux"), detect_mux_session).unwrap_or_default();
+        }
+        if has(Field::LocalIp) {
+            let include_virtual = config.local_ip_virtual;
+            self.local_ip = within(timeout("local_ip"), move || {
+                detect_local_ip(include_virtual)
+            })
+            .unwrap_or_default();
+        }
+        if has(Field::Wifi) {
+            self.wifi = within(timeout("wifi"), detect_wifi).flatten();
+        }
+        if has(Field::Audio) {
+            self.audio = within(timeout("audio"), detect_audio).unwrap_or_default();
+        }
+        if has(Field::NowPlaying) {
+            self.now_playing = within(timeout("now_playing"), detect_now_playing).flatten();
+        }
+        if has(Field::Dotfiles) {
+            self.dotfiles = within(timeout("dotfiles"), detect_dotfiles).unwrap_or_default();
+        }
+
+        if let Some(gpus) = gpus_handle.and_then(|h| wait_probe(h, timeout("gpus"))) {
+            self.gpu_models = gpus;
+        }
+        if let Some(packages) = packages_handle.and_then(|h| wait_probe(h, timeout("pkgs"))) {
+            self.set_packages(packages);
+        }
+        if let Some(updates) = updates_handle.and_then(|h| wait_probe(h, timeout("updates"))) {
+            self.updates = updates;
+        }
+        if let Some(displays) = displays_handle.and_then(|h| wait_probe(h, timeout("resolution"))) {
+            self.displays = displays;
+        }
+        if let Some(scale) = scale_handle.and_then(|h| wait_probe(h, timeout("scale"))) {
+            self.screen_scale = scale;
+        }
+        if let Some((depth, hdr)) = depth_handle.and_then(|h| wait_probe(h, timeout("color_depth")))
+        {
+            self.color_depth = depth;
+            self.hdr = hdr;
+        }
+        if let Some(monitors) = monitors_handle.and_then(|h| wait_probe(h, timeout("displays"))) {
+            self.monitors = monitors;
+        }
+    }
+
+    pub fn apply_slow(&mut self, field: SlowField) {
+        match field {
+            SlowField::Gpus(gpus) => self.gpu_models = gpus,
+            SlowField::Packages(packages) => self.set_packages(packages),
+            SlowField::Updates(updates) => self.updates = updates,
+        }
+    }
+
+    /// Stores the per-manager counts along with the total and the
+    /// "1204 (pacman), 12 (flatpak)" label that's shown.
+    pub fn set_packages(&mut self, packages: Vec<Packages>) {
+        self.pkgs = packages.iter().map(|p| p.count).sum();
+        self.pkgman_name = packages
+            .iter()
+            .map(|p| format!("{} ({})", p.count, p.manager))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.packages = packages;
+    }
+
+    /// Everything `populate` fills in except the fields `spawn_slow` probes.
+    pub fn populate_fast(&mut self, config: &Configuration) {
+        let fields = FieldSet::from(config)
+            .without(Field::Gpus)
+            .without(Field::Packages)
+            .without(Field::Updates);
+        self.collect_into(fields, config);
+    }
+
+    /// The fields that aren't cached, because they depend on where we're
+    /// run from, change from one minute to the next or (the serial) aren't
+    /// worth leaving on disk. A cached run detects these afresh.
+    pub fn populate_live(&mut self, config: &Configuration) {
+        self.collect_into(FieldSet::from(config).only(LIVE_FIELDS), config);
+    }
+
+    /// Runs one of the `get_*` probes within `timeout`, on a copy of what's
+    /// been found so far that replaces it once the probe is done. If it
+    /// runs out of time, the fields it fills in stay empty.
+    fn get_within(
+        &mut self,
+        timeout: Option<Duration>,
+        get: impl FnOnce(&mut SystemInfo) + Send + 'static,
+    ) {
+        if timeout.is_none() {
+            get(self);
+            return;
+        }
+        let mut info = self.clone();
+        if let Some(info) = within(timeout, move || {
+            get(&mut info);
+            info
+        }) {
+            *self = info;
         }
     }
 
     fn get_user_host_fast(&mut self) {
-        if let Ok(user) = std::env::var("USER") {
+        #[cfg(target_os = "windows")]
+        {
+            self.user = windows_user().unwrap_or_default();
+            self.host = windows_hostname().unwrap_or_default();
+            return;
+        }
+
+        #[cfg(unix)]
+        if let Some(user) = current_user() {
             self.user = user;
         }
 
@@ -97,6 +789,7 @@ impl SystemInfo {
 
     fn get_os_info(&mut self) {
         self.os_name = Self::detect_distro();
+        self.wsl = detect_wsl();
     }
 
     fn detect_distro() -> String {
@@ -104,8 +797,8 @@ impl SystemInfo {
         {
             if let Ok(content) = fs::read_to_string("/etc/os-release") {
                 for line in content.lines() {
-                    if line.starts_with("ID=") {
-                        return line[3..].trim_matches('"').to_string();
+                    if let Some(id) = line.strip_prefix("ID=") {
+                        return id.trim_matches('"').to_string();
                     }
                 }
             }
@@ -122,16 +815,47 @@ impl SystemInfo {
         }
 
         #[cfg(target_os = "macos")]
-        return "macos".to_string();
+        {
+            if let Ok(output) = Command::new("sw_vers").arg("-productVersion").output() {
+                let version = String::from_utf8_lossy(&output.stdout);
+                if let Some(major) = version.trim().split('.').next().filter(|m| !m.is_empty()) {
+                    return format!("macos{}", major);
+                }
+            }
+            return "macos".to_string();
+        }
 
         #[cfg(target_os = "freebsd")]
-        return "freebsd".to_string();
+        {
+            // Both derivatives report themselves as FreeBSD in uname.
+            if Command::new("sysctl")
+                .args(["-n", "hardening.version"])
+                .output()
+                .is_ok_and(|o| o.status.success())
+            {
+                return "hardenedbsd".to_string();
+            }
+            if Path::new("/etc/version").exists() {
+                return "ghostbsd".to_string();
+            }
+            return "freebsd".to_string();
+        }
 
         #[cfg(target_os = "openbsd")]
         return "openbsd".to_string();
 
+        #[cfg(target_os = "netbsd")]
+        return "netbsd".to_string();
+
+        #[cfg(target_os = "dragonfly")]
+        return "dragonfly".to_string();
+
         #[cfg(target_os = "windows")]
-        return "windows".to_string();
+        return match windows_build() {
+            Some(build) if build >= 22000 => "windows11".to_string(),
+            Some(build) if build >= 10240 => "windows10".to_string(),
+            _ => "windows".to_string(),
+        };
 
         "unknown".to_string()
     }
@@ -153,39 +877,9 @@ impl SystemInfo {
 
         #[cfg(windows)]
         {
-            unsafe {
-                use windows::core::HSTRING;
-                use windows::Win32::System::Registry::*;
-
-                let key_path = HSTRING::from("SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion");
-                let mut hkey = Default::default();
-
-                if RegOpenKeyExW(HKEY_LOCAL_MACHINE, &key_path, Some(0), KEY_READ, &mut hkey)
-                    .is_ok()
-                {
-                    let build_name = HSTRING::from("CurrentBuildNumber");
-                    let mut build_buffer = [0u8; 256];
-                    let mut build_size = build_buffer.len() as u32;
-
-                    if RegQueryValueExW(
-                        hkey,
-                        &build_name,
-                        None,
-                        None,
-                        Some(build_buffer.as_mut_ptr()),
-                        Some(&mut build_size),
-                    )
-                    .is_ok()
-                    {
-                        if let Ok(build_str) =
-                            std::str::from_utf8(&build_buffer[..build_size as usize - 1])
-                        {
-                            self.kernel =
-                                format!("Windows NT Build {}", build_str.trim_end_matches('\0'));
-                            return;
-                        }
-                    }
-                }
+            if let Some(build) = windows_build() {
+                self.kernel = format!("Windows NT Build {}", build);
+                return;
             }
 
             self.kernel = "Windows NT".to_string();
@@ -214,6 +908,14 @@ impl SystemInfo {
 
         #[cfg(target_os = "macos")]
         {
+            if let Some(model) = iokit::services(c"IOPlatformExpertDevice")
+                .first()
+                .and_then(|device| device.string("model"))
+            {
+                self.model = model;
+                return;
+            }
+
             if let Ok(output) = Command::new("sysctl").arg("hw.model").output() {
                 let model = String::from_utf8_lossy(&output.stdout);
                 if let Some(model) = model.split(':').nth(1) {
@@ -224,42 +926,12 @@ impl SystemInfo {
 
         #[cfg(target_os = "windows")]
         {
-            unsafe {
-                use windows::core::HSTRING;
-                use windows::Win32::System::Registry::*;
-
-                let key_path =
-                    HSTRING::from("SYSTEM\\CurrentControlSet\\Control\\SystemInformation");
-                let mut hkey = Default::default();
-
-                if RegOpenKeyExW(HKEY_LOCAL_MACHINE, &key_path, Some(0), KEY_READ, &mut hkey)
-                    .is_ok()
-                {
-                    let model_name = HSTRING::from("SystemProductName");
-                    let mut model_buffer = [0u8; 512];
-                    let mut model_size = model_buffer.len() as u32;
-
-                    if RegQueryValueExW(
-                        hkey,
-                        &model_name,
-                        None,
-                        None,
-                        Some(model_buffer.as_mut_ptr()),
-                        Some(&mut model_size),
-                    )
-                    .is_ok()
-                    {
-                        if let Ok(model_str) =
-                            std::str::from_utf8(&model_buffer[..model_size as usize - 1])
-                        {
-                            let model = model_str.trim_end_matches('\0').trim();
-                            if !model.is_empty() {
-                                self.model = model.to_string();
-                                return;
-                            }
-                        }
-                    }
-                }
+            if let Some(model) = registry_string(
+                "SYSTEM\\CurrentControlSet\\Control\\SystemInformation",
+                "SystemProductName",
+            ) {
+                self.model = model;
+                return;
             }
 
             if let Ok(output) = Command::new("wmic")
@@ -276,85 +948,159 @@ impl SystemInfo {
                 }
             }
         }
+    }
 
+    fn get_chassis(&mut self) {
         #[cfg(target_os = "linux")]
         {
-            let model_files = [
-                "/sys/devices/virtual/dmi/id/product_version",
-                "/sys/devices/virtual/dmi/id/product_name",
-                "/sys/devices/virtual/dmi/id/board_name",
-            ];
-
-            for file in &model_files {
-                if let Ok(content) = fs::read_to_string(file) {
-                    let content = content.trim();
-                    if !content.is_empty() && content != "To Be Filled By O.E.M." {
-                        self.model = content.to_string();
+            if let Ok(content) = fs::read_to_string("/etc/machine-info") {
+                for line in content.lines() {
+                    if let Some(value) = line.strip_prefix("CHASSIS=") {
+                        self.chassis = Chassis::parse(value.trim_matches('"'));
                         return;
                     }
                 }
             }
+
+            if let Ok(vendor) = fs::read_to_string("/sys/class/dmi/id/sys_vendor") {
+                let vendor = vendor.trim();
+                if ["QEMU", "VMware, Inc.", "innotek GmbH", "Xen", "Parallels"]
+                    .iter()
+                    .any(|v| vendor.starts_with(v))
+                {
+                    self.chassis = Chassis::Vm;
+                    return;
+                }
+            }
+
+            // SMBIOS system enclosure type codes
+            if let Ok(code) = fs::read_to_string("/sys/class/dmi/id/chassis_type") {
+                self.chassis = match code.trim().parse::<u32>().unwrap_or(0) {
+                    3..=7 | 13 | 15 | 16 | 24 => Chassis::Desktop,
+                    8..=10 | 14 => Chassis::Laptop,
+                    11 => Chassis::Handset,
+                    30 => Chassis::Tablet,
+                    31 | 32 => Chassis::Convertible,
+                    17 | 23 | 28 | 29 => Chassis::Server,
+                    33..=36 => Chassis::Embedded,
+                    _ => Chassis::Unknown,
+                };
+            }
         }
 
         #[cfg(target_os = "macos")]
         {
-            if let Ok(output) = Command::new("sysctl").arg("hw.model").output() {
-                let model = String::from_utf8_lossy(&output.stdout);
-                if let Some(model) = model.split(':').nth(1) {
-                    self.model = model.trim().to_string();
-                }
-            }
+            self.chassis = if self.model.starts_with("MacBook") {
+                Chassis::Laptop
+            } else if self.model.starts_with("Xserve") {
+                Chassis::Server
+            } else if !self.model.is_empty() {
+                Chassis::Desktop
+            } else {
+                Chassis::Unknown
+            };
         }
     }
 
-    fn get_cpu(&mut self) {
-        #[cfg(target_os = "windows")]
-        {
-            unsafe {
-                use windows::core::HSTRING;
-                use windows::Win32::System::Registry::*;
+    fn get_serial(&mut self) {
+        let placeholder = |s: &str| {
+            s.is_empty()
+                || s == "0"
+                || s.eq_ignore_ascii_case("To Be Filled By O.E.M.")
+                || s.eq_ignore_ascii_case("Default string")
+                || s.eq_ignore_ascii_case("Not Specified")
+                || s.eq_ignore_ascii_case("System Serial Number")
+        };
 
-                let key_path = HSTRING::from("HARDWARE\\DESCRIPTION\\System\\CentralProcessor\\0");
-                let mut hkey = Default::default();
+        #[cfg(target_os = "linux")]
+        {
+            if let Ok(serial) = fs::read_to_string("/sys/class/dmi/id/product_serial") {
+                let serial = serial.trim();
+                if !placeholder(serial) {
+                    self.serial = serial.to_string();
+                }
+            }
+            if let Ok(tag) = fs::read_to_string("/sys/class/dmi/id/chassis_asset_tag") {
+                let tag = tag.trim();
+                if !placeholder(tag) {
+                    self.asset_tag = tag.to_string();
+                }
+            }
+        }
 
-                if RegOpenKeyExW(HKEY_LOCAL_MACHINE, &key_path, Some(0), KEY_READ, &mut hkey)
-                    .is_ok()
-                {
-                    let processor_name = HSTRING::from("ProcessorNameString");
-                    let mut cpu_buffer = [0u8; 512];
-                    let mut cpu_size = cpu_buffer.len() as u32;
-
-                    if RegQueryValueExW(
-                        hkey,
-                        &processor_name,
-                        None,
-                        None,
-                        Some(cpu_buffer.as_mut_ptr()),
-                        Some(&mut cpu_size),
-                    )
-                    .is_ok()
-                    {
-                        if let Ok(cpu_str) =
-                            std::str::from_utf8(&cpu_buffer[..cpu_size as usize - 1])
-                        {
-                            self.cpu_model = cpu_str.trim_end_matches('\0').trim().to_string();
-                            return;
+        #[cfg(target_os = "macos")]
+        {
+            if let Ok(output) = Command::new("ioreg")
+                .args(["-c", "IOPlatformExpertDevice", "-d", "2"])
+                .output()
+            {
+                let output = String::from_utf8_lossy(&output.stdout);
+                for line in output.lines() {
+                    if line.contains("\"IOPlatformSerialNumber\"") {
+                        if let Some(value) = line.split('=').nth(1) {
+                            let value = value.trim().trim_matches('"');
+                            if !placeholder(value) {
+                                self.serial = value.to_string();
+                            }
                         }
                     }
                 }
             }
+        }
 
-            if let Ok(output) = Command::new("wmic").args(["cpu", "get", "name"]).output() {
-                let cpu = String::from_utf8_lossy(&output.stdout);
-                for line in cpu.lines().skip(1) {
+        #[cfg(target_os = "windows")]
+        {
+            if let Ok(output) = Command::new("wmic")
+                .args(["bios", "get", "serialnumber"])
+                .output()
+            {
+                let output = String::from_utf8_lossy(&output.stdout);
+                for line in output.lines().skip(1) {
                     let line = line.trim();
-                    if !line.is_empty() && line != "Name" {
-                        self.cpu_model = line.to_string();
-                        return;
+                    if !placeholder(line) {
+                        self.serial = line.to_string();
+                        break;
                     }
                 }
             }
-
+            if let Ok(output) = Command::new("wmic")
+                .args(["systemenclosure", "get", "smbiosassettag"])
+                .output()
+            {
+                let output = String::from_utf8_lossy(&output.stdout);
+                for line in output.lines().skip(1) {
+                    let line = line.trim();
+                    if !placeholder(line) {
+                        self.asset_tag = line.to_string();
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    fn get_cpu(&mut self) {
+        #[cfg(target_os = "windows")]
+        {
+            if let Some(cpu) = registry_string(
+                "HARDWARE\\DESCRIPTION\\System\\CentralProcessor\\0",
+                "ProcessorNameString",
+            ) {
+                self.cpu_model = cpu;
+                return;
+            }
+
+            if let Ok(output) = Command::new("wmic").args(["cpu", "get", "name"]).output() {
+                let cpu = String::from_utf8_lossy(&output.stdout);
+                for line in cpu.lines().skip(1) {
+                    let line = line.trim();
+                    if !line.is_empty() && line != "Name" {
+                        self.cpu_model = line.to_string();
+                        return;
+                    }
+                }
+            }
+
             self.cpu_model = "Unknown CPU".to_string();
         }
 
@@ -363,20 +1109,66 @@ impl SystemInfo {
             if let Ok(content) = fs::read_to_string("/proc/cpuinfo") {
                 let mut brand = String::new();
                 let mut count = 0u32;
+                // (processor, physical id, core id) of every logical CPU
+                let mut cpus: Vec<(u32, String, String)> = Vec::new();
 
-                for line in content.lines() {
-                    if line.starts_with("model name") {
-                        if let Some(name) = line.split(':').nth(1) {
-                            brand = name.trim().to_string();
+                for block in content.split("\n\n") {
+                    let mut processor = None;
+                    let mut package = String::new();
+                    let mut core = String::new();
+                    for line in block.lines() {
+                        let Some((key, value)) = line.split_once(':') else {
+                            continue;
+                        };
+                        let value = value.trim();
+                        match key.trim() {
+                            "processor" => processor = value.parse().ok(),
+                            "physical id" => package = value.to_string(),
+                            "core id" => core = value.to_string(),
+                            "model name" => {
+                                brand = value.to_string();
+                                count += 1;
+                            }
+                            "microcode" if self.microcode.is_empty() => {
+                                self.microcode = value.to_string();
+                            }
+                            _ => {}
                         }
-                        count += 1;
+                    }
+                    if let Some(processor) = processor {
+                        cpus.push((processor, package, core));
                     }
                 }
 
-                if !brand.is_empty() {
-                    self.cpu_model = brand;
-                } else {
+                if brand.is_empty() {
                     self.cpu_model = format!("{} Cores", count);
+                    return;
+                }
+
+                let mut packages: Vec<&str> = cpus.iter().map(|(_, p, _)| p.as_str()).collect();
+                packages.sort();
+                packages.dedup();
+                self.cpu_model = if packages.len() > 1 {
+                    format!("{}x {}", packages.len(), brand)
+                } else {
+                    brand
+                };
+
+                // Hybrid Intel chips register a PMU per core type.
+                let cores_of = |kind: &str| {
+                    let list = fs::read_to_string(format!("/sys/devices/{}/cpus", kind)).ok()?;
+                    let ids = parse_cpu_list(&list);
+                    let mut cores: Vec<(&str, &str)> = cpus
+                        .iter()
+                        .filter(|(processor, _, _)| ids.contains(processor))
+                        .map(|(_, package, core)| (package.as_str(), core.as_str()))
+                        .collect();
+                    cores.sort();
+                    cores.dedup();
+                    Some(cores.len())
+                };
+                if let (Some(p), Some(e)) = (cores_of("cpu_core"), cores_of("cpu_atom")) {
+                    self.cpu_model = format!("{} ({}P + {}E)", self.cpu_model, p, e);
                 }
                 return;
             }
@@ -399,9 +1191,315 @@ impl SystemInfo {
         self.cpu_model = "Unknown CPU".to_string();
     }
 
-    fn get_memory(&mut self) {
+    /// Physical cores, logical threads and the highest clock any core can
+    /// reach, across every socket.
+    fn get_cpu_topology(&mut self) {
+        #[cfg(target_os = "linux")]
+        {
+            let mut cores: Vec<(String, String)> = Vec::new();
+            let mut threads = 0u32;
+            let mut max_khz = 0u32;
+            if let Ok(entries) = read_dir("/sys/devices/system/cpu") {
+                for entry in entries.flatten() {
+                    let name = entry.file_name();
+                    let name = name.to_string_lossy();
+                    if !name
+                        .strip_prefix("cpu")
+                        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+                    {
+                        continue;
+                    }
+
+                    let path = entry.path();
+                    let read = |file: &str| {
+                        fs::read_to_string(path.join(file))
+                            .map(|s| s.trim().to_string())
+                            .ok()
+                    };
+                    // Offline CPUs have no topology directory.
+                    let Some(core) = read("topology/core_id") else {
+                        continue;
+                    };
+                    threads += 1;
+                    cores.push((
+                        read("topology/physical_package_id").unwrap_or_default(),
+                        core,
+                    ));
+                    if let Some(khz) = read("cpufreq/cpuinfo_max_freq").and_then(|f| f.parse().ok())
+                    {
+                        max_khz = max_khz.max(khz);
+                    }
+                }
+            }
+            cores.sort();
+            cores.dedup();
+
+            self.cpu_cores = cores.len() as u32;
+            self.cpu_threads = threads;
+            self.cpu_max_mhz = max_khz / 1000;
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            let sysctl = |name: &str| -> u64 {
+                Command::new("sysctl")
+                    .args(["-n", name])
+                    .output()
+                    .ok()
+                    .and_then(|out| String::from_utf8_lossy(&out.stdout).trim().parse().ok())
+                    .unwrap_or(0)
+            };
+            self.cpu_cores = sysctl("hw.physicalcpu") as u32;
+            self.cpu_threads = sysctl("hw.logicalcpu") as u32;
+            // Only Intel Macs report a frequency.
+            self.cpu_max_mhz = (sysctl("hw.cpufrequency_max") / 1_000_000) as u32;
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            if let Ok(output) = Command::new("wmic")
+                .args([
+                    "cpu",
+                    "get",
+                    "NumberOfCores,NumberOfLogicalProcessors,MaxClockSpeed",
+                    "/value",
+                ])
+                .output()
+            {
+                // One block per socket.
+                for line in String::from_utf8_lossy(&output.stdout).lines() {
+                    let Some((key, value)) = line.trim().split_once('=') else {
+                        continue;
+                    };
+                    let value: u32 = value.parse().unwrap_or(0);
+                    match key {
+                        "NumberOfCores" => self.cpu_cores += value,
+                        "NumberOfLogicalProcessors" => self.cpu_threads += value,
+                        "MaxClockSpeed" => self.cpu_max_mhz = self.cpu_max_mhz.max(value),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    fn get_cpu_vulns(&mut self) {
+        #[cfg(target_os = "linux")]
+        {
+            if let Ok(entries) = read_dir("/sys/devices/system/cpu/vulnerabilities") {
+                let mut mitigated = 0u32;
+                let mut vulnerable = Vec::new();
+
+                for entry in entries.flatten() {
+                    if let Ok(status) = fs::read_to_string(entry.path()) {
+                        if status.starts_with("Vulnerable") {
+                            vulnerable.push(entry.file_name().to_string_lossy().to_string());
+                        } else if status.starts_with("Mitigation") {
+                            mitigated += 1;
+                        }
+                    }
+                }
+
+                vulnerable.sort();
+                self.cpu_vulns = if !vulnerable.is_empty() {
+                    format!("vulnerable: {}", vulnerable.join(", "))
+                } else if mitigated > 0 {
+                    "all mitigated".to_string()
+                } else {
+                    "not affected".to_string()
+                };
+            }
+        }
+    }
+
+    fn get_kernel_params(&mut self) {
+        #[cfg(target_os = "linux")]
+        {
+            if let Ok(cmdline) = fs::read_to_string("/proc/cmdline") {
+                // Everything after `--` is passed to init, not the kernel.
+                let notable: Vec<&str> = cmdline
+                    .split_whitespace()
+                    .take_while(|param| *param != "--")
+                    .filter(|param| {
+                        let key = param.split_once('=').map_or(*param, |(key, _)| key);
+                        NOTABLE_KERNEL_PARAMS.contains(&key)
+                    })
+                    .collect();
+                self.kernel_params = notable.join(" ");
+            }
+        }
+    }
+
+    /// The loader that booted us: what it reports through the Boot Loader
+    /// Interface, else the firmware's entry for the current boot, else
+    /// whichever loader has its files in /boot or the ESP.
+    fn get_bootloader(&mut self) {
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(info) = efi_string("LoaderInfo-4a67b082-0a4c-41cf-b6c7-440b29bb8c4f") {
+                self.bootloader = info;
+                return;
+            }
+            if let Some(name) = current_boot_entry().as_deref().and_then(bootloader_name) {
+                self.bootloader = name.to_string();
+                return;
+            }
+            const FILES: &[(&str, &str)] = &[
+                ("/boot/loader/loader.conf", "systemd-boot"),
+                ("/efi/loader/loader.conf", "systemd-boot"),
+                ("/boot/efi/loader/loader.conf", "systemd-boot"),
+                ("/boot/limine.conf", "Limine"),
+                ("/boot/limine/limine.conf", "Limine"),
+                ("/boot/limine.cfg", "Limine"),
+                ("/boot/refind_linux.conf", "rEFInd"),
+                ("/boot/EFI/refind", "rEFInd"),
+                ("/boot/efi/EFI/refind", "rEFInd"),
+                ("/boot/grub/grub.cfg", "GRUB"),
+                ("/boot/grub2/grub.cfg", "GRUB"),
+                ("/boot/syslinux", "Syslinux"),
+            ];
+            if let Some((_, name)) = FILES.iter().find(|(path, _)| Path::new(path).exists()) {
+                self.bootloader = name.to_string();
+            }
+        }
+    }
+
+    /// Preemption model, tick rate and CPU scheduler, e.g.
+    /// "PREEMPT_DYNAMIC (full), 1000 Hz, tickless idle, BORE".
+    fn get_sched(&mut self) {
+        #[cfg(target_os = "linux")]
+        {
+            let version = fs::read_to_string("/proc/version").unwrap_or_default();
+            let config = kernel_config();
+            let enabled = |key: &str| {
+                config
+                    .iter()
+                    .any(|line| line == &format!("CONFIG_{}=y", key))
+            };
+            let value = |key: &str| {
+                config
+                    .iter()
+                    .find_map(|line| line.strip_prefix(&format!("CONFIG_{}=", key)))
+            };
+
+            let mut parts = Vec::new();
+
+            let words: Vec<&str> = version.split_whitespace().collect();
+            let model = if words.contains(&"PREEMPT_RT") || enabled("PREEMPT_RT") {
+                Some("PREEMPT_RT".to_string())
+            } else if words.contains(&"PREEMPT_DYNAMIC") || enabled("PREEMPT_DYNAMIC") {
+                // Without a preempt= override it runs the model the
+                // kernel was configured with.
+                let mode = dynamic_preempt_mode().or_else(|| {
+                    [
+                        ("PREEMPT_NONE", "none"),
+                        ("PREEMPT_VOLUNTARY", "voluntary"),
+                        ("PREEMPT", "full"),
+                        ("PREEMPT_LAZY", "lazy"),
+                    ]
+                    .iter()
+                    .find(|(key, _)| enabled(key))
+                    .map(|(_, mode)| mode.to_string())
+                });
+                Some(match mode {
+                    Some(mode) => format!("PREEMPT_DYNAMIC ({})", mode),
+                    None => "PREEMPT_DYNAMIC".to_string(),
+                })
+            } else if words.contains(&"PREEMPT") || enabled("PREEMPT") {
+                Some("PREEMPT".to_string())
+            } else if enabled("PREEMPT_VOLUNTARY") {
+                Some("PREEMPT_VOLUNTARY".to_string())
+            } else if enabled("PREEMPT_NONE") {
+                Some("PREEMPT_NONE".to_string())
+            } else {
+                None
+            };
+            parts.extend(model);
+
+            if let Some(hz) = value("HZ") {
+                parts.push(format!("{} Hz", hz));
+            }
+            if enabled("NO_HZ_FULL") {
+                parts.push("full tickless".to_string());
+            } else if enabled("NO_HZ_IDLE") {
+                parts.push("tickless idle".to_string());
+            } else if enabled("HZ_PERIODIC") {
+                parts.push("periodic tick".to_string());
+            }
+
+            // A sched_ext scheduler replaces the built-in one while loaded.
+            let scx = fs::read_to_string("/sys/kernel/sched_ext/state")
+                .is_ok_and(|state| state.trim() == "enabled")
+                .then(|| fs::read_to_string("/sys/kernel/sched_ext/root/ops").ok())
+                .flatten();
+            if let Some(ops) = scx.filter(|ops| !ops.trim().is_empty()) {
+                parts.push(format!("scx_{}", ops.trim()));
+            } else if Path::new("/proc/sys/kernel/sched_bore").exists() {
+                parts.push("BORE".to_string());
+            }
+
+            self.sched = parts.join(", ");
+        }
+    }
+
+    fn get_virt_support(&mut self) {
+        #[cfg(target_os = "linux")]
+        {
+            if let Ok(content) = fs::read_to_string("/proc/cpuinfo") {
+                let flags = content
+                    .lines()
+                    .find(|l| l.starts_with("flags") || l.starts_with("Features"))
+                    .and_then(|l| l.split(':').nth(1))
+                    .unwrap_or("");
+                let tech = flags.split_whitespace().find_map(|f| match f {
+                    "vmx" => Some("VT-x"),
+                    "svm" => Some("AMD-V"),
+                    _ => None,
+                });
+                let kvm = Path::new("/dev/kvm").exists();
+
+                self.virt_support = match (tech, kvm) {
+                    (Some(t), true) => format!("{} (KVM ready)", t),
+                    (Some(t), false) => t.to_string(),
+                    (None, true) => "KVM ready".to_string(),
+                    (None, false) => "unavailable".to_string(),
+                };
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            if let Ok(output) = Command::new("sysctl").arg("kern.hv_support").output() {
+                let value = String::from_utf8_lossy(&output.stdout);
+                self.virt_support = match value.split(':').nth(1).map(str::trim) {
+                    Some("1") => "Hypervisor.framework".to_string(),
+                    _ => "unavailable".to_string(),
+                };
+            }
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            unsafe {
+                use windows::Win32::System::Threading::{
+                    IsProcessorFeaturePresent, PF_VIRT_FIRMWARE_ENABLED,
+                };
+
+                self.virt_support = if IsProcessorFeaturePresent(PF_VIRT_FIRMWARE_ENABLED).as_bool()
+                {
+                    "enabled".to_string()
+                } else {
+                    "disabled in firmware".to_string()
+                };
+            }
+        }
+    }
+
+    fn get_memory(&mut self, mode: MemoryMode) {
         #[cfg(target_os = "windows")]
         {
+            let _ = mode;
+
             unsafe {
                 use windows::Win32::System::SystemInformation::{
                     GlobalMemoryStatusEx, MEMORYSTATUSEX,
@@ -450,24 +1548,7 @@ impl SystemInfo {
         #[cfg(target_os = "linux")]
         {
             if let Ok(content) = fs::read_to_string("/proc/meminfo") {
-                let mut total = 0u64;
-                let mut available = 0u64;
-
-                for line in content.lines() {
-                    if line.starts_with("MemTotal:") {
-                        if let Some(val) = line.split_whitespace().nth(1) {
-                            total = val.parse().unwrap_or(0);
-                        }
-                    } else if line.starts_with("MemAvailable:") {
-                        if let Some(val) = line.split_whitespace().nth(1) {
-                            available = val.parse().unwrap_or(0);
-                        }
-                    }
-                }
-
-                self.ram_total = total / 1024;
-                self.ram_used = (total - available) / 1024;
-                return;
+                (self.ram_total, self.ram_used) = parse_meminfo(&content, mode);
             }
         }
 
@@ -487,6 +1568,7 @@ impl SystemInfo {
                 let mut active = 0u64;
                 let mut wired = 0u64;
                 let mut compressed = 0u64;
+                let mut free = 0u64;
 
                 for line in vm_output.lines() {
                     if let Some(val) = line.split_whitespace().last() {
@@ -498,25 +1580,48 @@ impl SystemInfo {
                                 wired = pages;
                             } else if line.contains("Pages occupied by compressor:") {
                                 compressed = pages;
+                            } else if line.contains("Pages free:") {
+                                free = pages;
                             }
                         }
                     }
                 }
 
                 let page_size = 4096u64;
-                self.ram_used = (active + wired + compressed) * page_size / 1024 / 1024;
+                self.ram_used = match mode {
+                    MemoryMode::Free => self
+                        .ram_total
+                        .saturating_sub(free * page_size / 1024 / 1024),
+                    MemoryMode::Available | MemoryMode::Classic => {
+                        (active + wired + compressed) * page_size / 1024 / 1024
+                    }
+                };
             }
             return;
         }
     }
 
-    fn get_resolution(&mut self) {
-        self.screen_width = detect_resolution().0;
-        self.screen_height = detect_resolution().1;
-    }
-
     fn get_shell(&mut self) {
-        if let Ok(shell) = std::env::var("SHELL") {
+        // $SHELL is the login shell; the nearest shell among our ancestors is
+        // the one actually running us.
+        if let Some(shell) = process_ancestors()
+            .iter()
+            .map(|name| process_base_name(name))
+            .find(|name| SHELLS.contains(&name.to_lowercase().as_str()))
+        {
+            self.shell = shell;
+            return;
+        }
+
+        #[cfg(unix)]
+        let login_shell = || passwd().map(|entry| entry.shell.clone());
+        #[cfg(not(unix))]
+        let login_shell = || None;
+        if let Some(shell) = std::env::var("SHELL")
+            .ok()
+            .filter(|shell| !shell.is_empty())
+            .or_else(login_shell)
+        {
             if let Some(shell_name) = shell.rsplit('/').next() {
                 self.shell = shell_name.to_string();
             }
@@ -526,12 +1631,7 @@ impl SystemInfo {
     fn get_uptime(&mut self) {
         #[cfg(target_os = "windows")]
         {
-            unsafe {
-                use windows::Win32::System::SystemInformation::GetTickCount64;
-
-                let tick_count = GetTickCount64();
-                self.uptime = tick_count / 1000;
-            }
+            self.uptime = windows_uptime();
         }
 
         #[cfg(target_os = "linux")]
@@ -540,7 +1640,6 @@ impl SystemInfo {
                 if let Some(uptime_str) = content.split_whitespace().next() {
                     if let Ok(uptime_f) = uptime_str.parse::<f64>() {
                         self.uptime = uptime_f as u64;
-                        return;
                     }
                 }
             }
@@ -561,242 +1660,3555 @@ impl SystemInfo {
     }
 }
 
-fn detect_gpus() -> Vec<String> {
+/// Boot parameters worth showing off: mitigations, memory compression,
+/// huge pages, IOMMU, CPU isolation and scheduling tweaks.
+#[cfg(target_os = "linux")]
+const NOTABLE_KERNEL_PARAMS: &[&str] = &[
+    "mitigations",
+    "nosmt",
+    "zswap.enabled",
+    "zswap.compressor",
+    "hugepages",
+    "hugepagesz",
+    "default_hugepagesz",
+    "transparent_hugepage",
+    "iommu",
+    "intel_iommu",
+    "amd_iommu",
+    "isolcpus",
+    "nohz_full",
+    "rcu_nocbs",
+    "preempt",
+    "threadirqs",
+    "amd_pstate",
+    "intel_pstate",
+    "pcie_aspm",
+    "split_lock_detect",
+];
+
+/// Every spinning fan with its current speed.
+pub fn detect_fans() -> Vec<Fan> {
     #[cfg(target_os = "linux")]
     {
-        if which("lspci") {
-            if let Ok(out) = Command::new("lspci").args(["-mm", "-nn"]).output() {
-                let mut gpus = Vec::<String>::new();
-                let s = String::from_utf8_lossy(&out.stdout);
-                for line in s.lines() {
-                    if line.contains("VGA compatible controller")
-                        || line.contains("3D controller")
-                        || line.contains("Display controller")
-                    {
-                        let parts: Vec<&str> = line.split('"').collect();
-                        if parts.len() >= 10 {
-                            let vendor = parts.get(5).unwrap_or(&"").trim();
-                            let device = parts.get(7).unwrap_or(&"").trim();
-                            let combo = format!("{} {}", vendor, device).trim().to_string();
-                            if !combo.is_empty() {
-                                gpus.push(combo);
-                                continue;
-                            }
-                        }
-                        gpus.push(line.to_string());
-                    }
-                }
-                return gpus;
-            }
+        // Unconnected headers on motherboard chips read as 0 RPM.
+        return hwmon_readings("fan")
+            .into_iter()
+            .filter(|(_, rpm)| *rpm > 0)
+            .map(|(name, rpm)| Fan {
+                name,
+                rpm: rpm as u32,
+            })
+            .collect();
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let Some(smc) = iokit::Smc::open() else {
+            return Vec::new();
+        };
+        let count = smc.read_number(b"FNum").unwrap_or(0.0) as u8;
+        return (0..count)
+            .filter_map(|i| {
+                let rpm = smc.read_number(&[b'F', b'0' + i, b'A', b'c'])?;
+                Some(Fan {
+                    name: format!("Fan {}", i + 1),
+                    rpm: rpm as u32,
+                })
+            })
+            .filter(|fan| fan.rpm > 0)
+            .collect();
+    }
+
+    #[allow(unreachable_code)]
+    Vec::new()
+}
+
+/// Every battery with its charge. Linux only, from the BAT* power
+/// supplies; wireless mice and keyboards that report theirs aren't
+/// counted.
+pub fn detect_batteries() -> Vec<Battery> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut supplies: Vec<_> = read_dir("/sys/class/power_supply")
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|entry| entry.file_name().to_string_lossy().starts_with("BAT"))
+                    .map(|entry| entry.path())
+                    .collect()
+            })
+            .unwrap_or_default();
+        supplies.sort();
+
+        return supplies
+            .into_iter()
+            .filter_map(|supply| {
+                let read = |name: &str| {
+                    fs::read_to_string(supply.join(name))
+                        .map(|value| value.trim().to_string())
+                        .ok()
+                };
+                Some(Battery {
+                    name: supply.file_name()?.to_string_lossy().into_owned(),
+                    capacity: read("capacity")?.parse().ok()?,
+                    status: read("status").unwrap_or_default().to_lowercase(),
+                })
+            })
+            .collect();
+    }
+
+    #[allow(unreachable_code)]
+    Vec::new()
+}
+
+/// Whether the lid is "open" or "closed", from ACPI. Linux only.
+pub fn detect_lid() -> String {
+    #[cfg(target_os = "linux")]
+    {
+        let mut lids: Vec<_> = read_dir("/proc/acpi/button/lid")
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        lids.sort();
+
+        return lids
+            .into_iter()
+            .find_map(|lid| {
+                let state = fs::read_to_string(lid.join("state")).ok()?;
+                lid_state(&state).map(str::to_string)
+            })
+            .unwrap_or_default();
+    }
+
+    #[allow(unreachable_code)]
+    String::new()
+}
+
+/// The state from an ACPI lid `state` file, like "state:      open".
+#[cfg(target_os = "linux")]
+fn lid_state(content: &str) -> Option<&str> {
+    let state = content.trim().strip_prefix("state:")?.trim();
+    ["open", "closed"].contains(&state).then_some(state)
+}
+
+/// Raw readings of one sensor kind (`fan`, `temp`, `in`, ...) from every
+/// hwmon chip, as `(label, value)`. Channels without a label file are
+/// named after their chip, e.g. `thinkpad fan1`.
+#[cfg(target_os = "linux")]
+fn hwmon_readings(kind: &str) -> Vec<(String, i64)> {
+    let mut chips: Vec<_> = read_dir("/sys/class/hwmon")
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    chips.sort();
+
+    let mut readings = Vec::new();
+    for chip in chips {
+        let chip_name = fs::read_to_string(chip.join("name"))
+            .map(|name| name.trim().to_string())
+            .unwrap_or_default();
+        let mut channels: Vec<String> = read_dir(&chip)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|entry| {
+                        let name = entry.file_name().to_string_lossy().into_owned();
+                        let channel = name.strip_suffix("_input")?;
+                        let index = channel.strip_prefix(kind)?;
+                        index.parse::<u32>().ok()?;
+                        Some(channel.to_string())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        channels.sort_by_key(|channel| channel[kind.len()..].parse::<u32>().unwrap_or(0));
+
+        for channel in channels {
+            let Some(value) = fs::read_to_string(chip.join(format!("{}_input", channel)))
+                .ok()
+                .and_then(|v| v.trim().parse::<i64>().ok())
+            else {
+                continue;
+            };
+            let label = fs::read_to_string(chip.join(format!("{}_label", channel)))
+                .map(|label| label.trim().to_string())
+                .ok()
+                .filter(|label| !label.is_empty())
+                .unwrap_or_else(|| format!("{} {}", chip_name, channel).trim().to_string());
+            readings.push((label, value));
         }
+    }
+    readings
+}
 
-        let mut gpus = Vec::<String>::new();
-        if let Ok(entries) = read_dir("/sys/class/drm") {
-            for entry in entries.flatten() {
-                let name = entry.file_name();
-                let name = name.to_string_lossy();
-                if !name.starts_with("card") {
-                    continue;
-                }
-                let uevent = entry.path().join("device/uevent");
-                if let Ok(txt) = fs::read_to_string(uevent) {
-                    let mut driver = None;
-                    for line in txt.lines() {
-                        if let Some(val) = line.strip_prefix("DRIVER=") {
-                            driver = Some(val.trim().to_string());
-                        }
-                    }
-                    if let Some(d) = driver {
-                        if !gpus.contains(&d) {
-                            gpus.push(d);
-                        }
-                    }
-                }
+/// Expands a sysfs CPU list like `0-7,16-23`.
+#[cfg(target_os = "linux")]
+fn parse_cpu_list(list: &str) -> Vec<u32> {
+    list.trim()
+        .split(',')
+        .filter_map(|range| match range.split_once('-') {
+            Some((start, end)) => Some((start.parse().ok()?..=end.parse().ok()?).collect()),
+            None => range.parse().ok().map(|cpu| vec![cpu]),
+        })
+        .flatten()
+        .collect()
+}
+
+/// Seconds since the current user logged in, or 0 when the session can't
+/// be found.
+pub fn detect_session_uptime() -> u64 {
+    let Some(start) = session_start() else {
+        return 0;
+    };
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs().saturating_sub(start))
+}
+
+/// The login time of the current session as a Unix timestamp. Prefers the
+/// logind session, then the utmp entry for our terminal, then the user's
+/// earliest login.
+fn session_start() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        if let Ok(id) = env::var("XDG_SESSION_ID") {
+            let session = fs::read_to_string(format!("/run/systemd/sessions/{}", id));
+            let realtime = session.ok().and_then(|content| {
+                content
+                    .lines()
+                    .find_map(|line| line.strip_prefix("REALTIME="))
+                    .and_then(|usec| usec.trim().parse::<u64>().ok())
+            });
+            if let Some(usec) = realtime {
+                return Some(usec / 1_000_000);
             }
         }
-        return gpus;
+
+        let user = current_user()?;
+        let utmp = fs::read("/var/run/utmp").ok()?;
+        return utmp_login(&utmp, &user, current_tty().as_deref());
     }
 
     #[cfg(target_os = "macos")]
     {
-        let mut gpus = Vec::<String>::new();
-        if let Ok(output) = Command::new("system_profiler")
-            .args(&["SPDisplaysDataType"])
-            .output()
-        {
-            let output = String::from_utf8_lossy(&output.stdout);
-            for line in output.lines() {
-                if let Some(rest) = line.strip_prefix("      Chipset Model: ") {
-                    gpus.push(rest.trim().to_string());
+        let user = current_user()?;
+        let tty = current_tty();
+        let mut logins = Vec::new();
+
+        unsafe {
+            libc::setutxent();
+            loop {
+                let entry = libc::getutxent();
+                if entry.is_null() {
+                    break;
+                }
+                let entry = &*entry;
+                if entry.ut_type != libc::USER_PROCESS {
+                    continue;
+                }
+                let text = |chars: &[libc::c_char]| {
+                    let bytes: Vec<u8> = chars
+                        .iter()
+                        .take_while(|&&c| c != 0)
+                        .map(|&c| c as u8)
+                        .collect();
+                    String::from_utf8_lossy(&bytes).into_owned()
+                };
+                if text(&entry.ut_user) == user {
+                    logins.push((text(&entry.ut_line), entry.ut_tv.tv_sec as u64));
                 }
             }
+            libc::endutxent();
         }
-        return gpus;
+
+        return logins
+            .iter()
+            .find(|(line, _)| tty.as_deref() == Some(line.as_str()))
+            .or_else(|| logins.iter().min_by_key(|(_, sec)| *sec))
+            .map(|(_, sec)| *sec);
     }
 
     #[cfg(target_os = "windows")]
     {
-        let mut gpus: Vec<String> = Vec::new();
+        use windows::core::PWSTR;
+        use windows::Win32::System::RemoteDesktop::{
+            WTSFreeMemory, WTSQuerySessionInformationW, WTSSessionInfo, WTSINFOW,
+            WTS_CURRENT_SERVER_HANDLE, WTS_CURRENT_SESSION,
+        };
 
+        let mut buffer = PWSTR::null();
+        let mut bytes = 0u32;
         unsafe {
-            use windows::core::HSTRING;
-            use windows::Win32::System::Registry::*;
+            WTSQuerySessionInformationW(
+                Some(WTS_CURRENT_SERVER_HANDLE),
+                WTS_CURRENT_SESSION,
+                WTSSessionInfo,
+                &mut buffer,
+                &mut bytes,
+            )
+        }
+        .ok()?;
+        let logon = (bytes as usize >= std::mem::size_of::<WTSINFOW>())
+            .then(|| unsafe { (buffer.0 as *const WTSINFOW).read_unaligned().LogonTime });
+        unsafe { WTSFreeMemory(buffer.0 as _) };
 
-            let key_path = HSTRING::from(
-                "SYSTEM\\CurrentControlSet\\Control\\Class\\{4d36e968-e325-11ce-bfc1-08002be10318}",
+        // A FILETIME: 100ns ticks since 1601.
+        return (logon? as u64 / 10_000_000).checked_sub(11_644_473_600);
+    }
+
+    #[allow(unreachable_code)]
+    None
+}
+
+/// When `user` logged in on `tty` according to a utmp file, or else their
+/// earliest login in it.
+#[cfg(target_os = "linux")]
+fn utmp_login(utmp: &[u8], user: &str, tty: Option<&str>) -> Option<u64> {
+    // glibc's struct utmp: 384 bytes, with the fields used here at
+    // fixed offsets on every 64-bit and 32-bit target.
+    const RECORD: usize = 384;
+    const USER_PROCESS: i16 = 7;
+
+    let field = |record: &[u8], range: std::ops::Range<usize>| {
+        let bytes = &record[range];
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..end]).into_owned()
+    };
+
+    let logins: Vec<(String, u64)> = utmp
+        .chunks_exact(RECORD)
+        .filter(|record| i16::from_ne_bytes([record[0], record[1]]) == USER_PROCESS)
+        .filter(|record| field(record, 44..76) == user)
+        .map(|record| {
+            let sec = i32::from_ne_bytes(record[340..344].try_into().unwrap_or_default());
+            (field(record, 8..40), sec as u32 as u64)
+        })
+        .collect();
+    logins
+        .iter()
+        .find(|(line, _)| tty == Some(line.as_str()))
+        .or_else(|| logins.iter().min_by_key(|(_, sec)| *sec))
+        .map(|(_, sec)| *sec)
+}
+
+/// Our controlling terminal as utmp names it, e.g. `pts/3`.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn current_tty() -> Option<String> {
+    ["/dev/stdin", "/dev/stdout", "/dev/stderr"]
+        .iter()
+        .find_map(|fd| fs::canonicalize(fd).ok())
+        .and_then(|path| {
+            path.strip_prefix("/dev")
+                .ok()
+                .map(|tty| tty.to_string_lossy().into_owned())
+        })
+        .filter(|tty| tty.starts_with("pts/") || tty.starts_with("tty"))
+}
+
+/// Our user's name: $USER, $LOGNAME, or failing both (as in containers
+/// and initramfs, which often set neither) the user database.
+#[cfg(unix)]
+fn current_user() -> Option<String> {
+    env::var("USER")
+        .or_else(|_| env::var("LOGNAME"))
+        .ok()
+        .filter(|user| !user.is_empty())
+        .or_else(|| passwd().map(|entry| entry.name.clone()))
+}
+
+/// Our home directory: $HOME, or failing that (as under cron, systemd
+/// units or `env -i`) the one in the user database.
+pub fn home_dir() -> Option<String> {
+    let home = env::var("HOME").ok().filter(|home| !home.is_empty());
+    #[cfg(unix)]
+    let home = home.or_else(|| {
+        passwd()
+            .map(|entry| entry.home.clone())
+            .filter(|home| !home.is_empty())
+    });
+    home
+}
+
+/// Where Windows keeps a user's files, %USERPROFILE%, and `home_dir`
+/// everywhere else.
+fn user_home() -> Option<String> {
+    if cfg!(windows) {
+        env::var("USERPROFILE").ok()
+    } else {
+        home_dir()
+    }
+}
+
+/// The fields used from a user database entry.
+#[cfg(unix)]
+#[derive(Debug, PartialEq)]
+struct Passwd {
+    name: String,
+    home: String,
+    shell: String,
+}
+
+/// Our user's entry in the user database. With the `passwd` feature it's
+/// looked up with getpwuid_r, so users from NSS (LDAP, systemd-homed)
+/// are found; without it, or if that finds nothing, /etc/passwd is read
+/// directly, which is all a static musl build can do.
+#[cfg(unix)]
+fn passwd() -> Option<&'static Passwd> {
+    static PASSWD: OnceLock<Option<Passwd>> = OnceLock::new();
+    PASSWD
+        .get_or_init(|| {
+            let uid = current_uid()?;
+            #[cfg(any(target_os = "macos", all(target_os = "linux", feature = "passwd")))]
+            if let Some(entry) = getpwuid(uid) {
+                return Some(entry);
+            }
+            parse_passwd(&fs::read_to_string("/etc/passwd").ok()?, uid)
+        })
+        .as_ref()
+}
+
+/// The entry for `uid` in an /etc/passwd, whose lines are
+/// `name:password:uid:gid:gecos:home:shell`.
+#[cfg(unix)]
+fn parse_passwd(passwd: &str, uid: u32) -> Option<Passwd> {
+    passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.len() == 7 && fields[2].parse() == Ok(uid)).then(|| Passwd {
+            name: fields[0].to_string(),
+            home: fields[5].to_string(),
+            shell: fields[6].to_string(),
+        })
+    })
+}
+
+/// Our real uid, which Linux has in /proc without a libc call.
+#[cfg(unix)]
+fn current_uid() -> Option<u32> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = fs::read_to_string("/proc/self/status").ok()?;
+        let uids = status.lines().find_map(|line| line.strip_prefix("Uid:"))?;
+        return uids.split_whitespace().next()?.parse().ok();
+    }
+
+    #[cfg(target_os = "macos")]
+    return Some(unsafe { libc::getuid() });
+
+    #[allow(unreachable_code)]
+    None
+}
+
+#[cfg(any(target_os = "macos", all(target_os = "linux", feature = "passwd")))]
+fn getpwuid(uid: u32) -> Option<Passwd> {
+    let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buf = vec![0 as libc::c_char; 16 * 1024];
+    let mut result = std::ptr::null_mut();
+    loop {
+        let status =
+            unsafe { libc::getpwuid_r(uid, &mut entry, buf.as_mut_ptr(), buf.len(), &mut result) };
+        // ERANGE: the entry doesn't fit, as an LDAP one with a long gecos may
+        // not. Anything past a megabyte isn't an entry worth reading.
+        if status == libc::ERANGE && buf.len() < 1024 * 1024 {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if status != 0 || result.is_null() {
+            return None;
+        }
+        break;
+    }
+    let text = |ptr: *const libc::c_char| {
+        if ptr.is_null() {
+            String::new()
+        } else {
+            unsafe { std::ffi::CStr::from_ptr(ptr) }
+                .to_string_lossy()
+                .into_owned()
+        }
+    };
+    Some(Passwd {
+        name: text(entry.pw_name),
+        home: text(entry.pw_dir),
+        shell: text(entry.pw_shell),
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn parse_meminfo(content: &str, mode: MemoryMode) -> (u64, u64) {
+    let mut total = 0u64;
+    let mut available = 0u64;
+    let mut free = 0u64;
+    let mut buffers = 0u64;
+    let mut cached = 0u64;
+
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(val)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let val = val.parse().unwrap_or(0);
+        match key {
+            "MemTotal:" => total = val,
+            "MemAvailable:" => available = val,
+            "MemFree:" => free = val,
+            "Buffers:" => buffers = val,
+            "Cached:" => cached = val,
+            _ => {}
+        }
+    }
+
+    let unused = match mode {
+        MemoryMode::Available => available,
+        MemoryMode::Free => free,
+        MemoryMode::Classic => free + buffers + cached,
+    };
+
+    (total / 1024, total.saturating_sub(unused) / 1024)
+}
+
+const SHELLS: &[&str] = &[
+    "ash",
+    "bash",
+    "cmd",
+    "csh",
+    "dash",
+    "elvish",
+    "fish",
+    "ion",
+    "ksh",
+    "mksh",
+    "murex",
+    "nu",
+    "oksh",
+    "osh",
+    "powershell",
+    "pwsh",
+    "sh",
+    "tcsh",
+    "xonsh",
+    "yash",
+    "zsh",
+];
+
+/// Strips the login-shell dash and `.exe` from a process name.
+fn process_base_name(name: &str) -> String {
+    let name = name.trim_start_matches('-');
+    name.strip_suffix(".exe")
+        .or_else(|| name.strip_suffix(".EXE"))
+        .unwrap_or(name)
+        .to_string()
+}
+
+static ANCESTORS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Executable names of the processes above this one, nearest parent first.
+pub fn process_ancestors() -> Vec<String> {
+    ANCESTORS
+        .get_or_init(|| {
+            #[cfg(unix)]
+            return process_chain(std::os::unix::process::parent_id());
+            #[cfg(windows)]
+            return process_chain(std::process::id())
+                .into_iter()
+                .skip(1)
+                .collect();
+        })
+        .clone()
+}
+
+/// Executable names of `pid` and each of its ancestors, in that order.
+fn process_chain(pid: u32) -> Vec<String> {
+    const MAX_DEPTH: usize = 32;
+    let mut names = Vec::new();
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut pid = pid;
+        while pid > 1 && names.len() < MAX_DEPTH {
+            let Ok(stat) = fs::read_to_string(format!("/proc/{}/stat", pid)) else {
+                break;
+            };
+            // comm is parenthesised and may itself contain spaces or parens.
+            let Some((head, tail)) = stat.rsplit_once(')') else {
+                break;
+            };
+            // comm is cut at 15 bytes, so prefer the executable's real name.
+            let name = fs::read_link(format!("/proc/{}/exe", pid))
+                .ok()
+                .and_then(|exe| exe.file_name().map(|n| n.to_string_lossy().into_owned()))
+                .unwrap_or_else(|| {
+                    head.split_once('(')
+                        .map(|(_, comm)| comm.to_string())
+                        .unwrap_or_default()
+                });
+            names.push(name);
+            pid = tail
+                .split_whitespace()
+                .nth(1)
+                .and_then(|ppid| ppid.parse().ok())
+                .unwrap_or(0);
+        }
+        return names;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut pid = pid;
+        while pid > 1 && names.len() < MAX_DEPTH {
+            let mut info: libc::proc_bsdinfo = unsafe { std::mem::zeroed() };
+            let size = std::mem::size_of::<libc::proc_bsdinfo>() as libc::c_int;
+            let read = unsafe {
+                libc::proc_pidinfo(
+                    pid as libc::c_int,
+                    libc::PROC_PIDTBSDINFO,
+                    0,
+                    &mut info as *mut _ as *mut libc::c_void,
+                    size,
+                )
+            };
+            if read != size {
+                break;
+            }
+            let name = if info.pbi_name[0] != 0 {
+                &info.pbi_name[..]
+            } else {
+                &info.pbi_comm[..]
+            };
+            let name: Vec<u8> = name
+                .iter()
+                .take_while(|&&c| c != 0)
+                .map(|&c| c as u8)
+                .collect();
+            names.push(String::from_utf8_lossy(&name).into_owned());
+            pid = info.pbi_ppid;
+        }
+        return names;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::collections::HashMap;
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Diagnostics::ToolHelp::{
+            CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+            TH32CS_SNAPPROCESS,
+        };
+
+        let mut processes: HashMap<u32, (u32, String)> = HashMap::new();
+        unsafe {
+            if let Ok(snapshot) = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) {
+                let mut entry = PROCESSENTRY32W {
+                    dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+                    ..Default::default()
+                };
+                let mut ok = Process32FirstW(snapshot, &mut entry).is_ok();
+                while ok {
+                    let len = entry
+                        .szExeFile
+                        .iter()
+                        .position(|&c| c == 0)
+                        .unwrap_or(entry.szExeFile.len());
+                    processes.insert(
+                        entry.th32ProcessID,
+                        (
+                            entry.th32ParentProcessID,
+                            String::from_utf16_lossy(&entry.szExeFile[..len]),
+                        ),
+                    );
+                    ok = Process32NextW(snapshot, &mut entry).is_ok();
+                }
+                let _ = CloseHandle(snapshot);
+            }
+        }
+
+        let mut pid = pid;
+        while pid != 0 && names.len() < MAX_DEPTH {
+            let Some((ppid, name)) = processes.get(&pid) else {
+                break;
+            };
+            names.push(name.clone());
+            pid = *ppid;
+        }
+        return names;
+    }
+
+    #[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+    {
+        let mut pid = pid;
+        while pid > 1 && names.len() < MAX_DEPTH {
+            let Ok(output) = Command::new("ps")
+                .args(["-o", "ppid=", "-o", "comm=", "-p", &pid.to_string()])
+                .output()
+            else {
+                break;
+            };
+            let output = String::from_utf8_lossy(&output.stdout);
+            let mut fields = output.split_whitespace();
+            let Some(ppid) = fields.next().and_then(|p| p.parse().ok()) else {
+                break;
+            };
+            let name = fields.collect::<Vec<_>>().join(" ");
+            names.push(name.rsplit('/').next().unwrap_or(&name).to_string());
+            pid = ppid;
+        }
+    }
+
+    names
+}
+
+const MULTIPLEXERS: &[&str] = &["screen", "tmux", "zellij"];
+
+/// Processes that sit between a terminal and a shell without being either.
+const WRAPPERS: &[&str] = &[
+    "cargo", "doas", "env", "login", "nohup", "script", "su", "sudo", "time",
+];
+
+/// Where the walk has gone past the terminal without finding one.
+const SESSION_ROOTS: &[&str] = &[
+    "explorer",
+    "init",
+    "kernel_task",
+    "launchd",
+    "sshd",
+    "systemd",
+];
+
+/// The terminal emulator and any multiplexer in between, as
+/// `(terminal, multiplexer)`, either of which may be empty.
+pub fn detect_terminal() -> (String, String) {
+    let mut chain = process_ancestors();
+
+    let mut multiplexer = chain
+        .iter()
+        .map(|name| process_base_name(name))
+        .find(|name| MULTIPLEXERS.contains(&name.to_lowercase().as_str()))
+        .unwrap_or_default();
+    if multiplexer.is_empty() {
+        if env::var_os("TMUX").is_some() {
+            multiplexer = "tmux".to_string();
+        } else if env::var_os("STY").is_some() {
+            multiplexer = "screen".to_string();
+        } else if env::var_os("ZELLIJ").is_some() {
+            multiplexer = "zellij".to_string();
+        }
+    }
+
+    // The tmux server is daemonized, so our own ancestors never reach the
+    // terminal. The attached client's ancestors do.
+    if multiplexer == "tmux" {
+        if let Some(client) = Command::new("tmux")
+            .args(["display-message", "-p", "#{client_pid}"])
+            .output()
+            .ok()
+            .and_then(|o| String::from_utf8_lossy(&o.stdout).trim().parse().ok())
+        {
+            chain = process_chain(client);
+        }
+    }
+
+    let terminal = chain
+        .iter()
+        .map(|name| process_base_name(name))
+        .filter(|name| {
+            let lower = name.to_lowercase();
+            !SHELLS.contains(&lower.as_str())
+                && !MULTIPLEXERS.contains(&lower.as_str())
+                && !WRAPPERS.contains(&lower.as_str())
+        })
+        .take_while(|name| !SESSION_ROOTS.contains(&name.to_lowercase().as_str()))
+        .next()
+        .map(|name| terminal_display_name(&name))
+        .or_else(|| env::var("TERM_PROGRAM").ok())
+        .or_else(|| {
+            env::var("TERM")
+                .ok()
+                .map(|term| term.rsplit('-').next().unwrap_or(&term).to_string())
+        })
+        .unwrap_or_default();
+
+    (terminal, multiplexer)
+}
+
+/// The connected Wi-Fi network and its signal strength, from `iw` or
+/// `nmcli` on Linux, `airport` or `ipconfig` on macOS, and the WLAN API
+/// on Windows.
+pub fn detect_wifi() -> Option<Wifi> {
+    // The usual approximation: -100 dBm is no signal, -50 dBm is full.
+    #[cfg(not(target_os = "windows"))]
+    let percent = |dbm: i32| (2 * (dbm + 100)).clamp(0, 100) as u32;
+
+    #[cfg(target_os = "linux")]
+    {
+        let timeout = std::time::Duration::from_secs(2);
+
+        if !which("iw") && !which("nmcli") {
+            missing_tool("iw");
+        }
+        if which("iw") {
+            let dev = output_within("iw", &["dev"], timeout).unwrap_or_default();
+            let ifaces = dev
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("Interface "));
+            for iface in ifaces {
+                let Some(link) = output_within("iw", &["dev", iface, "link"], timeout) else {
+                    continue;
+                };
+                let field = |name: &str| {
+                    link.lines()
+                        .find_map(|line| line.trim().strip_prefix(name))
+                        .map(str::trim)
+                };
+                if let Some(ssid) = field("SSID:") {
+                    backend("wifi", "iw");
+                    let signal = field("signal:")
+                        .and_then(|s| s.split_whitespace().next()?.parse().ok())
+                        .map(percent);
+                    return Some(Wifi {
+                        ssid: ssid.to_string(),
+                        signal,
+                    });
+                }
+            }
+        }
+
+        if which("nmcli") {
+            let args = ["-t", "-f", "ACTIVE,SIGNAL,SSID", "dev", "wifi"];
+            let list = output_within("nmcli", &args, timeout).unwrap_or_default();
+            // SSID goes last since it may contain (escaped) colons.
+            let active = list.lines().find_map(|line| line.strip_prefix("yes:"));
+            if let Some((signal, ssid)) = active.and_then(|line| line.split_once(':')) {
+                backend("wifi", "nmcli");
+                return Some(Wifi {
+                    ssid: ssid.replace("\\:", ":"),
+                    signal: signal.parse().ok(),
+                });
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        const AIRPORT: &str = "/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport";
+
+        if let Ok(output) = Command::new(AIRPORT).arg("-I").output() {
+            let info = String::from_utf8_lossy(&output.stdout);
+            let field = |name: &str| {
+                info.lines()
+                    .find_map(|line| line.trim().strip_prefix(name))
+                    .map(str::trim)
+            };
+            if let Some(ssid) = field("SSID:") {
+                backend("wifi", "airport");
+                return Some(Wifi {
+                    ssid: ssid.to_string(),
+                    signal: field("agrCtlRSSI:")
+                        .and_then(|rssi| rssi.parse().ok())
+                        .map(percent),
+                });
+            }
+        }
+
+        // airport is gone since macOS 14.4; ipconfig still knows the SSID.
+        if let Ok(output) = Command::new("ipconfig")
+            .args(["getsummary", "en0"])
+            .output()
+        {
+            let summary = String::from_utf8_lossy(&output.stdout);
+            let ssid = summary
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("SSID : "))
+                .filter(|ssid| *ssid != "<redacted>");
+            if let Some(ssid) = ssid {
+                backend("wifi", "ipconfig");
+                return Some(Wifi {
+                    ssid: ssid.to_string(),
+                    signal: None,
+                });
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    unsafe {
+        use windows::Win32::Foundation::HANDLE;
+        use windows::Win32::NetworkManagement::WiFi::{
+            wlan_interface_state_connected, wlan_intf_opcode_current_connection, WlanCloseHandle,
+            WlanEnumInterfaces, WlanFreeMemory, WlanOpenHandle, WlanQueryInterface,
+            WLAN_CONNECTION_ATTRIBUTES, WLAN_INTERFACE_INFO_LIST,
+        };
+
+        let mut version = 0u32;
+        let mut handle = HANDLE::default();
+        if WlanOpenHandle(2, None, &mut version, &mut handle) != 0 {
+            return None;
+        }
+
+        let mut wifi = None;
+        let mut list: *mut WLAN_INTERFACE_INFO_LIST = std::ptr::null_mut();
+        if WlanEnumInterfaces(handle, None, &mut list) == 0 && !list.is_null() {
+            let count = (*list).dwNumberOfItems as usize;
+            let interfaces = std::slice::from_raw_parts((*list).InterfaceInfo.as_ptr(), count);
+            for iface in interfaces {
+                if iface.isState != wlan_interface_state_connected {
+                    continue;
+                }
+                let mut size = 0u32;
+                let mut data: *mut std::ffi::c_void = std::ptr::null_mut();
+                if WlanQueryInterface(
+                    handle,
+                    &iface.InterfaceGuid,
+                    wlan_intf_opcode_current_connection,
+                    None,
+                    &mut size,
+                    &mut data,
+                    None,
+                ) != 0
+                    || data.is_null()
+                {
+                    continue;
+                }
+                let attributes = &*(data as *const WLAN_CONNECTION_ATTRIBUTES);
+                let association = &attributes.wlanAssociationAttributes;
+                let ssid = &association.dot11Ssid;
+                let len = (ssid.uSSIDLength as usize).min(ssid.ucSSID.len());
+                backend("wifi", "WLAN API");
+                wifi = Some(Wifi {
+                    ssid: String::from_utf8_lossy(&ssid.ucSSID[..len]).into_owned(),
+                    signal: Some(association.wlanSignalQuality),
+                });
+                WlanFreeMemory(data);
+                break;
+            }
+            WlanFreeMemory(list as *const std::ffi::c_void);
+        }
+        WlanCloseHandle(handle, None);
+        return wifi;
+    }
+
+    #[allow(unreachable_code)]
+    None
+}
+
+/// The primary interface's IPv4 and global IPv6 addresses with its name,
+/// e.g. `192.168.1.23, 2001:db8::23 (wlan0)`. The primary interface is
+/// the one the default route goes through, or failing that the first one
+/// with an address. Loopback is always skipped, and virtual interfaces
+/// (bridges, VPN tunnels, container veths) unless `include_virtual`.
+pub fn detect_local_ip(include_virtual: bool) -> String {
+    use std::net::IpAddr;
+
+    #[allow(unused_mut)]
+    let mut addrs: Vec<(String, IpAddr)> = Vec::new();
+    #[allow(unused_mut, unused_assignments)]
+    let mut default_iface: Option<String> = None;
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::net::{Ipv4Addr, Ipv6Addr};
+
+        let allowed = |name: &str| {
+            name != "lo"
+                && (include_virtual || !Path::new("/sys/devices/virtual/net").join(name).exists())
+                && fs::read_to_string(format!("/sys/class/net/{}/operstate", name))
+                    .is_ok_and(|state| state.trim() != "down")
+        };
+
+        // /proc/net/route prints addresses as the native-endian value of
+        // their network-order bytes.
+        let hex_addr = |hex: &str| {
+            u32::from_str_radix(hex, 16)
+                .ok()
+                .map(|v| u32::from(Ipv4Addr::from(v.to_ne_bytes())))
+        };
+        // (interface, destination, mask, metric)
+        let routes: Vec<(String, u32, u32, u32)> = fs::read_to_string("/proc/net/route")
+            .unwrap_or_default()
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                Some((
+                    fields.first()?.to_string(),
+                    hex_addr(fields.get(1)?)?,
+                    hex_addr(fields.get(7)?)?,
+                    fields.get(6)?.parse().ok()?,
+                ))
+            })
+            .collect();
+        default_iface = routes
+            .iter()
+            .filter(|(iface, dest, mask, _)| *dest == 0 && *mask == 0 && allowed(iface))
+            .min_by_key(|(_, _, _, metric)| *metric)
+            .map(|(iface, ..)| iface.clone());
+
+        // The kernel's own addresses are the "/32 host LOCAL" leaves of the
+        // FIB trie; which interface each is on comes from the subnet route
+        // that covers it.
+        let trie = fs::read_to_string("/proc/net/fib_trie").unwrap_or_default();
+        let mut last = None;
+        for line in trie.lines() {
+            let line = line.trim_start_matches([' ', '|', '+', '-']);
+            if line == "/32 host LOCAL" {
+                let Some(addr) = last.filter(|addr: &Ipv4Addr| !addr.is_loopback()) else {
+                    continue;
+                };
+                let iface = routes
+                    .iter()
+                    .filter(|(_, dest, mask, _)| *mask != 0 && u32::from(addr) & mask == *dest)
+                    .max_by_key(|(_, _, mask, _)| *mask)
+                    .map(|(iface, ..)| iface.clone());
+                if let Some(iface) = iface.filter(|iface| allowed(iface)) {
+                    let entry = (iface, IpAddr::V4(addr));
+                    if !addrs.contains(&entry) {
+                        addrs.push(entry);
+                    }
+                }
+            } else {
+                last = line.parse().ok();
+            }
+        }
+
+        // address, index, prefix length, scope, flags, interface
+        if let Ok(inet6) = fs::read_to_string("/proc/net/if_inet6") {
+            for line in inet6.lines() {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let (Some(addr), Some(&"00"), Some(iface)) =
+                    (fields.first(), fields.get(3), fields.get(5))
+                else {
+                    continue;
+                };
+                if let Ok(addr) = u128::from_str_radix(addr, 16) {
+                    if allowed(iface) {
+                        addrs.push((iface.to_string(), IpAddr::V6(Ipv6Addr::from(addr))));
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use std::ffi::CStr;
+        use std::net::{Ipv4Addr, Ipv6Addr};
+
+        const VIRTUAL: [&str; 10] = [
+            "utun", "bridge", "awdl", "llw", "vmenet", "vmnet", "gif", "stf", "anpi", "ap",
+        ];
+        let allowed =
+            |name: &str| include_virtual || !VIRTUAL.iter().any(|prefix| name.starts_with(prefix));
+
+        unsafe {
+            let mut list: *mut libc::ifaddrs = std::ptr::null_mut();
+            if libc::getifaddrs(&mut list) == 0 {
+                let mut entry = list;
+                while let Some(ifa) = entry.as_ref() {
+                    entry = ifa.ifa_next;
+                    let flags = ifa.ifa_flags as libc::c_int;
+                    if ifa.ifa_addr.is_null()
+                        || flags & libc::IFF_UP == 0
+                        || flags & libc::IFF_LOOPBACK != 0
+                    {
+                        continue;
+                    }
+                    let name = CStr::from_ptr(ifa.ifa_name).to_string_lossy().into_owned();
+                    if !allowed(&name) {
+                        continue;
+                    }
+                    let addr = match (*ifa.ifa_addr).sa_family as libc::c_int {
+                        libc::AF_INET => {
+                            let sin = &*(ifa.ifa_addr as *const libc::sockaddr_in);
+                            IpAddr::V4(Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr)))
+                        }
+                        libc::AF_INET6 => {
+                            let sin6 = &*(ifa.ifa_addr as *const libc::sockaddr_in6);
+                            let addr = Ipv6Addr::from(sin6.sin6_addr.s6_addr);
+                            // Link-local addresses are the same on every network.
+                            if addr.segments()[0] & 0xffc0 == 0xfe80 {
+                                continue;
+                            }
+                            IpAddr::V6(addr)
+                        }
+                        _ => continue,
+                    };
+                    addrs.push((name, addr));
+                }
+                libc::freeifaddrs(list);
+            }
+        }
+
+        if let Ok(output) = Command::new("route")
+            .args(["-n", "get", "default"])
+            .output()
+        {
+            default_iface = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("interface:"))
+                .map(|iface| iface.trim().to_string());
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::net::{Ipv4Addr, Ipv6Addr};
+        use windows::Win32::NetworkManagement::IpHelper::{
+            GetAdaptersAddresses, GAA_FLAG_INCLUDE_GATEWAYS, GAA_FLAG_SKIP_ANYCAST,
+            GAA_FLAG_SKIP_MULTICAST, IP_ADAPTER_ADDRESSES_LH,
+        };
+        use windows::Win32::NetworkManagement::Ndis::IfOperStatusUp;
+        use windows::Win32::Networking::WinSock::{
+            AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR_IN, SOCKADDR_IN6,
+        };
+
+        // Loopback and tunnel interface types.
+        const IF_TYPE_SOFTWARE_LOOPBACK: u32 = 24;
+        const IF_TYPE_TUNNEL: u32 = 131;
+        const VIRTUAL: [&str; 5] = ["Virtual", "Hyper-V", "VMware", "VirtualBox", "TAP-"];
+
+        unsafe {
+            let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_INCLUDE_GATEWAYS;
+            let mut size = 16 * 1024u32;
+            let mut buffer: Vec<u64> = vec![0; size as usize / 8];
+            let mut result = GetAdaptersAddresses(
+                AF_UNSPEC.0 as u32,
+                flags,
+                None,
+                Some(buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH),
+                &mut size,
+            );
+            // ERROR_BUFFER_OVERFLOW: try once more at the size asked for.
+            if result == 111 {
+                buffer = vec![0; (size as usize).div_ceil(8)];
+                result = GetAdaptersAddresses(
+                    AF_UNSPEC.0 as u32,
+                    flags,
+                    None,
+                    Some(buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH),
+                    &mut size,
+                );
+            }
+
+            let mut best_metric = u32::MAX;
+            let mut adapter = if result == 0 {
+                buffer.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH
+            } else {
+                std::ptr::null()
+            };
+            while let Some(a) = adapter.as_ref() {
+                adapter = a.Next;
+                let description = a.Description.to_string().unwrap_or_default();
+                if a.OperStatus != IfOperStatusUp
+                    || a.IfType == IF_TYPE_SOFTWARE_LOOPBACK
+                    || (!include_virtual
+                        && (a.IfType == IF_TYPE_TUNNEL
+                            || VIRTUAL.iter().any(|v| description.contains(v))))
+                {
+                    continue;
+                }
+                let name = a.FriendlyName.to_string().unwrap_or_default();
+                if !a.FirstGatewayAddress.is_null() && a.Ipv4Metric < best_metric {
+                    best_metric = a.Ipv4Metric;
+                    default_iface = Some(name.clone());
+                }
+
+                let mut unicast = a.FirstUnicastAddress;
+                while let Some(u) = unicast.as_ref() {
+                    unicast = u.Next;
+                    let sockaddr = u.Address.lpSockaddr;
+                    if sockaddr.is_null() {
+                        continue;
+                    }
+                    let addr = if (*sockaddr).sa_family == AF_INET {
+                        let sin = &*(sockaddr as *const SOCKADDR_IN);
+                        IpAddr::V4(Ipv4Addr::from(u32::from_be(sin.sin_addr.S_un.S_addr)))
+                    } else if (*sockaddr).sa_family == AF_INET6 {
+                        let sin6 = &*(sockaddr as *const SOCKADDR_IN6);
+                        let addr = Ipv6Addr::from(sin6.sin6_addr.u.Byte);
+                        if addr.segments()[0] & 0xffc0 == 0xfe80 {
+                            continue;
+                        }
+                        IpAddr::V6(addr)
+                    } else {
+                        continue;
+                    };
+                    addrs.push((name.clone(), addr));
+                }
+            }
+        }
+    }
+
+    let iface = default_iface
+        .filter(|iface| addrs.iter().any(|(name, _)| name == iface))
+        .or_else(|| {
+            addrs
+                .iter()
+                .filter(|(_, addr)| addr.is_ipv4())
+                .chain(&addrs)
+                .map(|(name, _)| name.clone())
+                .next()
+        });
+    let Some(iface) = iface else {
+        return String::new();
+    };
+
+    let on_iface = |v4: bool| {
+        addrs
+            .iter()
+            .find(|(name, addr)| *name == iface && addr.is_ipv4() == v4)
+            .map(|(_, addr)| addr.to_string())
+    };
+    let shown: Vec<String> = [on_iface(true), on_iface(false)]
+        .into_iter()
+        .flatten()
+        .collect();
+    format!("{} ({})", shown.join(", "), iface)
+}
+
+/// The multiplexer session we're running in with its window count, e.g.
+/// `tmux: main (3 windows)`, or an empty string outside of one.
+pub fn detect_mux_session() -> String {
+    let output = |cmd: &str, args: &[&str]| {
+        Command::new(cmd)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    };
+    let describe = |mux: &str, name: &str, windows: Option<usize>| match windows {
+        Some(1) => format!("{}: {} (1 window)", mux, name),
+        Some(n) => format!("{}: {} ({} windows)", mux, name, n),
+        None => format!("{}: {}", mux, name),
+    };
+
+    if env::var_os("TMUX").is_some() {
+        if let Some(info) = output("tmux", &["display-message", "-p", "#S\t#{session_windows}"]) {
+            let (name, windows) = info.split_once('\t').unwrap_or((&info, ""));
+            return describe("tmux", name, windows.parse().ok());
+        }
+    }
+
+    // STY is <pid>.<name>
+    if let Ok(sty) = env::var("STY") {
+        let name = sty.split_once('.').map_or(sty.as_str(), |(_, name)| name);
+        // `-Q windows` lists them as "0$ bash  1-$ vim  2*$ top"
+        let windows = output("screen", &["-S", &sty, "-Q", "windows"]).map(|list| {
+            list.split("  ")
+                .filter(|w| w.trim_start().starts_with(|c: char| c.is_ascii_digit()))
+                .count()
+        });
+        return describe("screen", name, windows);
+    }
+
+    if let Ok(name) = env::var("ZELLIJ_SESSION_NAME") {
+        let tabs = output("zellij", &["action", "query-tab-names"]).map(|t| t.lines().count());
+        return describe("zellij", &name, tabs);
+    }
+
+    String::new()
+}
+
+/// Whether this session is Wayland, X11 or a bare TTY: XDG_SESSION_TYPE
+/// when logind set it, else whichever display variable is set. Empty on
+/// macOS and Windows, which only have the one.
+pub fn detect_display_server() -> String {
+    if cfg!(any(target_os = "macos", target_os = "windows")) {
+        return String::new();
+    }
+    let session = env::var("XDG_SESSION_TYPE").unwrap_or_default();
+    let server = match session.as_str() {
+        "wayland" => "Wayland",
+        "x11" => "X11",
+        "tty" => "TTY",
+        _ if env::var_os("WAYLAND_DISPLAY").is_some() => "Wayland",
+        _ if env::var_os("DISPLAY").is_some() => "X11",
+        _ => "TTY",
+    };
+    server.to_string()
+}
+
+/// The container or virtual machine we're running in, e.g. `Docker` or
+/// `KVM`, or empty on bare metal. Follows systemd-detect-virt: a container
+/// is looked for first, from what its manager leaves in pid 1's
+/// environment, /run and the cgroup path, then a hypervisor from CPUID and
+/// the DMI vendor strings.
+pub fn detect_virtualization() -> String {
+    #[cfg(target_os = "linux")]
+    if let Some(container) = linux_container() {
+        return container;
+    }
+
+    if let Some(vm) = cpuid_hypervisor() {
+        return vm.to_string();
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // Prefixes of sys_vendor, product_name, board_vendor and
+        // bios_vendor that only hypervisors use.
+        const VENDORS: &[(&str, &str)] = &[
+            ("KVM", "KVM"),
+            ("OpenStack", "KVM"),
+            ("QEMU", "QEMU"),
+            ("VMware", "VMware"),
+            ("VMW", "VMware"),
+            ("innotek GmbH", "VirtualBox"),
+            ("VirtualBox", "VirtualBox"),
+            ("Xen", "Xen"),
+            ("Bochs", "Bochs"),
+            ("Parallels", "Parallels"),
+            ("BHYVE", "bhyve"),
+            ("Hyper-V", "Hyper-V"),
+            ("Apple Virtualization", "Apple Virtualization"),
+            ("Google Compute Engine", "Google Compute Engine"),
+            ("Amazon EC2", "Amazon EC2"),
+        ];
+        let dmi = |name: &str| {
+            fs::read_to_string(format!("/sys/class/dmi/id/{}", name))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default()
+        };
+        for file in ["sys_vendor", "product_name", "board_vendor", "bios_vendor"] {
+            let value = dmi(file);
+            if let Some((_, name)) = VENDORS.iter().find(|(v, _)| value.starts_with(v)) {
+                return name.to_string();
+            }
+        }
+        // Surface laptops share the vendor; only the VMs have this name.
+        if dmi("sys_vendor") == "Microsoft Corporation" && dmi("product_name") == "Virtual Machine"
+        {
+            return "Hyper-V".to_string();
+        }
+        // Xen PV guests and ARM VMs have no DMI.
+        if fs::read_to_string("/sys/hypervisor/type").is_ok_and(|t| t.trim() == "xen") {
+            return "Xen".to_string();
+        }
+        if let Ok(compatible) = fs::read_to_string("/proc/device-tree/hypervisor/compatible") {
+            let compatible = compatible.trim_end_matches('\0');
+            if compatible.starts_with("linux,kvm") {
+                return "KVM".to_string();
+            }
+            if compatible.starts_with("xen") {
+                return "Xen".to_string();
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    if let Ok(output) = Command::new("sysctl")
+        .args(["-n", "kern.hv_vmm_present"])
+        .output()
+    {
+        if String::from_utf8_lossy(&output.stdout).trim() == "1" {
+            return "Apple Virtualization".to_string();
+        }
+    }
+
+    String::new()
+}
+
+/// `WSL1` or `WSL2` under the Windows Subsystem for Linux, from the kernel
+/// Microsoft builds for it: `4.4.0-19041-Microsoft` on WSL1,
+/// `5.15.153.1-microsoft-standard-WSL2` on WSL2. WSL_DISTRO_NAME, which
+/// WSL sets in every session, catches WSL2 with a custom kernel.
+pub fn detect_wsl() -> String {
+    #[cfg(target_os = "linux")]
+    {
+        let version = fs::read_to_string("/proc/version").unwrap_or_default();
+        if version.contains("-Microsoft") {
+            return "WSL1".to_string();
+        }
+        if version.to_lowercase().contains("microsoft") || env::var_os("WSL_DISTRO_NAME").is_some()
+        {
+            return "WSL2".to_string();
+        }
+    }
+    String::new()
+}
+
+/// Whether we're under WSL, where the PCI bus and X server (WSLg's, if
+/// any) are virtual and asking them hangs or describes the VM.
+#[cfg(target_os = "linux")]
+fn under_wsl() -> bool {
+    static WSL: OnceLock<bool> = OnceLock::new();
+    *WSL.get_or_init(|| !detect_wsl().is_empty())
+}
+
+/// Whether there's an X server worth asking about displays.
+#[cfg(target_os = "linux")]
+fn x_display() -> bool {
+    env::var_os("DISPLAY").is_some() && !under_wsl()
+}
+
+/// The container manager, from the `container=` variable systemd and most
+/// managers hand pid 1 (readable in /run/systemd/container when
+/// /proc/1/environ isn't), the files Podman and Docker drop, or the
+/// cgroup path.
+#[cfg(target_os = "linux")]
+fn linux_container() -> Option<String> {
+    let name = |id: &str| match id {
+        "docker" => "Docker".to_string(),
+        "podman" => "Podman".to_string(),
+        "lxc" | "lxc-libvirt" => "LXC".to_string(),
+        "systemd-nspawn" => "systemd-nspawn".to_string(),
+        other => other.to_string(),
+    };
+
+    let environ = fs::read("/proc/1/environ").unwrap_or_default();
+    let from_environ = environ
+        .split(|&b| b == 0)
+        .find_map(|var| var.strip_prefix(b"container="))
+        .map(|id| String::from_utf8_lossy(id).into_owned());
+    let from_run = fs::read_to_string("/run/systemd/container")
+        .ok()
+        .map(|id| id.trim().to_string());
+    if let Some(id) = from_environ.or(from_run).filter(|id| !id.is_empty()) {
+        return Some(name(&id));
+    }
+
+    if Path::new("/run/.containerenv").exists() {
+        return Some(name("podman"));
+    }
+    if Path::new("/.dockerenv").exists() {
+        return Some(name("docker"));
+    }
+    let wsl = detect_wsl();
+    if !wsl.is_empty() {
+        return Some(wsl);
+    }
+    // OpenVZ has /proc/vz in containers and /proc/bc on the host too.
+    if Path::new("/proc/vz").exists() && !Path::new("/proc/bc").exists() {
+        return Some("OpenVZ".to_string());
+    }
+
+    let cgroup = fs::read_to_string("/proc/1/cgroup").unwrap_or_default();
+    if cgroup.contains("kubepods") {
+        Some("Kubernetes".to_string())
+    } else if cgroup.contains("/docker") {
+        Some(name("docker"))
+    } else if cgroup.contains("/lxc") {
+        Some(name("lxc"))
+    } else {
+        None
+    }
+}
+
+/// The hypervisor x86 CPUs name in leaf 0x40000000, once leaf 1 says
+/// there is one.
+fn cpuid_hypervisor() -> Option<&'static str> {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        #[cfg(target_arch = "x86")]
+        use std::arch::x86::__cpuid;
+        #[cfg(target_arch = "x86_64")]
+        use std::arch::x86_64::__cpuid;
+
+        // __cpuid is only unsafe on older compilers.
+        #[allow(unused_unsafe)]
+        let cpuid = |leaf| unsafe { __cpuid(leaf) };
+        if cpuid(1).ecx & (1 << 31) == 0 {
+            return None;
+        }
+        let leaf = cpuid(0x4000_0000);
+        let mut vendor = [0u8; 12];
+        vendor[..4].copy_from_slice(&leaf.ebx.to_le_bytes());
+        vendor[4..8].copy_from_slice(&leaf.ecx.to_le_bytes());
+        vendor[8..].copy_from_slice(&leaf.edx.to_le_bytes());
+        return match &vendor {
+            b"KVMKVMKVM\0\0\0" | b"Linux KVM Hv" => Some("KVM"),
+            b"TCGTCGTCGTCG" => Some("QEMU"),
+            b"XenVMMXenVMM" => Some("Xen"),
+            b"VMwareVMware" => Some("VMware"),
+            b"VBoxVBoxVBox" => Some("VirtualBox"),
+            b"bhyve bhyve " => Some("bhyve"),
+            b" lrpepyh  vr" => Some("Parallels"),
+            b"ACRNACRNACRN" => Some("ACRN"),
+            b"QNXQVMBSQG\0\0" => Some("QNX"),
+            // With virtualization-based security Windows itself runs under
+            // Hyper-V, as the root partition, which may create partitions.
+            b"Microsoft Hv" if cpuid(0x4000_0003).ebx & 1 == 0 => Some("Hyper-V"),
+            _ => None,
+        };
+    }
+
+    #[allow(unreachable_code)]
+    None
+}
+
+/// Desktop environments as (name, the prefixes XDG_CURRENT_DESKTOP and
+/// DESKTOP_SESSION use for them, a process only they run).
+const DESKTOPS: &[(&str, &[&str], &str)] = &[
+    ("GNOME", &["gnome"], "gnome-shell"),
+    ("KDE Plasma", &["kde", "plasma"], "plasmashell"),
+    ("XFCE", &["xfce"], "xfce4-session"),
+    ("Cinnamon", &["cinnamon"], "cinnamon-session"),
+    ("MATE", &["mate"], "mate-session"),
+    ("LXQt", &["lxqt"], "lxqt-session"),
+    ("LXDE", &["lxde"], "lxsession"),
+    ("Budgie", &["budgie"], "budgie-panel"),
+    ("Deepin", &["deepin", "dde"], "dde-desktop"),
+    ("Pantheon", &["pantheon"], "gala"),
+    ("Unity", &["unity"], "unity-panel-service"),
+    ("Enlightenment", &["enlightenment"], "enlightenment"),
+    ("COSMIC", &["cosmic"], "cosmic-session"),
+];
+
+/// The desktop environment, from XDG_CURRENT_DESKTOP, then DESKTOP_SESSION,
+/// then the running processes. An XDG_CURRENT_DESKTOP that names no known
+/// desktop (a bare compositor like Hyprland) is shown as is.
+pub fn detect_de() -> String {
+    #[cfg(target_os = "macos")]
+    return "Aqua".to_string();
+
+    let known = |value: &str| {
+        // X-Cinnamon, /usr/share/xsessions/plasma, gnome-xorg
+        let value = value.rsplit('/').next().unwrap_or(value).to_lowercase();
+        let value = value.strip_prefix("x-").unwrap_or(&value);
+        DESKTOPS
+            .iter()
+            .find(|(_, ids, _)| ids.iter().any(|id| value.starts_with(id)))
+            .map(|(name, _, _)| name.to_string())
+    };
+
+    // Colon separated, most specific first: "Budgie:GNOME", "ubuntu:GNOME".
+    if let Ok(current) = env::var("XDG_CURRENT_DESKTOP") {
+        if let Some(name) = current.split(':').find_map(known) {
+            backend("de", "XDG_CURRENT_DESKTOP");
+            return name;
+        }
+        if let Some(first) = current.split(':').find(|s| !s.is_empty()) {
+            backend("de", "XDG_CURRENT_DESKTOP");
+            return first.to_string();
+        }
+    }
+    if let Some(name) = env::var("DESKTOP_SESSION").ok().and_then(|s| known(&s)) {
+        backend("de", "DESKTOP_SESSION");
+        return name;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // comm is cut at 15 bytes.
+        let running = running_processes();
+        if let Some((name, _, _)) = DESKTOPS.iter().find(|(_, _, process)| {
+            let process = &process[..process.len().min(15)];
+            running.iter().any(|comm| comm == process)
+        }) {
+            backend("de", "process list");
+            return name.to_string();
+        }
+    }
+
+    String::new()
+}
+
+/// The names of every running process, as /proc/<pid>/comm has them.
+#[cfg(target_os = "linux")]
+fn running_processes() -> Vec<String> {
+    read_dir("/proc")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+        })
+        .filter_map(|e| fs::read_to_string(e.path().join("comm")).ok())
+        .map(|comm| comm.trim_end().to_string())
+        .collect()
+}
+
+/// The sound server and the default output device, e.g. `PipeWire
+/// (Built-in Audio Analog Stereo)`. Linux tells PipeWire, PulseAudio and
+/// bare ALSA apart by what's running and asks pactl for the device; macOS
+/// always has CoreAudio and Windows WASAPI.
+pub fn detect_audio() -> String {
+    #[allow(unused_mut, unused_assignments)]
+    let mut server = "";
+    #[allow(unused_mut, unused_assignments)]
+    let mut device: Option<String> = None;
+
+    #[cfg(target_os = "linux")]
+    {
+        let running = running_processes();
+        let cards = fs::read_to_string("/proc/asound/cards").unwrap_or_default();
+        server = if running.iter().any(|p| p == "pipewire") {
+            "PipeWire"
+        } else if running.iter().any(|p| p == "pulseaudio") {
+            "PulseAudio"
+        } else if !cards.trim().is_empty() && !cards.contains("no soundcards") {
+            "ALSA"
+        } else {
+            return String::new();
+        };
+
+        let timeout = std::time::Duration::from_secs(2);
+        if server != "ALSA" && !which("pactl") {
+            missing_tool("pactl");
+        }
+        if server != "ALSA" && which("pactl") {
+            // The Name: and Description: labels are translated otherwise.
+            let pactl = |args: &[&str]| {
+                let mut command = Command::new("pactl");
+                command.args(args).env("LC_ALL", "C");
+                command_within(command, timeout).map(|(_, output)| output)
+            };
+            let sink = pactl(&["get-default-sink"]);
+            let sinks = pactl(&["list", "sinks"]).unwrap_or_default();
+            device = sink.and_then(|sink| {
+                backend("audio", "pactl");
+                let sink = sink.trim();
+                // Each sink's Name: line comes a little before its Description:.
+                sinks
+                    .lines()
+                    .map(str::trim)
+                    .skip_while(|line| line.strip_prefix("Name: ") != Some(sink))
+                    .find_map(|line| line.strip_prefix("Description: "))
+                    .map(str::to_string)
+            });
+        }
+        // " 0 [PCH            ]: HDA-Intel - HDA Intel PCH"
+        device = device.or_else(|| {
+            backend("audio", "/proc/asound");
+            cards
+                .lines()
+                .find_map(|line| line.split_once("]: ")?.1.split_once(" - "))
+                .map(|(_, name)| name.trim().to_string())
+        });
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        server = "CoreAudio";
+        backend("audio", "system_profiler");
+        if let Ok(output) = Command::new("system_profiler")
+            .arg("SPAudioDataType")
+            .output()
+        {
+            // Each device is a "Name:" heading over its properties.
+            let mut heading = None;
+            for line in String::from_utf8_lossy(&output.stdout).lines() {
+                let line = line.trim();
+                if line == "Default Output Device: Yes" {
+                    device = heading.map(str::to_string);
+                    break;
+                }
+                if let Some(name) = line.strip_suffix(':') {
+                    heading = Some(name);
+                }
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        server = "WASAPI";
+        backend("audio", "MMDevice API");
+        device = windows_audio_device();
+    }
+
+    match device {
+        Some(device) => format!("{} ({})", server, device),
+        None => server.to_string(),
+    }
+}
+
+/// The friendly name of the default playback device.
+#[cfg(target_os = "windows")]
+fn windows_audio_device() -> Option<String> {
+    use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName;
+    use windows::Win32::Media::Audio::{
+        eConsole, eRender, IMMDeviceEnumerator, MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED, STGM_READ,
+    };
+
+    unsafe {
+        // Fails harmlessly when COM is already set up on this thread.
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).ok()?;
+        let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole).ok()?;
+        let store = device.OpenPropertyStore(STGM_READ).ok()?;
+        let name = store.GetValue(&PKEY_Device_FriendlyName).ok()?.to_string();
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+/// Whatever a media player is playing right now. Linux asks MPRIS
+/// players over D-Bus, through playerctl or else, with the `dbus`
+/// feature, dbus-send; macOS asks Music and Spotify over AppleScript;
+/// Windows asks the system media transport controls. Paused and stopped players don't count.
+pub fn detect_now_playing() -> Option<NowPlaying> {
+    #[cfg(target_os = "linux")]
+    {
+        let timeout = std::time::Duration::from_secs(2);
+
+        let dbus_send = cfg!(feature = "dbus") && which("dbus-send");
+        if !which("playerctl") && !dbus_send {
+            missing_tool("playerctl");
+        }
+        if which("playerctl") {
+            backend("now_playing", "playerctl");
+            let players = output_within(
+                "playerctl",
+                &[
+                    "--all-players",
+                    "metadata",
+                    "--format",
+                    "{{status}}\t{{artist}}\t{{title}}",
+                ],
+                timeout,
+            )
+            .unwrap_or_default();
+            return players.lines().find_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                if fields.next()? != "Playing" {
+                    return None;
+                }
+                let artist = fields.next()?.trim().to_string();
+                let title = fields.next()?.trim().to_string();
+                (!title.is_empty()).then_some(NowPlaying { artist, title })
+            });
+        }
+
+        #[cfg(feature = "dbus")]
+        if dbus_send {
+            backend("now_playing", "dbus-send");
+            let get = |player: &str, property: &str| {
+                output_within(
+                    "dbus-send",
+                    &[
+                        "--session",
+                        "--print-reply",
+                        &format!("--dest={}", player),
+                        "/org/mpris/MediaPlayer2",
+                        "org.freedesktop.DBus.Properties.Get",
+                        "string:org.mpris.MediaPlayer2.Player",
+                        &format!("string:{}", property),
+                    ],
+                    timeout,
+                )
+                .unwrap_or_default()
+            };
+            let names = output_within(
+                "dbus-send",
+                &[
+                    "--session",
+                    "--print-reply",
+                    "--dest=org.freedesktop.DBus",
+                    "/org/freedesktop/DBus",
+                    "org.freedesktop.DBus.ListNames",
+                ],
+                timeout,
+            )
+            .unwrap_or_default();
+            let players = names
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("string \""))
+                .filter_map(|name| name.strip_suffix('"'))
+                .filter(|name| name.starts_with("org.mpris.MediaPlayer2."));
+            for player in players {
+                if !get(player, "PlaybackStatus").contains("\"Playing\"") {
+                    continue;
+                }
+                let metadata = get(player, "Metadata");
+                // Each key is a `string "xesam:title"` line, and its value
+                // the next string line: `variant string "..."`, or the
+                // first entry of `variant array [` for xesam:artist.
+                let value = |key: &str| {
+                    let key = format!("string \"{}\"", key);
+                    metadata
+                        .lines()
+                        .map(str::trim)
+                        .skip_while(|line| *line != key)
+                        .skip(1)
+                        .find_map(|line| {
+                            let (_, value) = line.split_once("string \"")?;
+                            value.strip_suffix('"').map(str::to_string)
+                        })
+                        .unwrap_or_default()
+                };
+                let title = value("xesam:title");
+                if !title.is_empty() {
+                    return Some(NowPlaying {
+                        artist: value("xesam:artist"),
+                        title,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // MediaRemote would see every player, but it's a private framework;
+        // these two cover most people.
+        backend("now_playing", "osascript");
+        ["Music", "Spotify"].iter().find_map(|app| {
+            let script = format!(
+                "if application \"{app}\" is running then tell application \"{app}\" \
+                 to if player state is playing then \
+                 return (artist of current track) & tab & (name of current track)"
             );
-            let mut hkey = HKEY::default();
-
-            if RegOpenKeyExW(HKEY_LOCAL_MACHINE, &key_path, Some(0), KEY_READ, &mut hkey).is_ok() {
-                for i in 0..10 {
-                    let subkey_name = HSTRING::from(format!("{:04}", i));
-                    let mut subkey = HKEY::default();
-
-                    if RegOpenKeyExW(hkey, &subkey_name, Some(0), KEY_READ, &mut subkey).is_ok() {
-                        let desc_name = HSTRING::from("DriverDesc");
-                        let mut desc_buffer = [0u8; 512];
-                        let mut desc_size = desc_buffer.len() as u32;
-
-                        if RegQueryValueExW(
-                            subkey,
-                            &desc_name,
-                            None,
-                            None,
-                            Some(desc_buffer.as_mut_ptr()),
-                            Some(&mut desc_size),
-                        )
-                        .is_ok()
-                        {
-                            if let Ok(desc_str) =
-                                std::str::from_utf8(&desc_buffer[..desc_size as usize - 1])
-                            {
-                                let desc = desc_str.trim_end_matches('\0').trim();
-                                if !desc.is_empty() && !gpus.contains(&desc.to_string()) {
-                                    gpus.push(desc.to_string());
-                                }
+            let output = Command::new("osascript")
+                .args(["-e", &script])
+                .output()
+                .ok()?;
+            let output = String::from_utf8_lossy(&output.stdout);
+            let (artist, title) = output.trim_end().split_once('\t')?;
+            (!title.is_empty()).then(|| NowPlaying {
+                artist: artist.to_string(),
+                title: title.to_string(),
+            })
+        })
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Media::Control::{
+            GlobalSystemMediaTransportControlsSessionManager as SessionManager,
+            GlobalSystemMediaTransportControlsSessionPlaybackStatus as PlaybackStatus,
+        };
+
+        backend("now_playing", "GSMTC");
+        let manager = SessionManager::RequestAsync().ok()?.join().ok()?;
+        let session = manager.GetCurrentSession().ok()?;
+        let status = session.GetPlaybackInfo().ok()?.PlaybackStatus().ok()?;
+        if status != PlaybackStatus::Playing {
+            return None;
+        }
+        let properties = session.TryGetMediaPropertiesAsync().ok()?.join().ok()?;
+        let title = properties.Title().ok()?.to_string();
+        let artist = properties
+            .Artist()
+            .map(|a| a.to_string())
+            .unwrap_or_default();
+        (!title.is_empty()).then_some(NowPlaying { artist, title })
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    None
+}
+
+/// How the dotfiles are managed and whether the repo has uncommitted
+/// changes, e.g. `chezmoi (clean)` or `yadm (3 changes)`. Knows chezmoi's
+/// source directory, yadm's repo, and ~/.dotfiles as either a bare repo
+/// over $HOME or an ordinary one (as stow users keep it). The state is left
+/// off if git fails or doesn't answer within a second.
+pub fn detect_dotfiles() -> String {
+    let Some(home) = user_home() else {
+        return String::new();
+    };
+    let home = Path::new(&home);
+    let work_tree = format!("--work-tree={}", home.display());
+
+    let chezmoi = home.join(".local/share/chezmoi");
+    let yadm = [
+        home.join(".local/share/yadm/repo.git"),
+        home.join(".config/yadm/repo.git"),
+        home.join(".yadm/repo.git"),
+    ]
+    .into_iter()
+    .find(|repo| repo.is_dir());
+    let dotfiles = home.join(".dotfiles");
+
+    // The git arguments that select the repo, before the subcommand.
+    let (manager, repo): (&str, Vec<String>) = if chezmoi.join(".git").exists() {
+        ("chezmoi", vec!["-C".into(), chezmoi.display().to_string()])
+    } else if let Some(repo) = yadm {
+        (
+            "yadm",
+            vec![format!("--git-dir={}", repo.display()), work_tree],
+        )
+    } else if dotfiles.join("HEAD").is_file() && dotfiles.join("objects").is_dir() {
+        (
+            "bare git",
+            vec![format!("--git-dir={}", dotfiles.display()), work_tree],
+        )
+    } else if dotfiles.join(".git").exists() {
+        ("git", vec!["-C".into(), dotfiles.display().to_string()])
+    } else {
+        return String::new();
+    };
+
+    let mut args: Vec<&str> = repo.iter().map(String::as_str).collect();
+    // A repo over the whole of $HOME would list everything untracked.
+    args.extend([
+        "--no-optional-locks",
+        "status",
+        "--porcelain",
+        "--untracked-files=no",
+    ]);
+    #[cfg(not(target_os = "windows"))]
+    if !which("git") {
+        missing_tool("git");
+    }
+    // A failed status (not a repo, an unsafe directory) says nothing, not
+    // that the repo is clean.
+    match status_within("git", &args, std::time::Duration::from_secs(1)) {
+        Some((exit, status)) if exit.success() => match status.lines().count() {
+            0 => format!("{} (clean)", manager),
+            1 => format!("{} (1 change)", manager),
+            changes => format!("{} ({} changes)", manager, changes),
+        },
+        _ => manager.to_string(),
+    }
+}
+
+fn terminal_display_name(name: &str) -> String {
+    match name {
+        "gnome-terminal-server" | "gnome-terminal-" => "gnome-terminal",
+        "wezterm-gui" => "wezterm",
+        "WindowsTerminal" => "Windows Terminal",
+        "Terminal" => "Apple Terminal",
+        "iTerm2" | "iTerm" => "iTerm2",
+        _ => name,
+    }
+    .to_string()
+}
+
+// The slow probes below are memoized until `reset_probes`, so a value read
+// by several consumers (or from several threads) is detected once a frame.
+static GPUS: Memo<Vec<String>> = Memo::new();
+static DISPLAYS: Memo<Vec<Display>> = Memo::new();
+static SCALE: Memo<f32> = Memo::new();
+static COLOR_DEPTH: Memo<(u32, bool)> = Memo::new();
+static MONITORS: Memo<Vec<Monitor>> = Memo::new();
+static PACKAGES: Memo<Vec<Packages>> = Memo::new();
+
+/// A `OnceLock` that can be emptied again. Clearing swaps in a fresh one
+/// rather than waiting on a probe that's still filling the old one, which
+/// keeps whatever it finds to itself.
+struct Memo<T>(Mutex<Option<Arc<OnceLock<T>>>>);
+
+impl<T: Clone> Memo<T> {
+    const fn new() -> Self {
+        Memo(Mutex::new(None))
+    }
+
+    fn get_or_init(&self, init: impl FnOnce() -> T) -> T {
+        let cell = {
+            let mut cell = self.0.lock().unwrap_or_else(|e| e.into_inner());
+            Arc::clone(cell.get_or_insert_with(Default::default))
+        };
+        cell.get_or_init(init).clone()
+    }
+
+    fn clear(&self) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// Forgets the memoized GPUs, displays and packages, so the next read
+/// probes them again. `--watch` calls this before every frame.
+pub fn reset_probes() {
+    GPUS.clear();
+    DISPLAYS.clear();
+    SCALE.clear();
+    COLOR_DEPTH.clear();
+    MONITORS.clear();
+    PACKAGES.clear();
+}
+
+fn gpus() -> Vec<String> {
+    GPUS.get_or_init(detect_gpus)
+}
+
+fn displays() -> Vec<Display> {
+    DISPLAYS.get_or_init(detect_displays)
+}
+
+fn scale() -> f32 {
+    SCALE.get_or_init(detect_scale)
+}
+
+fn color_depth() -> (u32, bool) {
+    COLOR_DEPTH.get_or_init(detect_color_depth)
+}
+
+fn monitors() -> Vec<Monitor> {
+    MONITORS.get_or_init(detect_monitors)
+}
+
+fn packages() -> Vec<Packages> {
+    PACKAGES.get_or_init(detect_packages_fast)
+}
+
+/// The X cursor theme and its size, e.g. `Bibata-Modern-Ice (24px)`.
+/// XCURSOR_THEME wins when it's set, as it does for X clients; then
+/// Plasma's or GNOME's setting for those desktops, then the theme
+/// ~/.icons/default inherits, which is what plain X falls back to.
+pub fn detect_cursor() -> String {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let home = home_dir().unwrap_or_default();
+        let mut theme = env::var("XCURSOR_THEME").unwrap_or_default();
+        let mut size = env::var("XCURSOR_SIZE").unwrap_or_default();
+        if !theme.is_empty() {
+            backend("cursor", "XCURSOR_THEME");
+        }
+
+        let desktop = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+        if theme.is_empty() && desktop.contains("KDE") {
+            let kcminputrc =
+                fs::read_to_string(format!("{}/.config/kcminputrc", home)).unwrap_or_default();
+            for line in kcminputrc.lines() {
+                if let Some(value) = line.strip_prefix("cursorTheme=") {
+                    theme = value.trim().to_string();
+                } else if let Some(value) = line.strip_prefix("cursorSize=") {
+                    size = value.trim().to_string();
+                }
+            }
+            if !theme.is_empty() {
+                backend("cursor", "kcminputrc");
+            }
+        }
+
+        // gsettings answers with its default even where nothing reads it,
+        // so it's only asked on the desktops that do.
+        let gnome = ["GNOME", "Unity", "Budgie", "Pantheon"]
+            .iter()
+            .any(|de| desktop.contains(de));
+        if theme.is_empty() && gnome && which("gsettings") {
+            let timeout = std::time::Duration::from_secs(1);
+            let get = |key| {
+                output_within(
+                    "gsettings",
+                    &["get", "org.gnome.desktop.interface", key],
+                    timeout,
+                )
+                .unwrap_or_default()
+            };
+            // 'Adwaita' and "uint32 24", or a bare 24.
+            backend("cursor", "gsettings");
+            theme = get("cursor-theme").trim().trim_matches('\'').to_string();
+            if size.is_empty() {
+                size = get("cursor-size")
+                    .split_whitespace()
+                    .last()
+                    .unwrap_or_default()
+                    .to_string();
+            }
+        }
+
+        if theme.is_empty() {
+            theme = [
+                format!("{}/.icons/default/index.theme", home),
+                format!("{}/.local/share/icons/default/index.theme", home),
+                "/usr/share/icons/default/index.theme".to_string(),
+            ]
+            .iter()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .find_map(|index| {
+                index.lines().find_map(|line| {
+                    let theme = line.strip_prefix("Inherits=")?;
+                    theme.split(',').next().map(|t| t.trim().to_string())
+                })
+            })
+            .unwrap_or_default();
+            if !theme.is_empty() {
+                backend("cursor", "index.theme");
+            }
+        }
+
+        match (theme.is_empty(), size.parse::<u32>()) {
+            (true, _) => String::new(),
+            (false, Ok(size)) if size > 0 => format!("{} ({}px)", theme, size),
+            (false, _) => theme,
+        }
+    }
+
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    String::new()
+}
+
+/// The rustc on $PATH and rustup's default toolchain, then the cargo on
+/// $PATH, e.g. `rustc 1.80.1 (stable), cargo 1.80.0`. The toolchain's host
+/// triple is dropped, and a nightly keeps its date.
+pub fn detect_rust() -> String {
+    // "rustc 1.80.1 (3f5fd8dd4 2024-08-06)"
+    let version = output_within("rustc", &["--version"], std::time::Duration::from_secs(2))
+        .and_then(|v| v.split_whitespace().nth(1).map(str::to_string));
+    let Some(version) = version else {
+        return String::new();
+    };
+
+    let home = user_home().unwrap_or_default();
+    let rustup_home = env::var("RUSTUP_HOME").unwrap_or_else(|_| format!("{}/.rustup", home));
+    let settings =
+        fs::read_to_string(Path::new(&rustup_home).join("settings.toml")).unwrap_or_default();
+    let toolchain = settings.lines().find_map(|line| {
+        let value = line.strip_prefix("default_toolchain")?.trim_start();
+        Some(value.strip_prefix('=')?.trim().trim_matches('"'))
+    });
+
+    let mut rust = match toolchain {
+        Some(toolchain) => {
+            // stable-x86_64-unknown-linux-gnu, nightly-2024-08-01-aarch64-apple-darwin
+            let mut parts = toolchain.split('-');
+            let mut channel = parts.next().unwrap_or_default().to_string();
+            let date: Vec<&str> = parts.take(3).collect();
+            if date.len() == 3 && date.iter().all(|p| p.bytes().all(|b| b.is_ascii_digit())) {
+                channel = format!("{}-{}", channel, date.join("-"));
+            }
+            format!("rustc {} ({})", version, channel)
+        }
+        None => format!("rustc {}", version),
+    };
+
+    // "cargo 1.80.0 (376290515 2024-07-16)"
+    let cargo = output_within("cargo", &["--version"], std::time::Duration::from_secs(2))
+        .and_then(|v| v.split_whitespace().nth(1).map(str::to_string));
+    if let Some(cargo) = cargo {
+        rust.push_str(&format!(", cargo {}", cargo));
+    }
+    rust
+}
+
+/// Counts the games in every Steam library, from the appmanifest each
+/// one has in its library's steamapps folder. Proton and the Steam
+/// runtimes are installed the same way, so they're left out of the count;
+/// the Proton shown is the one set for all titles in Steam's settings, or
+/// else the newest one installed.
+pub fn detect_steam() -> Option<Steam> {
+    let home = user_home()?;
+    let roots = if cfg!(target_os = "macos") {
+        vec![format!("{}/Library/Application Support/Steam", home)]
+    } else if cfg!(windows) {
+        vec![format!(
+            r"{}\Steam",
+            env::var("ProgramFiles(x86)").unwrap_or_else(|_| r"C:\Program Files (x86)".into())
+        )]
+    } else {
+        vec![
+            format!("{}/.local/share/Steam", home),
+            format!("{}/.steam/steam", home),
+            format!(
+                "{}/.var/app/com.valvesoftware.Steam/.local/share/Steam",
+                home
+            ),
+        ]
+    };
+    let root = roots
+        .into_iter()
+        .map(std::path::PathBuf::from)
+        .find(|root| root.join("steamapps/libraryfolders.vdf").exists())?;
+
+    let folders = fs::read_to_string(root.join("steamapps/libraryfolders.vdf")).ok()?;
+    let mut libraries: Vec<std::path::PathBuf> = vdf_pairs(&folders)
+        .filter(|(key, _)| *key == "path")
+        .map(|(_, path)| std::path::PathBuf::from(path.replace(r"\\", r"\")))
+        .collect();
+    if libraries.is_empty() {
+        libraries.push(root.clone());
+    }
+
+    let mut steam = Steam::default();
+    let mut protons = Vec::new();
+    for library in libraries {
+        let Ok(entries) = read_dir(library.join("steamapps")) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            if !file_name.starts_with("appmanifest_") || !file_name.ends_with(".acf") {
+                continue;
+            }
+            let Ok(manifest) = fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            let mut name = "";
+            let mut size = 0;
+            for (key, value) in vdf_pairs(&manifest) {
+                match key {
+                    "name" if name.is_empty() => name = value,
+                    "SizeOnDisk" => size = value.parse().unwrap_or(0),
+                    _ => {}
+                }
+            }
+            if name.starts_with("Proton") {
+                protons.push(name.to_string());
+            } else if !name.starts_with("Steam Linux Runtime")
+                && !name.starts_with("Steamworks Common Redistributables")
+            {
+                steam.games += 1;
+                steam.size += size;
+            }
+        }
+    }
+
+    // "CompatToolMapping" { "0" { "name" "proton_9" ... } }: app 0 is the
+    // "enable Steam Play for all other titles" setting.
+    let config = fs::read_to_string(root.join("config/config.vdf")).unwrap_or_default();
+    let default_tool = config
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| *line != "\"CompatToolMapping\"")
+        .skip_while(|line| *line != "\"0\"")
+        .find_map(|line| vdf_pairs(line).find(|(key, _)| *key == "name"))
+        .map(|(_, name)| name)
+        .filter(|name| !name.is_empty());
+    steam.proton = match default_tool {
+        // Steam's own builds are named proton_9, proton_experimental and
+        // so on; custom ones like GE-Proton9-20 keep their folder name.
+        Some(name) => match name.strip_prefix("proton_") {
+            Some("experimental") => "Proton Experimental".to_string(),
+            Some(version) => format!("Proton {}", version.replace('_', ".")),
+            None => name.to_string(),
+        },
+        None => protons
+            .into_iter()
+            .max_by(|a, b| proton_version(a).total_cmp(&proton_version(b)))
+            .unwrap_or_default(),
+    };
+
+    Some(steam)
+}
+
+/// The `"key"  "value"` lines of a Valve KeyValues (.vdf/.acf) file.
+/// Nesting is ignored.
+fn vdf_pairs(text: &str) -> impl Iterator<Item = (&str, &str)> {
+    text.lines().filter_map(|line| {
+        let mut quoted = line.split('"').skip(1).step_by(2);
+        Some((quoted.next()?, quoted.next()?))
+    })
+}
+
+/// "Proton 9.0 (Beta)" is 9.0; unnumbered builds like Experimental sort
+/// last.
+fn proton_version(name: &str) -> f32 {
+    name.split_whitespace()
+        .find_map(|word| word.parse().ok())
+        .unwrap_or(-1.0)
+}
+
+/// The driver behind each GPU with its version: the proprietary NVIDIA
+/// driver's from /proc, Mesa's from glxinfo or eglinfo for the open
+/// drivers, and the registry's on Windows.
+pub fn detect_gpu_driver() -> String {
+    #[cfg(target_os = "linux")]
+    {
+        let mut cards: Vec<_> = read_dir("/sys/class/drm")
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        cards.sort();
+
+        let mut drivers: Vec<String> = Vec::new();
+        for card in cards {
+            let is_card = card
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("card"))
+                .is_some_and(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()));
+            if !is_card {
+                continue;
+            }
+            let Some(driver) = fs::read_link(card.join("device/driver"))
+                .ok()
+                .and_then(|link| Some(link.file_name()?.to_string_lossy().into_owned()))
+            else {
+                continue;
+            };
+            if !drivers.contains(&driver) {
+                drivers.push(driver);
+            }
+        }
+        // The NVIDIA driver doesn't always register a DRM card.
+        let nvidia = fs::read_to_string("/proc/driver/nvidia/version")
+            .ok()
+            .and_then(|version| nvidia_version(&version));
+        if nvidia.is_some() && !drivers.iter().any(|d| d == "nvidia") {
+            drivers.push("nvidia".to_string());
+        }
+
+        let mut mesa = None;
+        let labels: Vec<String> = drivers
+            .iter()
+            .map(|driver| match driver.as_str() {
+                "nvidia" => match &nvidia {
+                    Some(version) => format!("nvidia {}", version),
+                    None => driver.clone(),
+                },
+                _ => match mesa.get_or_insert_with(mesa_version) {
+                    Some(version) => format!("{} (Mesa {})", driver, version),
+                    None => driver.clone(),
+                },
+            })
+            .collect();
+        return labels.join(", ");
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // Display adapters are numbered subkeys of the display device class.
+        const DISPLAY_CLASS: &str =
+            "SYSTEM\\CurrentControlSet\\Control\\Class\\{4d36e968-e325-11ce-bfc1-08002be10318}";
+
+        let mut drivers: Vec<String> = Vec::new();
+        for i in 0..8 {
+            let key = format!("{}\\{:04}", DISPLAY_CLASS, i);
+            let Some(version) = registry_string(&key, "DriverVersion") else {
+                continue;
+            };
+            let driver = match registry_string(&key, "ProviderName") {
+                Some(provider) => format!("{} {}", provider, version),
+                None => version,
+            };
+            if !drivers.contains(&driver) {
+                drivers.push(driver);
+            }
+        }
+        return drivers.join(", ");
+    }
+
+    String::new()
+}
+
+/// The version out of /proc/driver/nvidia/version's first line, e.g.
+/// "NVRM version: NVIDIA UNIX Open Kernel Module for x86_64  550.78  ...".
+#[cfg(target_os = "linux")]
+fn nvidia_version(version: &str) -> Option<String> {
+    let line = version.lines().next()?;
+    let rest = line.split("Kernel Module").nth(1)?;
+    rest.split_whitespace()
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
+/// The Mesa version in the OpenGL (or, without X, the EGL) version string.
+/// "Mesa" can also be the vendor, so it has to be followed by a number.
+#[cfg(target_os = "linux")]
+fn mesa_version() -> Option<String> {
+    let timeout = std::time::Duration::from_secs(2);
+    if under_wsl() {
+        return None;
+    }
+    if !which("glxinfo") && !which("eglinfo") {
+        missing_tool("glxinfo");
+    }
+    ["glxinfo", "eglinfo"]
+        .iter()
+        .filter(|cmd| which(cmd))
+        .filter_map(|cmd| output_within(cmd, &["-B"], timeout))
+        .find_map(|output| {
+            let words: Vec<&str> = output.split_whitespace().collect();
+            words.windows(2).find_map(|pair| {
+                let numbered = pair[1].starts_with(|c: char| c.is_ascii_digit());
+                (pair[0] == "Mesa" && numbered).then(|| pair[1].to_string())
+            })
+        })
+}
+
+/// GPUs from the DRM class, for machines without lspci or without a PCI
+/// bus. Render nodes only exist for devices that can render, so they skip
+/// display-only controllers; cards are the fallback for drivers without one.
+#[cfg(target_os = "linux")]
+fn drm_gpus() -> Vec<String> {
+    let mut nodes: Vec<_> = read_dir("/sys/class/drm")
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    nodes.sort();
+
+    let is_node = |path: &Path, prefix: &str| {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(prefix))
+            .is_some_and(|id| id.chars().all(|c| c.is_ascii_digit()))
+    };
+    let render: Vec<_> = nodes.iter().filter(|p| is_node(p, "renderD")).collect();
+    let nodes = if render.is_empty() {
+        nodes.iter().filter(|p| is_node(p, "card")).collect()
+    } else {
+        render
+    };
+
+    let mut gpus = Vec::<String>::new();
+    for node in nodes {
+        let Ok(uevent) = fs::read_to_string(node.join("device/uevent")) else {
+            continue;
+        };
+        let driver = uevent
+            .lines()
+            .find_map(|line| line.strip_prefix("DRIVER="))
+            .map(str::trim);
+        let compatible: Vec<&str> = uevent
+            .lines()
+            .filter(|line| line.starts_with("OF_COMPATIBLE_"))
+            .filter_map(|line| line.split_once('=').map(|(_, value)| value.trim()))
+            .collect();
+
+        let gpu = soc_gpu_name(&compatible).or_else(|| driver.map(str::to_string));
+        if let Some(gpu) = gpu {
+            if !gpus.contains(&gpu) {
+                gpus.push(gpu);
+            }
+        }
+    }
+    gpus
+}
+
+/// Names an SoC GPU from its device-tree `compatible` strings.
+#[cfg(target_os = "linux")]
+fn soc_gpu_name(compatible: &[&str]) -> Option<String> {
+    for compat in compatible {
+        if let Some(model) = compat.strip_prefix("arm,mali-") {
+            return Some(match model {
+                "midgard" | "bifrost" | "valhall" | "utgard" => {
+                    let mut family = model.to_string();
+                    family[..1].make_ascii_uppercase();
+                    format!("ARM Mali ({})", family)
+                }
+                _ => format!("ARM Mali-{}", model.to_uppercase()),
+            });
+        }
+        if let Some(model) = compat.strip_prefix("qcom,adreno-") {
+            let model = model.split('.').next().unwrap_or(model);
+            return Some(format!("Qualcomm Adreno {}", model));
+        }
+        let videocore = match *compat {
+            "brcm,bcm2835-vc4" | "brcm,bcm2835-v3d" | "brcm,cygnus-v3d" => Some("IV"),
+            "brcm,bcm2711-vc5" | "brcm,2711-v3d" | "brcm,7268-v3d" => Some("VI"),
+            "brcm,bcm2712-vc6" | "brcm,2712-v3d" => Some("VII"),
+            _ => None,
+        };
+        if let Some(version) = videocore {
+            return Some(format!("Broadcom VideoCore {}", version));
+        }
+        if compat.starts_with("apple,agx-") {
+            return Some("Apple AGX".to_string());
+        }
+        if compat.starts_with("img,powervr-") {
+            return Some("Imagination PowerVR".to_string());
+        }
+    }
+    None
+}
+
+pub fn detect_gpus() -> Vec<String> {
+    #[cfg(target_os = "linux")]
+    {
+        // WSL1 has no PCI bus to list, and WSL2 only a virtual adapter.
+        if which("lspci") && !under_wsl() {
+            if let Ok(out) = Command::new("lspci").args(["-mm", "-nn"]).output() {
+                let mut gpus = Vec::<String>::new();
+                let s = String::from_utf8_lossy(&out.stdout);
+                for line in s.lines() {
+                    if line.contains("VGA compatible controller")
+                        || line.contains("3D controller")
+                        || line.contains("Display controller")
+                    {
+                        let parts: Vec<&str> = line.split('"').collect();
+                        let name = if parts.len() >= 10 {
+                            let vendor = parts.get(5).unwrap_or(&"").trim();
+                            let device = parts.get(7).unwrap_or(&"").trim();
+                            format!("{} {}", vendor, device).trim().to_string()
+                        } else {
+                            String::new()
+                        };
+                        let name = if name.is_empty() {
+                            line.to_string()
+                        } else {
+                            name
+                        };
+
+                        // The GPU the firmware booted with goes first.
+                        // lspci leaves out domain 0000.
+                        let slot = parts.first().unwrap_or(&"").trim();
+                        let slot = if slot.matches(':').count() == 1 {
+                            format!("0000:{}", slot)
+                        } else {
+                            slot.to_string()
+                        };
+                        let boot_vga =
+                            fs::read_to_string(format!("/sys/bus/pci/devices/{}/boot_vga", slot))
+                                .is_ok_and(|v| v.trim() == "1");
+                        if boot_vga {
+                            gpus.insert(0, name);
+                        } else {
+                            gpus.push(name);
+                        }
+                    }
+                }
+                // ARM boards often have lspci installed but no PCI bus.
+                if !gpus.is_empty() {
+                    backend("gpus", "lspci");
+                    return gpus;
+                }
+            }
+        }
+
+        let gpus = drm_gpus();
+        return if gpus.is_empty() {
+            fallback_gpus()
+        } else {
+            backend("gpus", "/sys/class/drm");
+            gpus
+        };
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // Apple Silicon GPUs show up as AGXAccelerator, discrete and Intel
+        // ones as PCI devices with the display controller class (0x03).
+        let mut gpus: Vec<String> = iokit::services(c"AGXAccelerator")
+            .iter()
+            .filter_map(|service| service.string("model"))
+            .collect();
+        for device in iokit::services(c"IOPCIDevice") {
+            let is_display = device
+                .data("class-code")
+                .is_some_and(|class| class.get(2) == Some(&0x03));
+            if let Some(model) = device.string("model").filter(|_| is_display) {
+                if !gpus.contains(&model) {
+                    gpus.push(model);
+                }
+            }
+        }
+        if !gpus.is_empty() {
+            backend("gpus", "IOKit");
+            return gpus;
+        }
+
+        backend("gpus", "system_profiler");
+        if let Ok(output) = Command::new("system_profiler")
+            .args(&["SPDisplaysDataType"])
+            .output()
+        {
+            let output = String::from_utf8_lossy(&output.stdout);
+            for line in output.lines() {
+                if let Some(rest) = line.strip_prefix("      Chipset Model: ") {
+                    gpus.push(rest.trim().to_string());
+                }
+            }
+        }
+        return gpus;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Graphics::Dxgi::{
+            CreateDXGIFactory1, IDXGIFactory1, DXGI_ADAPTER_FLAG_SOFTWARE,
+        };
+
+        let mut gpus: Vec<String> = Vec::new();
+
+        backend("gpus", "DXGI");
+        unsafe {
+            let Ok(factory) = CreateDXGIFactory1::<IDXGIFactory1>() else {
+                return gpus;
+            };
+
+            let mut index = 0;
+            while let Ok(adapter) = factory.EnumAdapters1(index) {
+                index += 1;
+                let Ok(desc) = adapter.GetDesc1() else {
+                    continue;
+                };
+                // Skips the Microsoft Basic Render Driver.
+                if desc.Flags & DXGI_ADAPTER_FLAG_SOFTWARE.0 as u32 != 0 {
+                    continue;
+                }
+
+                let len = desc
+                    .Description
+                    .iter()
+                    .position(|&c| c == 0)
+                    .unwrap_or(desc.Description.len());
+                let name = String::from_utf16_lossy(&desc.Description[..len])
+                    .trim()
+                    .to_string();
+                if name.is_empty() {
+                    continue;
+                }
+
+                let vram_mib = desc.DedicatedVideoMemory / 1024 / 1024;
+                let gpu = if vram_mib >= 1024 {
+                    format!("{} ({} GiB)", name, (vram_mib + 512) / 1024)
+                } else if vram_mib > 0 {
+                    format!("{} ({} MiB)", name, vram_mib)
+                } else {
+                    name
+                };
+                if !gpus.contains(&gpu) {
+                    gpus.push(gpu);
+                }
+            }
+        }
+
+        return gpus;
+    }
+
+    fallback_gpus()
+}
+
+/// Last resort when the platform's own detection finds nothing.
+fn fallback_gpus() -> Vec<String> {
+    #[cfg(feature = "vulkan")]
+    {
+        backend("gpus", "vulkan");
+        vulkan_gpus()
+    }
+
+    #[cfg(not(feature = "vulkan"))]
+    {
+        Vec::new()
+    }
+}
+
+/// GPUs as the Vulkan loader sees them, with their type and driver
+/// version. Software rasterizers like llvmpipe are skipped.
+#[cfg(feature = "vulkan")]
+fn vulkan_gpus() -> Vec<String> {
+    use ash::vk;
+
+    let Ok(entry) = (unsafe { ash::Entry::load() }) else {
+        return Vec::new();
+    };
+    let app = vk::ApplicationInfo::default().api_version(vk::API_VERSION_1_0);
+    let create_info = vk::InstanceCreateInfo::default().application_info(&app);
+    let Ok(instance) = (unsafe { entry.create_instance(&create_info, None) }) else {
+        return Vec::new();
+    };
+
+    let mut gpus = Vec::new();
+    for device in unsafe { instance.enumerate_physical_devices() }.unwrap_or_default() {
+        let props = unsafe { instance.get_physical_device_properties(device) };
+        let kind = match props.device_type {
+            vk::PhysicalDeviceType::INTEGRATED_GPU => "integrated",
+            vk::PhysicalDeviceType::DISCRETE_GPU => "discrete",
+            vk::PhysicalDeviceType::VIRTUAL_GPU => "virtual",
+            _ => continue,
+        };
+        let Ok(name) = props.device_name_as_c_str() else {
+            continue;
+        };
+
+        let v = props.driver_version;
+        // NVIDIA packs its driver version as 10.8.8.6 bits, everyone else
+        // uses the Vulkan version encoding.
+        let driver = if props.vendor_id == 0x10de {
+            format!("{}.{}.{}", v >> 22, (v >> 14) & 0xff, (v >> 6) & 0xff)
+        } else {
+            format!(
+                "{}.{}.{}",
+                vk::api_version_major(v),
+                vk::api_version_minor(v),
+                vk::api_version_patch(v)
+            )
+        };
+
+        gpus.push(format!(
+            "{} ({}, driver {})",
+            name.to_string_lossy(),
+            kind,
+            driver
+        ));
+    }
+
+    unsafe { instance.destroy_instance(None) };
+    gpus
+}
+
+/// Every active display's current mode, the primary one first.
+pub fn detect_displays() -> Vec<Display> {
+    #[cfg(target_os = "linux")]
+    {
+        // fb0 is the console's framebuffer, which a Wayland session may not
+        // be using at all, and without X there's no xrandr to ask.
+        if env::var_os("WAYLAND_DISPLAY").is_some() {
+            let displays = wayland_outputs();
+            if !displays.is_empty() {
+                return displays;
+            }
+        }
+        if x_display() {
+            if which("xrandr") {
+                let displays = xrandr_displays();
+                if !displays.is_empty() {
+                    backend("resolution", "xrandr");
+                    return displays;
+                }
+            } else {
+                missing_tool("xrandr");
+            }
+        }
+        // One display at most, and no refresh rate.
+        if let Ok(v) = fs::read_to_string("/sys/class/graphics/fb0/virtual_size") {
+            if let Some((w, h)) = v.trim().split_once(',') {
+                if let (Ok(width), Ok(height)) = (w.parse(), h.parse()) {
+                    backend("resolution", "fb0");
+                    return vec![Display {
+                        width,
+                        height,
+                        refresh: 0,
+                    }];
+                }
+            }
+        }
+        return Vec::new();
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut displays = macos_displays();
+        displays.sort_by_key(|(main, _, _)| !main);
+        backend("resolution", "CoreGraphics");
+        return displays
+            .into_iter()
+            .map(|(_, monitor, refresh)| Display {
+                width: monitor.width,
+                height: monitor.height,
+                refresh,
+            })
+            .collect();
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::core::PCWSTR;
+        use windows::Win32::Graphics::Gdi::{
+            EnumDisplayDevicesW, EnumDisplaySettingsW, DEVMODEW, DISPLAY_DEVICEW,
+            DISPLAY_DEVICE_ACTIVE, DISPLAY_DEVICE_PRIMARY_DEVICE, ENUM_CURRENT_SETTINGS,
+        };
+
+        let mut displays = Vec::new();
+        unsafe {
+            for i in 0.. {
+                let mut device = DISPLAY_DEVICEW {
+                    cb: std::mem::size_of::<DISPLAY_DEVICEW>() as u32,
+                    ..Default::default()
+                };
+                if !EnumDisplayDevicesW(PCWSTR::null(), i, &mut device, 0).as_bool() {
+                    break;
+                }
+                if !device.StateFlags.contains(DISPLAY_DEVICE_ACTIVE) {
+                    continue;
+                }
+                let mut mode = DEVMODEW {
+                    dmSize: std::mem::size_of::<DEVMODEW>() as u16,
+                    ..Default::default()
+                };
+                let name = PCWSTR(device.DeviceName.as_ptr());
+                if !EnumDisplaySettingsW(name, ENUM_CURRENT_SETTINGS, &mut mode).as_bool() {
+                    continue;
+                }
+                let display = Display {
+                    width: mode.dmPelsWidth,
+                    height: mode.dmPelsHeight,
+                    // 0 and 1 mean the hardware default.
+                    refresh: if mode.dmDisplayFrequency > 1 {
+                        mode.dmDisplayFrequency * 1000
+                    } else {
+                        0
+                    },
+                };
+                if device.StateFlags.contains(DISPLAY_DEVICE_PRIMARY_DEVICE) {
+                    displays.insert(0, display);
+                } else {
+                    displays.push(display);
+                }
+            }
+        }
+        backend("resolution", "EnumDisplaySettings");
+        return displays;
+    }
+
+    #[allow(unreachable_code)]
+    Vec::new()
+}
+
+/// Each connected output's size from `xrandr --current`, with the refresh
+/// rate of its current mode, the one marked `*`:
+///
+/// ```text
+/// DP-1 connected primary 2560x1440+0+0 (normal left inverted ...) 597mm x 336mm
+///    2560x1440     59.95 +  164.96*
+/// ```
+///
+/// The geometry is after any rotation, unlike the mode lines.
+#[cfg(target_os = "linux")]
+fn xrandr_displays() -> Vec<Display> {
+    let timeout = std::time::Duration::from_secs(2);
+    let output = output_within("xrandr", &["--current"], timeout).unwrap_or_default();
+
+    let mut displays = Vec::new();
+    // The display the mode lines below belong to, if its output is on.
+    let mut current = None;
+    for line in output.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            current = None;
+            if !line.contains(" connected") {
+                continue;
+            }
+            let size = line.split_whitespace().find_map(|word| {
+                let (size, _) = word.split_once('+')?;
+                let (w, h) = size.split_once('x')?;
+                Some((w.parse().ok()?, h.parse().ok()?))
+            });
+            if let Some((width, height)) = size {
+                let display = Display {
+                    width,
+                    height,
+                    refresh: 0,
+                };
+                if line.contains(" primary ") {
+                    displays.insert(0, display);
+                    current = Some(0);
+                } else {
+                    displays.push(display);
+                    current = Some(displays.len() - 1);
+                }
+            }
+        } else if let Some(i) = current {
+            let rate = line
+                .split_whitespace()
+                .find(|word| word.contains('*'))
+                .and_then(|word| word.trim_end_matches(['*', '+']).parse::<f32>().ok());
+            if let Some(rate) = rate {
+                displays[i].refresh = (rate * 1000.0).round() as u32;
+                current = None;
+            }
+        }
+    }
+    displays
+}
+
+/// The current mode of each Wayland output, turned the way the output is:
+/// from the compositor itself, else from wlr-randr or swaymsg.
+#[cfg(target_os = "linux")]
+fn wayland_outputs() -> Vec<Display> {
+    let outputs = wayland_output_modes();
+    if !outputs.is_empty() {
+        backend("resolution", "wl_output");
+        return outputs;
+    }
+
+    let timeout = std::time::Duration::from_secs(1);
+    if which("wlr-randr") {
+        // "  2560x1440 px, 143.912003 Hz (preferred, current)"
+        let modes = output_within("wlr-randr", &[], timeout).unwrap_or_default();
+        let outputs: Vec<Display> = modes
+            .lines()
+            .filter(|line| line.contains("current"))
+            .filter_map(|line| {
+                let mut words = line.split_whitespace();
+                let (w, h) = words.next()?.split_once('x')?;
+                let refresh = words
+                    .nth(1)
+                    .and_then(|hz| hz.parse::<f32>().ok())
+                    .map_or(0, |hz| (hz * 1000.0).round() as u32);
+                Some(Display {
+                    width: w.parse().ok()?,
+                    height: h.parse().ok()?,
+                    refresh,
+                })
+            })
+            .collect();
+        if !outputs.is_empty() {
+            backend("resolution", "wlr-randr");
+            return outputs;
+        }
+    }
+    if which("swaymsg") {
+        backend("resolution", "swaymsg");
+        let json = output_within("swaymsg", &["-t", "get_outputs", "--raw"], timeout);
+        let outputs: Vec<serde_json::Value> = json
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        return outputs
+            .iter()
+            .filter(|output| output["active"].as_bool().unwrap_or(true))
+            .filter_map(|output| {
+                let mode = &output["current_mode"];
+                let (w, h) = (
+                    mode["width"].as_u64()? as u32,
+                    mode["height"].as_u64()? as u32,
+                );
+                let turned = matches!(
+                    output["transform"].as_str(),
+                    Some("90" | "270" | "flipped-90" | "flipped-270")
+                );
+                let (width, height) = if turned { (h, w) } else { (w, h) };
+                Some(Display {
+                    width,
+                    height,
+                    refresh: mode["refresh"].as_u64().unwrap_or(0) as u32,
+                })
+            })
+            .collect();
+    }
+    Vec::new()
+}
+
+/// Asks the compositor for every wl_output's current mode, see
+/// `wayland_query_outputs`.
+#[cfg(target_os = "linux")]
+fn wayland_output_modes() -> Vec<Display> {
+    let Some(mut socket) = wayland_connect() else {
+        return Vec::new();
+    };
+    wayland_query_outputs(&mut socket)
+        .into_iter()
+        .map(|(mode, _)| mode)
+        .collect()
+}
+
+/// The first wl_output's scale, as the compositor reports it. Whole
+/// numbers only: a fractional scale is rounded up, as wl_output sends it.
+#[cfg(target_os = "linux")]
+fn wayland_output_scale() -> Option<f32> {
+    let mut socket = wayland_connect()?;
+    wayland_query_outputs(&mut socket)
+        .into_iter()
+        .map(|(_, scale)| scale)
+        .find(|scale| *scale > 0)
+        .map(|scale| scale as f32)
+}
+
+/// A connection to the compositor named by WAYLAND_DISPLAY.
+#[cfg(target_os = "linux")]
+fn wayland_connect() -> Option<std::os::unix::net::UnixStream> {
+    use std::os::unix::net::UnixStream;
+
+    let display = env::var("WAYLAND_DISPLAY").ok()?;
+    let path = if display.starts_with('/') {
+        std::path::PathBuf::from(display)
+    } else {
+        Path::new(&env::var("XDG_RUNTIME_DIR").unwrap_or_default()).join(display)
+    };
+    let socket = UnixStream::connect(path).ok()?;
+    let _ = socket.set_read_timeout(Some(std::time::Duration::from_millis(500)));
+    Some(socket)
+}
+
+/// Asks the compositor for every wl_output's current mode and scale,
+/// speaking the Wayland wire protocol over `socket`: get the registry,
+/// bind each wl_output it lists, and collect their `geometry`, `mode` and
+/// `scale` events up to a sync. Messages are a header of the object id and
+/// (size << 16 | opcode), then arguments as 32-bit words, with strings
+/// length-prefixed and padded to a word. The scale is 0 for outputs bound
+/// at version 1, which have none.
+#[cfg(target_os = "linux")]
+fn wayland_query_outputs(socket: &mut std::os::unix::net::UnixStream) -> Vec<(Display, u32)> {
+    use std::io::Write;
+
+    // Ids: 1 is wl_display, 2 the registry, 3 and 4 sync callbacks, and
+    // the outputs from 5.
+    let mut request = Vec::new();
+    wayland_message(&mut request, 1, 1, &2u32.to_ne_bytes()); // get_registry
+    wayland_message(&mut request, 1, 0, &3u32.to_ne_bytes()); // sync
+    if socket.write_all(&request).is_err() {
+        return Vec::new();
+    }
+
+    let mut buffer = Vec::new();
+    let mut globals = Vec::new();
+    let done = wayland_events(socket, &mut buffer, 3, |object, opcode, args| {
+        // wl_registry.global(name, interface, version)
+        if object == 2 && opcode == 0 {
+            let interface = wayland_string(args, 4);
+            if interface.as_deref() == Some("wl_output") {
+                let version_at = args.len() - 4;
+                let word = |at: usize| u32::from_ne_bytes(args[at..at + 4].try_into().unwrap());
+                globals.push((word(0), word(version_at)));
+            }
+        }
+    });
+    if !done || globals.is_empty() {
+        return Vec::new();
+    }
+
+    // Version 2 added the scale event.
+    let versions: Vec<u32> = globals
+        .iter()
+        .map(|(_, version)| (*version).min(2))
+        .collect();
+    let mut request = Vec::new();
+    for (i, ((name, _), version)) in globals.iter().zip(&versions).enumerate() {
+        // wl_registry.bind(name, interface, version, new id)
+        let mut args = name.to_ne_bytes().to_vec();
+        wayland_string_arg(&mut args, "wl_output");
+        args.extend(version.to_ne_bytes());
+        args.extend((5 + i as u32).to_ne_bytes());
+        wayland_message(&mut request, 2, 0, &args);
+    }
+    wayland_message(&mut request, 1, 0, &4u32.to_ne_bytes());
+    if socket.write_all(&request).is_err() {
+        return Vec::new();
+    }
+
+    // (mode, turned, scale) per output. A compositor that never sends
+    // the scale means 1.
+    let mut outputs: Vec<_> = versions
+        .iter()
+        .map(|version| (Display::default(), false, u32::from(*version >= 2)))
+        .collect();
+    wayland_events(socket, &mut buffer, 4, |object, opcode, args| {
+        let Some(output) = (object as usize)
+            .checked_sub(5)
+            .and_then(|i| outputs.get_mut(i))
+        else {
+            return;
+        };
+        let word = |at: usize| {
+            args.get(at..at + 4)
+                .map_or(0, |b| u32::from_ne_bytes(b.try_into().unwrap()))
+        };
+        match opcode {
+            // geometry(x, y, mm width, mm height, subpixel, make, model,
+            // transform): 90 and 270 degrees are the odd transforms.
+            0 => output.1 = word(args.len().saturating_sub(4)) % 2 == 1,
+            // mode(flags, width, height, refresh), flag 1 being current.
+            1 if word(0) & 1 == 1 => {
+                output.0 = Display {
+                    width: word(4),
+                    height: word(8),
+                    refresh: word(12),
+                }
+            }
+            // scale(factor)
+            3 => output.2 = word(0),
+            _ => {}
+        }
+    });
+
+    outputs
+        .into_iter()
+        .filter(|(mode, _, _)| mode.width > 0 && mode.height > 0)
+        .map(|(mode, turned, scale)| {
+            let mode = if turned {
+                Display {
+                    width: mode.height,
+                    height: mode.width,
+                    ..mode
+                }
+            } else {
+                mode
+            };
+            (mode, scale)
+        })
+        .collect()
+}
+
+/// Reads Wayland events into `on_event` until `callback` reports done.
+/// False if the compositor stops answering first.
+#[cfg(target_os = "linux")]
+fn wayland_events(
+    socket: &mut std::os::unix::net::UnixStream,
+    buffer: &mut Vec<u8>,
+    callback: u32,
+    mut on_event: impl FnMut(u32, u16, &[u8]),
+) -> bool {
+    use std::io::Read;
+
+    let mut chunk = [0u8; 4096];
+    loop {
+        while buffer.len() >= 8 {
+            let object = u32::from_ne_bytes(buffer[0..4].try_into().unwrap());
+            let header = u32::from_ne_bytes(buffer[4..8].try_into().unwrap());
+            let (size, opcode) = ((header >> 16) as usize, (header & 0xffff) as u16);
+            if size < 8 {
+                return false;
+            }
+            if buffer.len() < size {
+                break;
+            }
+            if object == callback && opcode == 0 {
+                buffer.drain(..size);
+                return true;
+            }
+            on_event(object, opcode, &buffer[8..size]);
+            buffer.drain(..size);
+        }
+        match socket.read(&mut chunk) {
+            Ok(0) | Err(_) => return false,
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
+
+/// Appends a message from `object` with `opcode` and `args` to `out`.
+#[cfg(target_os = "linux")]
+fn wayland_message(out: &mut Vec<u8>, object: u32, opcode: u16, args: &[u8]) {
+    out.extend(object.to_ne_bytes());
+    out.extend(((((8 + args.len()) as u32) << 16) | opcode as u32).to_ne_bytes());
+    out.extend(args);
+}
+
+/// Appends `s` to `out` as a string argument, NUL-terminated and padded.
+#[cfg(target_os = "linux")]
+fn wayland_string_arg(out: &mut Vec<u8>, s: &str) {
+    out.extend((s.len() as u32 + 1).to_ne_bytes());
+    out.extend(s.as_bytes());
+    out.resize(out.len() + 4 - s.len() % 4, 0);
+}
+
+/// The length-prefixed string argument at `at` in a Wayland message.
+#[cfg(target_os = "linux")]
+fn wayland_string(args: &[u8], at: usize) -> Option<String> {
+    let len = u32::from_ne_bytes(args.get(at..at + 4)?.try_into().ok()?) as usize;
+    let bytes = args.get(at + 4..at + 4 + len)?;
+    Some(String::from_utf8_lossy(bytes.strip_suffix(&[0]).unwrap_or(bytes)).into_owned())
+}
+
+/// The display scale factor. On Wayland, wlr-randr's where it's
+/// installed, since it has fractional scales, else the compositor's own
+/// whole-number one from wl_output, as on GNOME and KDE.
+pub fn detect_scale() -> f32 {
+    #[cfg(target_os = "linux")]
+    {
+        if env::var("WAYLAND_DISPLAY").is_ok() {
+            if which("wlr-randr") {
+                if let Ok(out) = Command::new("wlr-randr").output() {
+                    let s = String::from_utf8_lossy(&out.stdout);
+                    for line in s.lines() {
+                        if let Some(v) = line.trim().strip_prefix("Scale:") {
+                            if let Ok(scale) = v.trim().parse::<f32>() {
+                                backend("scale", "wlr-randr");
+                                return scale;
                             }
                         }
                     }
                 }
             }
+            if let Some(scale) = wayland_output_scale() {
+                backend("scale", "wl_output");
+                return scale;
+            }
+        }
+        if x_display() && which("xrdb") {
+            if let Ok(out) = Command::new("xrdb").arg("-query").output() {
+                let s = String::from_utf8_lossy(&out.stdout);
+                for line in s.lines() {
+                    if let Some(v) = line.strip_prefix("Xft.dpi:") {
+                        if let Ok(dpi) = v.trim().parse::<f32>() {
+                            return dpi / 96.0;
+                        }
+                    }
+                }
+            }
         }
+        return 0.0;
+    }
 
-        if !gpus.is_empty() {
-            return gpus;
+    #[cfg(target_os = "macos")]
+    {
+        use core_graphics::display::CGDisplay;
+
+        if let Some(mode) = CGDisplay::main().display_mode() {
+            if mode.width() > 0 {
+                return mode.pixel_width() as f32 / mode.width() as f32;
+            }
+        }
+        return 0.0;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        unsafe {
+            use windows::Win32::UI::HiDpi::GetDpiForSystem;
+
+            let dpi = GetDpiForSystem();
+            if dpi > 0 {
+                return dpi as f32 / 96.0;
+            }
         }
+        return 0.0;
+    }
 
-        if let Ok(output) = Command::new("wmic")
-            .args(["path", "win32_VideoController", "get", "name"])
-            .output()
-        {
-            let output = String::from_utf8_lossy(&output.stdout);
-            for line in output.lines().skip(1) {
-                let line = line.trim();
-                if !line.is_empty() && line != "Name" {
-                    gpus.push(line.to_string());
+    0.0
+}
+
+pub fn detect_color_depth() -> (u32, bool) {
+    #[cfg(target_os = "linux")]
+    {
+        if x_display() && which("xrandr") {
+            if let Ok(out) = Command::new("xrandr").arg("--prop").output() {
+                let s = String::from_utf8_lossy(&out.stdout);
+                let mut in_connected = false;
+                let mut bpc = 0u32;
+                let mut hdr = false;
+                for line in s.lines() {
+                    if !line.starts_with(char::is_whitespace) {
+                        if in_connected {
+                            break;
+                        }
+                        in_connected = line.contains(" connected");
+                        continue;
+                    }
+                    if !in_connected {
+                        continue;
+                    }
+                    if let Some((key, value)) = line.trim().split_once(':') {
+                        let value = value.split_whitespace().next().unwrap_or("");
+                        match key {
+                            "max bpc" => bpc = value.parse().unwrap_or(0),
+                            "Colorspace" => hdr = value.starts_with("BT2020"),
+                            _ => {}
+                        }
+                    }
+                }
+                if bpc > 0 {
+                    return (bpc * 3, hdr);
+                }
+            }
+        }
+        if let Ok(v) = fs::read_to_string("/sys/class/graphics/fb0/bits_per_pixel") {
+            if let Ok(bpp) = v.trim().parse::<u32>() {
+                return (bpp, false);
+            }
+        }
+        return (0, false);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use core_graphics::display::CGDisplay;
+
+        if let Some(mode) = CGDisplay::main().display_mode() {
+            let depth = mode.bit_depth() as u32;
+            return (depth, depth >= 48);
+        }
+        return (0, false);
+    }
+
+    (0, false)
+}
+
+pub fn detect_monitors() -> Vec<Monitor> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut monitors = Vec::new();
+        if let Ok(entries) = read_dir("/sys/class/drm") {
+            let mut connectors: Vec<_> = entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.join("edid").exists())
+                .collect();
+            connectors.sort();
+
+            for connector in connectors {
+                let connected = fs::read_to_string(connector.join("status"))
+                    .map(|s| s.trim() == "connected")
+                    .unwrap_or(false);
+                if !connected {
+                    continue;
                 }
+                if let Ok(edid) = fs::read(connector.join("edid")) {
+                    if let Some(monitor) = parse_edid(&edid) {
+                        monitors.push(monitor);
+                    }
+                }
+            }
+        }
+        return monitors;
+    }
+
+    #[cfg(target_os = "macos")]
+    return macos_displays()
+        .into_iter()
+        .map(|(_, monitor, _)| monitor)
+        .collect();
+
+    Vec::new()
+}
+
+#[cfg(target_os = "macos")]
+mod iokit {
+    use core_foundation::base::{kCFAllocatorDefault, CFAllocatorRef, CFType, CFTypeRef, TCFType};
+    use core_foundation::data::CFData;
+    use core_foundation::dictionary::CFMutableDictionaryRef;
+    use core_foundation::string::{CFString, CFStringRef};
+    use std::ffi::{c_char, CStr};
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOServiceMatching(name: *const c_char) -> CFMutableDictionaryRef;
+        fn IOServiceGetMatchingServices(
+            main_port: u32,
+            matching: CFMutableDictionaryRef,
+            existing: *mut u32,
+        ) -> i32;
+        fn IOIteratorNext(iterator: u32) -> u32;
+        fn IOObjectRelease(object: u32) -> i32;
+        fn IORegistryEntryCreateCFProperty(
+            entry: u32,
+            key: CFStringRef,
+            allocator: CFAllocatorRef,
+            options: u32,
+        ) -> CFTypeRef;
+        fn IOServiceOpen(service: u32, owning_task: u32, kind: u32, connect: *mut u32) -> i32;
+        fn IOServiceClose(connect: u32) -> i32;
+        fn IOConnectCallStructMethod(
+            connect: u32,
+            selector: u32,
+            input: *const SmcKeyData,
+            input_size: usize,
+            output: *mut SmcKeyData,
+            output_size: *mut usize,
+        ) -> i32;
+    }
+
+    extern "C" {
+        static mach_task_self_: u32;
+    }
+
+    pub struct Service(u32);
+
+    impl Drop for Service {
+        fn drop(&mut self) {
+            unsafe {
+                IOObjectRelease(self.0);
+            }
+        }
+    }
+
+    impl Service {
+        fn property(&self, key: &str) -> Option<CFType> {
+            let key = CFString::new(key);
+            unsafe {
+                let value = IORegistryEntryCreateCFProperty(
+                    self.0,
+                    key.as_concrete_TypeRef(),
+                    kCFAllocatorDefault,
+                    0,
+                );
+                (!value.is_null()).then(|| CFType::wrap_under_create_rule(value))
+            }
+        }
+
+        pub fn data(&self, key: &str) -> Option<Vec<u8>> {
+            let data = self.property(key)?.downcast::<CFData>()?;
+            Some(data.bytes().to_vec())
+        }
+
+        /// Reads a string property, which IOKit stores either as a CFString
+        /// or as NUL-terminated CFData.
+        pub fn string(&self, key: &str) -> Option<String> {
+            let value = self.property(key)?;
+            let text = if let Some(string) = value.downcast::<CFString>() {
+                string.to_string()
+            } else {
+                let data = value.downcast::<CFData>()?;
+                String::from_utf8_lossy(data.bytes())
+                    .trim_end_matches('\0')
+                    .to_string()
+            };
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_string())
+        }
+    }
+
+    /// SMCKeyData_t from the AppleSMC user client, 80 bytes.
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct SmcKeyData {
+        key: u32,
+        vers: [u8; 6],
+        p_limit_data: [u32; 4],
+        data_size: u32,
+        data_type: u32,
+        data_attributes: u8,
+        // keyInfo is its own struct in C, padded to 12 bytes
+        _padding: [u8; 3],
+        result: u8,
+        status: u8,
+        data8: u8,
+        data32: u32,
+        bytes: [u8; 32],
+    }
+
+    const _: () = assert!(std::mem::size_of::<SmcKeyData>() == 80);
+
+    /// A connection to the System Management Controller, which owns the
+    /// fans and most sensors on Macs.
+    pub struct Smc(u32);
+
+    impl Drop for Smc {
+        fn drop(&mut self) {
+            unsafe {
+                IOServiceClose(self.0);
             }
         }
-
-        return gpus;
     }
 
-    Vec::new()
-}
+    impl Smc {
+        const HANDLE_YPC_EVENT: u32 = 2;
+        const READ_KEY: u8 = 5;
+        const GET_KEY_INFO: u8 = 9;
 
-fn detect_resolution() -> (u32, u32) {
-    #[cfg(target_os = "linux")]
-    {
-        if let Ok(v) = fs::read_to_string("/sys/class/graphics/fb0/virtual_size") {
-            let mut it = v.trim().split(',');
-            if let (Some(w), Some(h)) = (it.next(), it.next()) {
-                if let (Ok(ww), Ok(hh)) = (w.parse::<u32>(), h.parse::<u32>()) {
-                    return (ww, hh);
-                }
-            }
+        pub fn open() -> Option<Self> {
+            let service = services(c"AppleSMC").into_iter().next()?;
+            let mut connection = 0;
+            let status = unsafe { IOServiceOpen(service.0, mach_task_self_, 0, &mut connection) };
+            (status == 0).then_some(Smc(connection))
         }
-        if std::env::var("DISPLAY").is_ok() && which("xrandr") {
-            if let Ok(out) = Command::new("xrandr").arg("--current").output() {
-                let s = String::from_utf8_lossy(&out.stdout);
-                for line in s.lines() {
-                    if let Some(idx) = line.find("current") {
-                        let tail = &line[idx + "current".len()..];
-                        let mut it = tail.split_whitespace();
-                        let w = it.next().and_then(|t| t.parse::<u32>().ok());
-                        let _x = it.next();
-                        let h = it
-                            .next()
-                            .map(|t| t.trim_end_matches(','))
-                            .and_then(|t| t.parse::<u32>().ok());
-                        if let (Some(ww), Some(hh)) = (w, h) {
-                            return (ww, hh);
-                        }
-                    }
-                }
+
+        fn call(&self, input: &SmcKeyData) -> Option<SmcKeyData> {
+            let mut output = SmcKeyData::default();
+            let mut size = std::mem::size_of::<SmcKeyData>();
+            let status = unsafe {
+                IOConnectCallStructMethod(
+                    self.0,
+                    Self::HANDLE_YPC_EVENT,
+                    input,
+                    size,
+                    &mut output,
+                    &mut size,
+                )
+            };
+            (status == 0 && output.result == 0).then_some(output)
+        }
+
+        /// Reads a four character key, returning its type code and bytes.
+        pub fn read(&self, key: &[u8; 4]) -> Option<([u8; 4], Vec<u8>)> {
+            let mut input = SmcKeyData {
+                key: u32::from_be_bytes(*key),
+                data8: Self::GET_KEY_INFO,
+                ..Default::default()
+            };
+            let info = self.call(&input)?;
+
+            input.data_size = info.data_size;
+            input.data8 = Self::READ_KEY;
+            let value = self.call(&input)?;
+            let len = (info.data_size as usize).min(value.bytes.len());
+            Some((info.data_type.to_be_bytes(), value.bytes[..len].to_vec()))
+        }
+
+        /// Reads a numeric key of any of the types fans and sensors use.
+        pub fn read_number(&self, key: &[u8; 4]) -> Option<f32> {
+            let (kind, bytes) = self.read(key)?;
+            match &kind {
+                b"ui8 " => Some(*bytes.first()? as f32),
+                b"ui16" => Some(u16::from_be_bytes(bytes.get(..2)?.try_into().ok()?) as f32),
+                b"fpe2" => Some(u16::from_be_bytes(bytes.get(..2)?.try_into().ok()?) as f32 / 4.0),
+                b"flt " => Some(f32::from_le_bytes(bytes.get(..4)?.try_into().ok()?)),
+                _ => None,
             }
         }
-        return (0u32, 0u32);
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        if let Ok(output) = Command::new("system_profiler")
-            .args(&["SPDisplaysDataType"])
-            .output()
-        {
-            let output = String::from_utf8_lossy(&output.stdout);
-            for line in output.lines() {
-                if let Some(rest) = line.strip_prefix("          Resolution: ") {
-                    let mut parts = rest.split_whitespace();
-                    if let (Some(w), Some(_x), Some(h)) = (parts.next(), parts.next(), parts.next())
-                    {
-                        if let (Ok(ww), Ok(hh)) = (w.parse::<u32>(), h.parse::<u32>()) {
-                            return (ww, hh);
-                        }
-                    }
+    /// Every registered service of the given IOKit class.
+    pub fn services(class: &CStr) -> Vec<Service> {
+        let mut services = Vec::new();
+        unsafe {
+            let matching = IOServiceMatching(class.as_ptr());
+            if matching.is_null() {
+                return services;
+            }
+            let mut iterator = 0;
+            // Consumes the matching dictionary. Port 0 is kIOMainPortDefault.
+            if IOServiceGetMatchingServices(0, matching, &mut iterator) != 0 {
+                return services;
+            }
+            loop {
+                let service = IOIteratorNext(iterator);
+                if service == 0 {
+                    break;
                 }
+                services.push(Service(service));
             }
+            IOObjectRelease(iterator);
         }
-        return (0u32, 0u32);
+        services
     }
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        unsafe {
-            use windows::Win32::UI::WindowsAndMessaging::{
-                GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN,
+/// Active displays as `(is_main, monitor)`, in pixels rather than the
+/// points CGDisplayBounds reports, so Retina panels show their real size.
+#[cfg(target_os = "macos")]
+fn macos_displays() -> Vec<(bool, Monitor, u32)> {
+    use core_graphics::display::CGDisplay;
+
+    let Ok(ids) = CGDisplay::active_displays() else {
+        return Vec::new();
+    };
+
+    ids.into_iter()
+        .map(|id| {
+            let display = CGDisplay::new(id);
+            let bounds = display.bounds();
+            let mode = display.display_mode();
+            let (width, height) = mode
+                .as_ref()
+                .map(|mode| (mode.pixel_width() as u32, mode.pixel_height() as u32))
+                .filter(|&(w, h)| w > 0 && h > 0)
+                .unwrap_or((bounds.size.width as u32, bounds.size.height as u32));
+            // Built-in panels often report 0.
+            let refresh = mode.map_or(0, |mode| (mode.refresh_rate() * 1000.0).round() as u32);
+            let name = if display.is_builtin() {
+                "Built-in Display".to_string()
+            } else {
+                format!("Display {}", id)
             };
 
-            let width = GetSystemMetrics(SM_CXSCREEN);
-            let height = GetSystemMetrics(SM_CYSCREEN);
+            (
+                display.is_main(),
+                Monitor {
+                    name,
+                    width,
+                    height,
+                },
+                refresh,
+            )
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn parse_edid(edid: &[u8]) -> Option<Monitor> {
+    const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+    if edid.len() < 128 || edid[..8] != HEADER {
+        return None;
+    }
 
-            if width > 0 && height > 0 {
-                return (width as u32, height as u32);
+    let mut monitor = Monitor::default();
+    for block in edid[54..126].chunks(18) {
+        if block[0] != 0 || block[1] != 0 {
+            if monitor.width == 0 {
+                monitor.width = block[2] as u32 | ((block[4] as u32 & 0xf0) << 4);
+                monitor.height = block[5] as u32 | ((block[7] as u32 & 0xf0) << 4);
             }
+        } else if block[3] == 0xfc {
+            let text: String = block[5..]
+                .iter()
+                .take_while(|b| **b != 0x0a)
+                .map(|b| *b as char)
+                .collect();
+            monitor.name = text.trim().to_string();
         }
+    }
 
-        return (0u32, 0u32);
+    if monitor.name.is_empty() {
+        let id = u16::from_be_bytes([edid[8], edid[9]]);
+        let vendor: String = [(id >> 10) & 0x1f, (id >> 5) & 0x1f, id & 0x1f]
+            .iter()
+            .map(|c| (b'A' + *c as u8 - 1) as char)
+            .collect();
+        let product = u16::from_le_bytes([edid[10], edid[11]]);
+        monitor.name = format!("{} {:04X}", vendor, product);
     }
 
-    (0u32, 0u32)
+    Some(monitor)
 }
 
-fn detect_packages_fast() -> (u32, String) {
-    let mut total: u32 = 0;
-    let mut labels: Vec<String> = Vec::new();
+pub fn detect_packages_fast() -> Vec<Packages> {
+    #[allow(unused_mut)]
+    let mut packages: Vec<Packages> = Vec::new();
 
     #[cfg(target_os = "linux")]
     {
-        if Path::new("/var/lib/dpkg/status").exists() {
-            if let Ok(s) = fs::read_to_string("/var/lib/dpkg/status") {
-                let count = s
-                    .lines()
-                    .filter(|l| *l == "Status: install ok installed")
-                    .count() as u32;
-                if count > 0 {
-                    total += count;
-                    labels.push(format!("{} (dpkg)", count));
-                }
+        if let Ok(file) = fs::File::open("/var/lib/dpkg/status") {
+            let count = dpkg_installed(io::BufReader::with_capacity(64 * 1024, file));
+            if count > 0 {
+                packages.push(Packages {
+                    manager: "dpkg".to_string(),
+                    count,
+                });
             }
         }
 
@@ -811,11 +5223,20 @@ fn detect_packages_fast() -> (u32, String) {
                 }
             }
             if count > 0 {
-                total += count;
-                labels.push(format!("{} (pacman)", count));
+                packages.push(Packages {
+                    manager: "pacman".to_string(),
+                    count,
+                });
             }
         }
 
+        if let Some(count) = portage_packages().filter(|&count| count > 0) {
+            packages.push(Packages {
+                manager: "emerge".to_string(),
+                count,
+            });
+        }
+
         if which("rpm") {
             if let Ok(out) = Command::new("rpm")
                 .args(["-qa", "--qf", "%{NAME}\n"])
@@ -826,8 +5247,10 @@ fn detect_packages_fast() -> (u32, String) {
                     .filter(|l| !l.trim().is_empty())
                     .count() as u32;
                 if count > 0 {
-                    total += count;
-                    labels.push(format!("{} (rpm)", count));
+                    packages.push(Packages {
+                        manager: "rpm".to_string(),
+                        count,
+                    });
                 }
             }
         }
@@ -842,8 +5265,10 @@ fn detect_packages_fast() -> (u32, String) {
                     .filter(|l| !l.trim().is_empty())
                     .count() as u32;
                 if count > 0 {
-                    total += count;
-                    labels.push(format!("{} (flatpak)", count));
+                    packages.push(Packages {
+                        manager: "flatpak".to_string(),
+                        count,
+                    });
                 }
             }
         }
@@ -856,8 +5281,10 @@ fn detect_packages_fast() -> (u32, String) {
                     .filter(|l| !l.trim().is_empty())
                     .count() as u32;
                 if count > 0 {
-                    total += count;
-                    labels.push(format!("{} (snap)", count));
+                    packages.push(Packages {
+                        manager: "snap".to_string(),
+                        count,
+                    });
                 }
             }
         }
@@ -878,8 +5305,10 @@ fn detect_packages_fast() -> (u32, String) {
             }
         }
         if count > 0 {
-            total += count;
-            labels.push(format!("{} (brew)", count));
+            packages.push(Packages {
+                manager: "brew".to_string(),
+                count,
+            });
         }
 
         let mut casks = 0u32;
@@ -895,8 +5324,10 @@ fn detect_packages_fast() -> (u32, String) {
             }
         }
         if casks > 0 {
-            total += casks;
-            labels.push(format!("{} (casks)", casks));
+            packages.push(Packages {
+                manager: "casks".to_string(),
+                count: casks,
+            });
         }
     }
 
@@ -908,36 +5339,934 @@ fn detect_packages_fast() -> (u32, String) {
                 if let Ok(rd) = read_dir(p) {
                     let count = rd.flatten().filter(|e| e.path().is_dir()).count() as u32;
                     if count > 0 {
-                        total += count;
-                        labels.push(format!("{} (scoop)", count));
+                        packages.push(Packages {
+                            manager: "scoop".to_string(),
+                            count,
+                        });
                     }
                 }
             }
         }
     }
 
-    if total == 0 {
-        return (0u32, String::new());
+    packages
+}
+
+/// How long a pending update count is trusted before checking again.
+#[cfg(all(target_os = "linux", feature = "network-fields"))]
+const UPDATES_TTL: u64 = 6 * 60 * 60;
+
+/// How long one update check may take before it's given up on.
+#[cfg(all(target_os = "linux", feature = "network-fields"))]
+const UPDATES_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Pending updates for each package manager that can report them without
+/// root. Counts are cached for `UPDATES_TTL`, since checking usually means
+/// refreshing the package lists over the network; so are failed checks,
+/// so an offline machine doesn't wait out `UPDATES_TIMEOUT` on every run.
+/// Never checked without the `network-fields` feature.
+pub fn detect_updates() -> Vec<Updates> {
+    #[cfg(all(target_os = "linux", feature = "network-fields"))]
+    {
+        // (manager, command, args)
+        const CHECKS: &[(&str, &str, &[&str])] = &[
+            ("pacman", "checkupdates", &[]),
+            ("apt", "apt", &["list", "--upgradable"]),
+            ("dnf", "dnf", &["check-update", "-q"]),
+        ];
+
+        // pacman can't check without root; checkupdates can.
+        if which("pacman") && !which("checkupdates") {
+            missing_tool("checkupdates (pacman-contrib)");
+        }
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        let handles: Vec<_> = CHECKS
+            .iter()
+            .filter(|(_, cmd, _)| which(cmd))
+            .map(|&(manager, cmd, args)| {
+                thread::spawn(move || {
+                    let key = format!("updates.{}", manager);
+                    let cached = crate::cache::read_stamped(&key)
+                        .filter(|(checked, _)| now.saturating_sub(*checked) < UPDATES_TTL);
+                    if let Some((_, count)) = cached {
+                        // Empty for a check that failed or timed out.
+                        return Some((manager, count.parse().ok()?));
+                    }
+
+                    let Some(output) = output_within(cmd, args, UPDATES_TIMEOUT) else {
+                        crate::cache::write_stamped(&key, now, "");
+                        return None;
+                    };
+                    let count = match manager {
+                        // "Listing..." comes first.
+                        "apt" => output.lines().filter(|l| l.contains("[upgradable")).count(),
+                        // name, version and repo; a blank line and any
+                        // "Obsoleting Packages" section follow.
+                        "dnf" => output
+                            .lines()
+                            .take_while(|l| !l.starts_with("Obsoleting"))
+                            .filter(|l| l.split_whitespace().count() == 3)
+                            .count(),
+                        _ => output.lines().filter(|l| !l.trim().is_empty()).count(),
+                    } as u32;
+                    crate::cache::write_pkg_count(&key, now, count);
+                    Some((manager, count))
+                })
+            })
+            .collect();
+
+        return handles
+            .into_iter()
+            .filter_map(|h| h.join().ok().flatten())
+            .map(|(manager, count)| Updates {
+                manager: manager.to_string(),
+                count,
+            })
+            .collect();
     }
-    (total, labels.join(", "))
+
+    Vec::new()
 }
 
-#[cfg(not(target_os = "windows"))]
-fn which(cmd: &str) -> bool {
-    if let Ok(paths) = env::var("PATH") {
-        for path in env::split_paths(&paths) {
-            let full_path = path.join(cmd);
-            if full_path.is_file() {
-                return true;
+/// How long the kernel.org release list is trusted before fetching it
+/// again. Failed fetches count too, so offline runs don't keep trying.
+#[cfg(all(target_os = "linux", feature = "network-fields"))]
+const KERNEL_FEED_TTL: u64 = 24 * 60 * 60;
+
+/// Compares a Linux kernel release like "6.9.3-arch1-1" with the
+/// maintained releases on kernel.org: "up to date" when it's at least the
+/// newest release of its own stable or longterm branch (or of the newest
+/// stable, for branches that are no longer maintained), "newer available"
+/// otherwise. Empty when the release can't be parsed or no list has ever
+/// been fetched, and "check disabled" when built without the
+/// `network-fields` feature.
+pub fn detect_kernel_status(kernel: &str) -> String {
+    #[cfg(all(target_os = "linux", not(feature = "network-fields")))]
+    {
+        let _ = kernel;
+        return "check disabled".to_string();
+    }
+
+    #[cfg(all(target_os = "linux", feature = "network-fields"))]
+    {
+        let Some(running) = kernel_version(kernel) else {
+            return String::new();
+        };
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let cached = crate::cache::read_stamped("kernel.org");
+        let releases = match cached {
+            Some((checked, releases)) if now.saturating_sub(checked) < KERNEL_FEED_TTL => releases,
+            _ => {
+                let releases = fetch_kernel_releases()
+                    .or_else(|| cached.map(|(_, releases)| releases))
+                    .unwrap_or_default();
+                crate::cache::write_stamped("kernel.org", now, &releases);
+                releases
             }
-            #[cfg(windows)]
-            {
-                let exe_path = full_path.with_extension("exe");
-                if exe_path.is_file() {
-                    return true;
+        };
+
+        // Newest stable first, then the longterm branches.
+        let releases: Vec<Vec<u32>> = releases.split(' ').filter_map(kernel_version).collect();
+        let Some(latest) = releases
+            .iter()
+            .find(|release| release[..2] == running[..2])
+            .or(releases.first())
+        else {
+            return String::new();
+        };
+        return if running >= *latest {
+            "up to date".to_string()
+        } else {
+            "newer available".to_string()
+        };
+    }
+
+    #[allow(unreachable_code)]
+    {
+        let _ = kernel;
+        String::new()
+    }
+}
+
+/// The numeric part of a kernel release, padded to major.minor.patch.
+#[cfg(all(target_os = "linux", feature = "network-fields"))]
+fn kernel_version(release: &str) -> Option<Vec<u32>> {
+    let numeric = release
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .next()?;
+    let mut version: Vec<u32> = numeric
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .take(3)
+        .collect();
+    if version.len() < 2 {
+        return None;
+    }
+    version.resize(3, 0);
+    Some(version)
+}
+
+/// The stable and longterm versions from kernel.org's releases.json, space
+/// separated with the newest stable first. Gives up after a few seconds,
+/// and sooner when kernel.org can't be reached at all.
+#[cfg(all(target_os = "linux", feature = "network-fields"))]
+fn fetch_kernel_releases() -> Option<String> {
+    if !which("curl") {
+        missing_tool("curl");
+        return None;
+    }
+    let args = [
+        "-fsS",
+        "--connect-timeout",
+        "1",
+        "--max-time",
+        "3",
+        "https://www.kernel.org/releases.json",
+    ];
+    let body = output_within("curl", &args, std::time::Duration::from_secs(4))?;
+    let feed: serde_json::Value = serde_json::from_str(&body).ok()?;
+
+    let mut versions = vec![feed["latest_stable"]["version"].as_str()?.to_string()];
+    for release in feed["releases"].as_array()? {
+        if release["moniker"] == "longterm" || release["moniker"] == "stable" {
+            if let Some(version) = release["version"].as_str() {
+                if !versions.iter().any(|v| v == version) {
+                    versions.push(version.to_string());
                 }
             }
         }
     }
-    false
+    Some(versions.join(" "))
+}
+
+/// Tools an enabled field needed and didn't find, for the hint printed
+/// after the output.
+static MISSING_TOOLS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// Notes that `tool` would have filled in a field that's left empty. Each
+/// tool is noted once however many probes ask for it.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn missing_tool(tool: &'static str) {
+    let mut missing = MISSING_TOOLS.lock().unwrap_or_else(|e| e.into_inner());
+    if !missing.contains(&tool) {
+        missing.push(tool);
+    }
+}
+
+/// The tools noted by `missing_tool` so far, in the order they were missed.
+pub fn missing_tools() -> Vec<&'static str> {
+    MISSING_TOOLS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Which of its backends each multi-backend probe last answered from,
+/// for `uwufetch doctor`.
+static BACKENDS: Mutex<Vec<(&'static str, &'static str)>> = Mutex::new(Vec::new());
+
+/// Notes that `field` was filled in by `name`, replacing whatever an
+/// earlier run of the same probe noted.
+fn backend(field: &'static str, name: &'static str) {
+    let mut backends = BACKENDS.lock().unwrap_or_else(|e| e.into_inner());
+    match backends.iter_mut().find(|(f, _)| *f == field) {
+        Some(entry) => entry.1 = name,
+        None => backends.push((field, name)),
+    }
+}
+
+/// The backends noted by `backend` so far, as `(field, backend)`.
+pub fn backends() -> Vec<(&'static str, &'static str)> {
+    BACKENDS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Runs `cmd` and returns its stdout, or `None` if it can't be started
+/// or is still running after `timeout`, in which case it's killed. The
+/// exit status is ignored: update checkers use it to say "updates found".
+fn output_within(cmd: &str, args: &[&str], timeout: std::time::Duration) -> Option<String> {
+    status_within(cmd, args, timeout).map(|(_, output)| output)
+}
+
+/// `output_within` along with the exit status, for commands whose empty
+/// output means something only when they succeeded.
+fn status_within(
+    cmd: &str,
+    args: &[&str],
+    timeout: std::time::Duration,
+) -> Option<(std::process::ExitStatus, String)> {
+    let mut command = Command::new(cmd);
+    command.args(args);
+    command_within(command, timeout)
+}
+
+/// `status_within` for a command set up by the caller, as with its own
+/// environment.
+fn command_within(
+    mut command: Command,
+    timeout: std::time::Duration,
+) -> Option<(std::process::ExitStatus, String)> {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdout = child.stdout.take()?;
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stdout.read_to_string(&mut output);
+        output
+    });
+
+    let deadline = std::time::Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if std::time::Instant::now() < deadline => {
+                thread::sleep(std::time::Duration::from_millis(20));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+    reader.join().ok().map(|output| (status, output))
+}
+
+/// Counts the installed packages in a dpkg status database. The file
+/// runs to several megabytes on a desktop install, so it's scanned a line
+/// at a time as raw bytes rather than read into a `String` first.
+pub fn dpkg_installed(mut status: impl io::BufRead) -> u32 {
+    let mut count = 0;
+    let mut line = Vec::with_capacity(256);
+    while status.read_until(b'\n', &mut line).unwrap_or(0) > 0 {
+        if line.trim_ascii_end() == b"Status: install ok installed" {
+            count += 1;
+        }
+        line.clear();
+    }
+    count
+}
+
+/// Installed portage packages, one directory each under
+/// `/var/db/pkg/<category>`. Categories are counted in parallel, and the
+/// total is cached against the newest mtime among them, which changes
+/// whenever a package is merged or unmerged.
+#[cfg(target_os = "linux")]
+fn portage_packages() -> Option<u32> {
+    let db = Path::new("/var/db/pkg");
+    let categories: Vec<_> = read_dir(db)
+        .ok()?
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.path())
+        .collect();
+
+    let stamp = categories
+        .iter()
+        .map(|path| path.as_path())
+        .chain([db])
+        .filter_map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .filter_map(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|since| since.as_nanos() as u64)
+        .max()?;
+    if let Some((_, count)) =
+        crate::cache::read_pkg_count("portage").filter(|&(stored, _)| stored == stamp)
+    {
+        return Some(count);
+    }
+
+    let threads = thread::available_parallelism().map_or(4, |n| n.get());
+    let chunk = categories.len().div_ceil(threads).max(1);
+    let count = thread::scope(|s| {
+        let handles: Vec<_> = categories
+            .chunks(chunk)
+            .map(|chunk| {
+                s.spawn(move || {
+                    chunk
+                        .iter()
+                        .filter_map(|category| read_dir(category).ok())
+                        .flat_map(|rd| rd.flatten())
+                        // -MERGING- directories are packages mid-install.
+                        .filter(|e| !e.file_name().to_string_lossy().starts_with('-'))
+                        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+                        .count() as u32
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap_or(0)).sum()
+    });
+
+    crate::cache::write_pkg_count("portage", stamp, count);
+    Some(count)
+}
+
+/// The running kernel's build config, from /proc/config.gz or the copy
+/// distros install in /boot. Empty when neither is there.
+#[cfg(target_os = "linux")]
+fn kernel_config() -> Vec<String> {
+    use std::io::Read;
+
+    let mut text = String::new();
+    let gz = fs::File::open("/proc/config.gz")
+        .ok()
+        .and_then(|file| libflate::gzip::Decoder::new(file).ok());
+    if let Some(mut decoder) = gz {
+        if decoder.read_to_string(&mut text).is_err() {
+            text.clear();
+        }
+    }
+    if text.is_empty() {
+        let release = fs::read_to_string("/proc/sys/kernel/osrelease").unwrap_or_default();
+        text = fs::read_to_string(format!("/boot/config-{}", release.trim())).unwrap_or_default();
+    }
+
+    text.lines()
+        .filter(|line| line.starts_with("CONFIG_"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// The mode a PREEMPT_DYNAMIC kernel runs in: `preempt=` on the command
+/// line, else the active one in debugfs, shown there as "none (full)".
+#[cfg(target_os = "linux")]
+fn dynamic_preempt_mode() -> Option<String> {
+    let cmdline = fs::read_to_string("/proc/cmdline").unwrap_or_default();
+    if let Some(mode) = cmdline
+        .split_whitespace()
+        .find_map(|param| param.strip_prefix("preempt="))
+    {
+        return Some(mode.to_string());
+    }
+
+    let modes = fs::read_to_string("/sys/kernel/debug/sched/preempt").ok()?;
+    let start = modes.find('(')? + 1;
+    let end = start + modes[start..].find(')')?;
+    Some(modes[start..end].to_string())
+}
+
+/// An EFI variable holding a UTF-16 string, after its 4 attribute bytes.
+#[cfg(target_os = "linux")]
+fn efi_string(name: &str) -> Option<String> {
+    let data = fs::read(format!("/sys/firmware/efi/efivars/{}", name)).ok()?;
+    let text = utf16_until_nul(data.get(4..)?);
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(target_os = "linux")]
+fn utf16_until_nul(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// The description of the firmware boot entry we were started from, from
+/// BootCurrent and its Boot#### variable, or from efibootmgr when the
+/// variables can't be read.
+#[cfg(target_os = "linux")]
+fn current_boot_entry() -> Option<String> {
+    const GLOBAL: &str = "8be4df61-93ca-11d2-aa0d-00e098032b8c";
+    let efivars = Path::new("/sys/firmware/efi/efivars");
+    if !Path::new("/sys/firmware/efi").exists() {
+        return None;
+    }
+
+    let current = fs::read(efivars.join(format!("BootCurrent-{}", GLOBAL)))
+        .ok()
+        .and_then(|data| Some(u16::from_le_bytes([*data.get(4)?, *data.get(5)?])));
+    if let Some(current) = current {
+        // EFI_LOAD_OPTION: u32 attributes, u16 path length, then the
+        // description.
+        let entry = fs::read(efivars.join(format!("Boot{:04X}-{}", current, GLOBAL))).ok();
+        if let Some(description) = entry.and_then(|data| Some(utf16_until_nul(data.get(10..)?))) {
+            return Some(description);
+        }
+    }
+
+    let output = Command::new("efibootmgr").output().ok()?;
+    let output = String::from_utf8_lossy(&output.stdout);
+    let current = output
+        .lines()
+        .find_map(|line| line.strip_prefix("BootCurrent:"))?
+        .trim()
+        .to_string();
+    output.lines().find_map(|line| {
+        let rest = line.strip_prefix("Boot")?.strip_prefix(current.as_str())?;
+        let description = rest.trim_start_matches('*').trim();
+        Some(
+            description
+                .split('\t')
+                .next()
+                .unwrap_or(description)
+                .to_string(),
+        )
+    })
+}
+
+/// Names a loader from a firmware boot entry's description. Entries named
+/// after the distro ("ubuntu", "Fedora") don't say, so they give `None`.
+#[cfg(target_os = "linux")]
+fn bootloader_name(description: &str) -> Option<&'static str> {
+    let description = description.to_lowercase();
+    [
+        ("linux boot manager", "systemd-boot"),
+        ("systemd-boot", "systemd-boot"),
+        ("grub", "GRUB"),
+        ("refind", "rEFInd"),
+        ("limine", "Limine"),
+        ("clover", "Clover"),
+        ("opencore", "OpenCore"),
+        ("zfsbootmenu", "ZFSBootMenu"),
+    ]
+    .iter()
+    .find(|(key, _)| description.contains(key))
+    .map(|(_, name)| *name)
+}
+
+/// Seconds since boot, from the tick counter.
+#[cfg(target_os = "windows")]
+pub(crate) fn windows_uptime() -> u64 {
+    unsafe { windows::Win32::System::SystemInformation::GetTickCount64() / 1000 }
+}
+
+#[cfg(target_os = "windows")]
+fn windows_user() -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::System::WindowsProgramming::GetUserNameW;
+
+    let mut buffer = [0u16; 257];
+    let mut len = buffer.len() as u32;
+    unsafe { GetUserNameW(Some(PWSTR(buffer.as_mut_ptr())), &mut len) }.ok()?;
+    // len comes back including the terminating NUL.
+    let end = (len as usize).saturating_sub(1).min(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..end]))
+}
+
+/// The machine's DNS host name, which is what `hostname` prints, rather
+/// than the upper-cased NetBIOS name.
+#[cfg(target_os = "windows")]
+fn windows_hostname() -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::System::SystemInformation::{
+        ComputerNamePhysicalDnsHostname, GetComputerNameExW,
+    };
+
+    let mut buffer = [0u16; 256];
+    let mut len = buffer.len() as u32;
+    unsafe {
+        GetComputerNameExW(
+            ComputerNamePhysicalDnsHostname,
+            Some(PWSTR(buffer.as_mut_ptr())),
+            &mut len,
+        )
+    }
+    .ok()?;
+    // Here len excludes the NUL.
+    let end = (len as usize).min(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..end]))
+}
+
+#[cfg(target_os = "windows")]
+fn windows_build() -> Option<u32> {
+    registry_string(
+        "SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion",
+        "CurrentBuildNumber",
+    )?
+    .parse()
+    .ok()
+}
+
+/// Reads a REG_SZ value under HKEY_LOCAL_MACHINE. Without the
+/// `windows-registry` feature every lookup misses and callers fall back to
+/// wmic.
+#[cfg(all(target_os = "windows", feature = "windows-registry"))]
+fn registry_string(key_path: &str, value: &str) -> Option<String> {
+    use windows::core::HSTRING;
+    use windows::Win32::System::Registry::*;
+
+    unsafe {
+        let mut hkey = HKEY::default();
+        if RegOpenKeyExW(
+            HKEY_LOCAL_MACHINE,
+            &HSTRING::from(key_path),
+            Some(0),
+            KEY_READ,
+            &mut hkey,
+        )
+        .is_err()
+        {
+            return None;
+        }
+
+        let mut buffer = [0u16; 256];
+        let mut size = (buffer.len() * 2) as u32;
+        let result = RegQueryValueExW(
+            hkey,
+            &HSTRING::from(value),
+            None,
+            None,
+            Some(buffer.as_mut_ptr() as *mut u8),
+            Some(&mut size),
+        );
+        let _ = RegCloseKey(hkey);
+        if result.is_err() {
+            return None;
+        }
+
+        let len = (size as usize / 2).min(buffer.len());
+        let value = String::from_utf16_lossy(&buffer[..len]);
+        let value = value.trim_end_matches('\0').trim();
+        (!value.is_empty()).then(|| value.to_string())
+    }
+}
+
+#[cfg(all(target_os = "windows", not(feature = "windows-registry")))]
+fn registry_string(_key_path: &str, _value: &str) -> Option<String> {
+    None
+}
+
+/// The free-form machine name set with `hostnamectl set-hostname --pretty`
+/// or in the macOS sharing settings ("Ana's MacBook Pro").
+fn pretty_hostname() -> Option<String> {
+    #[cfg(target_os = "macos")]
+    {
+        use core_foundation::base::TCFType;
+        use core_foundation::string::{CFString, CFStringRef};
+        use std::ffi::c_void;
+
+        #[link(name = "SystemConfiguration", kind = "framework")]
+        extern "C" {
+            fn SCDynamicStoreCopyComputerName(
+                store: *const c_void,
+                encoding: *mut u32,
+            ) -> CFStringRef;
+        }
+
+        let name =
+            unsafe { SCDynamicStoreCopyComputerName(std::ptr::null(), std::ptr::null_mut()) };
+        if name.is_null() {
+            return None;
+        }
+        let name = unsafe { CFString::wrap_under_create_rule(name) }.to_string();
+        Some(name).filter(|name| !name.is_empty())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let content = fs::read_to_string("/etc/machine-info").ok()?;
+        let value = content
+            .lines()
+            .find_map(|line| line.strip_prefix("PRETTY_HOSTNAME="))?
+            .trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+
+        let mut name = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                name.extend(chars.next());
+            } else {
+                name.push(c);
+            }
+        }
+        Some(name).filter(|name| !name.is_empty())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn which(cmd: &str) -> bool {
+    tool_path(cmd).is_some()
+}
+
+/// Where `cmd` would be run from, searching $PATH like a shell does.
+pub fn tool_path(cmd: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    for path in env::split_paths(&paths) {
+        let full_path = path.join(cmd);
+        if full_path.is_file() {
+            return Some(full_path);
+        }
+        #[cfg(windows)]
+        {
+            let exe_path = full_path.with_extension("exe");
+            if exe_path.is_file() {
+                return Some(exe_path);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Field, FieldSet, SystemInfo};
+
+    #[test]
+    fn collect_detects_only_the_fields_asked_for() {
+        let info = SystemInfo::collect(FieldSet::new().with(Field::Memory));
+        assert!(info.ram_total > 0);
+        assert!(info.os_name.is_empty());
+        assert!(info.gpu_models.is_empty());
+        assert!(info.packages.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parse_passwd_finds_the_uid() {
+        use super::{parse_passwd, Passwd};
+
+        let passwd = "root:x:0:0:root:/root:/bin/bash\n\
+                      # a comment:::\n\
+                      broken:x:1000\n\
+                      kim:x:1000:1000:Kim,,,:/home/kim:/usr/bin/fish\n";
+        assert_eq!(
+            parse_passwd(passwd, 1000),
+            Some(Passwd {
+                name: "kim".to_string(),
+                home: "/home/kim".to_string(),
+                shell: "/usr/bin/fish".to_string(),
+            })
+        );
+        assert_eq!(parse_passwd(passwd, 0).unwrap().home, "/root");
+        assert_eq!(parse_passwd(passwd, 1001), None);
+        assert_eq!(parse_passwd("", 0), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn wayland_outputs_from_a_compositor() {
+        use super::{wayland_message, wayland_query_outputs, wayland_string_arg, Display};
+        use std::io::{Read, Write};
+        use std::os::unix::net::UnixStream;
+
+        fn global(out: &mut Vec<u8>, name: u32, interface: &str, version: u32) {
+            let mut args = name.to_ne_bytes().to_vec();
+            wayland_string_arg(&mut args, interface);
+            args.extend(version.to_ne_bytes());
+            wayland_message(out, 2, 0, &args);
+        }
+        fn words(words: &[u32]) -> Vec<u8> {
+            words.iter().flat_map(|w| w.to_ne_bytes()).collect()
+        }
+        fn geometry(out: &mut Vec<u8>, output: u32, transform: u32) {
+            let mut args = words(&[0, 0, 600, 340, 0]);
+            wayland_string_arg(&mut args, "Foo");
+            wayland_string_arg(&mut args, "Bar Display");
+            args.extend(transform.to_ne_bytes());
+            wayland_message(out, output, 0, &args);
+        }
+
+        let (mut client, mut compositor) = UnixStream::pair().unwrap();
+        let server = std::thread::spawn(move || {
+            // get_registry and sync
+            let mut request = [0; 24];
+            compositor.read_exact(&mut request).unwrap();
+            let mut events = Vec::new();
+            // Interface names of every length mod 4, to exercise padding.
+            global(&mut events, 1, "wl_compositor", 4);
+            global(&mut events, 2, "wl_output", 4);
+            global(&mut events, 3, "wl_seat", 7);
+            global(&mut events, 4, "wl_output", 3);
+            global(&mut events, 5, "xdg_wm_base", 2);
+            wayland_message(&mut events, 3, 0, &words(&[1]));
+            compositor.write_all(&events).unwrap();
+
+            // Two binds of 36 bytes and a sync.
+            let mut request = [0; 84];
+            compositor.read_exact(&mut request).unwrap();
+            let mut events = Vec::new();
+            geometry(&mut events, 5, 0);
+            wayland_message(&mut events, 5, 1, &words(&[2, 1920, 1080, 60000]));
+            wayland_message(&mut events, 5, 1, &words(&[3, 2560, 1440, 143912]));
+            wayland_message(&mut events, 5, 3, &words(&[2]));
+            // The second output never sends its scale.
+            geometry(&mut events, 6, 1);
+            wayland_message(&mut events, 6, 1, &words(&[1, 1920, 1080, 60000]));
+            wayland_message(&mut events, 9, 1, &words(&[1, 640, 480, 60000]));
+            // A mode event cut off partway, then the connection closes.
+            let mut cut = Vec::new();
+            wayland_message(&mut cut, 5, 1, &words(&[1, 800, 600, 60000]));
+            events.extend(&cut[..12]);
+            compositor.write_all(&events).unwrap();
+            request
+        });
+
+        let outputs = wayland_query_outputs(&mut client);
+        let binds = server.join().unwrap();
+        let word = |at: usize| u32::from_ne_bytes(binds[at..at + 4].try_into().unwrap());
+        // bind(name, "wl_output", version, id), the version capped at 2.
+        assert_eq!([word(8), word(28), word(32)], [2, 2, 5]);
+        assert_eq!([word(44), word(64), word(68)], [4, 2, 6]);
+        assert_eq!(
+            outputs,
+            [
+                (
+                    Display {
+                        width: 2560,
+                        height: 1440,
+                        refresh: 143912,
+                    },
+                    2
+                ),
+                (
+                    Display {
+                        width: 1080,
+                        height: 1920,
+                        refresh: 60000,
+                    },
+                    1
+                ),
+            ]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn wayland_string_reads_padded_and_rejects_truncated() {
+        use super::{wayland_string, wayland_string_arg};
+
+        let mut args = 7u32.to_ne_bytes().to_vec();
+        wayland_string_arg(&mut args, "wl_output");
+        assert_eq!(args.len(), 4 + 4 + 12);
+        assert_eq!(wayland_string(&args, 4).as_deref(), Some("wl_output"));
+        assert_eq!(wayland_string(&args[..12], 4), None);
+        assert_eq!(wayland_string(&args, 20), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    fn edid(name: Option<&str>) -> Vec<u8> {
+        let mut edid = vec![0u8; 128];
+        edid[..8].copy_from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+        // "DEL", product 0xA0C3
+        edid[8..12].copy_from_slice(&[0x10, 0xac, 0xc3, 0xa0]);
+        // The preferred timing, 2560x1440, then a second one.
+        edid[54..62].copy_from_slice(&[0x56, 0x5e, 0x00, 0x00, 0xa0, 0xa0, 0x00, 0x50]);
+        edid[72..80].copy_from_slice(&[0x1a, 0x36, 0x80, 0x00, 0x70, 0x38, 0x00, 0x40]);
+        if let Some(name) = name {
+            edid[90..95].copy_from_slice(&[0, 0, 0, 0xfc, 0]);
+            let text = format!("{}\n", name);
+            edid[95..95 + text.len()].copy_from_slice(text.as_bytes());
+        }
+        edid
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parse_edid_reads_size_and_name() {
+        use super::parse_edid;
+
+        let monitor = parse_edid(&edid(Some("DELL U2720Q"))).unwrap();
+        assert_eq!(monitor.name, "DELL U2720Q");
+        assert_eq!((monitor.width, monitor.height), (2560, 1440));
+
+        // No name descriptor: the PNP vendor id and product code.
+        assert_eq!(parse_edid(&edid(None)).unwrap().name, "DEL A0C3");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parse_edid_rejects_short_or_headerless() {
+        use super::parse_edid;
+
+        let edid = edid(None);
+        assert!(parse_edid(&edid[..127]).is_none());
+        let mut broken = edid.clone();
+        broken[0] = 0xff;
+        assert!(parse_edid(&broken).is_none());
+        assert!(parse_edid(&[]).is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    fn utmp_record(kind: i16, line: &str, user: &str, sec: i32) -> Vec<u8> {
+        let mut record = vec![0u8; 384];
+        record[..2].copy_from_slice(&kind.to_ne_bytes());
+        record[8..8 + line.len()].copy_from_slice(line.as_bytes());
+        record[44..44 + user.len()].copy_from_slice(user.as_bytes());
+        record[340..344].copy_from_slice(&sec.to_ne_bytes());
+        record
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn utmp_login_prefers_our_terminal() {
+        use super::utmp_login;
+
+        let utmp = [
+            utmp_record(2, "~", "reboot", 100),
+            utmp_record(7, "tty1", "kim", 300),
+            utmp_record(7, "pts/0", "kim", 200),
+            utmp_record(7, "pts/1", "root", 50),
+            // A logout leaves a DEAD_PROCESS record behind.
+            utmp_record(8, "pts/2", "kim", 10),
+            utmp_record(7, "pts/3", "kim", 400),
+        ]
+        .concat();
+        assert_eq!(utmp_login(&utmp, "kim", Some("pts/3")), Some(400));
+        // Elsewhere, the earliest login.
+        assert_eq!(utmp_login(&utmp, "kim", Some("pts/9")), Some(200));
+        assert_eq!(utmp_login(&utmp, "kim", None), Some(200));
+        assert_eq!(utmp_login(&utmp, "kimberly", None), None);
+        // A torn last record is ignored.
+        assert_eq!(
+            utmp_login(&utmp[..384 * 6 - 1], "kim", Some("pts/3")),
+            Some(200)
+        );
+        assert_eq!(utmp_login(&[], "kim", None), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parse_cpu_list_expands_ranges() {
+        use super::parse_cpu_list;
+
+        assert_eq!(parse_cpu_list("0-3,8-9\n"), [0, 1, 2, 3, 8, 9]);
+        assert_eq!(parse_cpu_list("0,2,4"), [0, 2, 4]);
+        assert_eq!(parse_cpu_list("5"), [5]);
+        // An empty list, as for a node with no CPUs, and junk.
+        assert!(parse_cpu_list("\n").is_empty());
+        assert_eq!(parse_cpu_list("0-1,x,3-2,4-y,6"), [0, 1, 6]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn lid_state_reads_acpi_state() {
+        use super::lid_state;
+
+        assert_eq!(lid_state("state:      open\n"), Some("open"));
+        assert_eq!(lid_state("state:      closed\n"), Some("closed"));
+        assert_eq!(lid_state("state:      unsupported\n"), None);
+        assert_eq!(lid_state(""), None);
+    }
+
+    #[test]
+    fn battery_and_lid_follow_the_chassis() {
+        use super::Chassis;
+        use crate::config::Toggle;
+
+        assert!(Toggle::Auto.shows(Chassis::Laptop));
+        assert!(Toggle::Auto.shows(Chassis::Convertible));
+        assert!(!Toggle::Auto.shows(Chassis::Desktop));
+        assert!(!Toggle::Auto.shows(Chassis::Vm));
+        assert!(Toggle::Auto.shows(Chassis::Unknown));
+        assert!(Toggle::On.shows(Chassis::Desktop));
+        assert!(!Toggle::Off.shows(Chassis::Laptop));
+    }
 }
@@ -0,0 +1,13 @@
+pub mod assets;
+pub mod cache;
+pub mod color;
+pub mod config;
+pub mod diff;
+pub mod display;
+pub mod doctor;
+pub mod filter;
+pub mod history;
+pub mod info;
+pub mod remote;
+pub mod theme;
+pub mod uwufy;
@@ -1,12 +1,20 @@
-mod assets;
-mod cache;
-mod config;
-mod display;
-mod info;
-mod uwufy;
-
-use clap::Parser;
-use std::io;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::engine::{
+    ArgValueCandidates, ArgValueCompleter, CompletionCandidate, PathCompleter, ValueCompleter,
+};
+use clap_complete::CompleteEnv;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use uwufetch::{
+    assets, cache, color, config, diff, display, doctor, filter, history, info, remote, theme,
+    uwufy,
+};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -16,37 +24,191 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 #[command(about = "A system information fetcher with uwu", long_about = None)]
 #[command(disable_version_flag = true)]
 struct Args {
-    #[arg(short = 'c', long = "config", help = "Use custom config path")]
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(
+        short = 'c',
+        long = "config",
+        help = "Use this config file instead of searching for one; a directory also supplies ascii/ and images/, and - reads stdin"
+    )]
     config: Option<String>,
 
-    #[arg(short = 'd', long = "distro", help = "Choose the logo to print")]
+    #[arg(
+        short = 'p',
+        long = "profile",
+        help = "Use a named profile from the config"
+    )]
+    profile: Option<String>,
+
+    #[arg(
+        short = 'd',
+        long = "distro",
+        help = "Choose the logo to print",
+        add = ArgValueCandidates::new(logo_candidates)
+    )]
     distro: Option<String>,
 
     #[arg(
         short = 'i',
         long = "image",
-        help = "Print logo as image (requires sixel)"
+        help = "Print logo as image (requires sixel)",
+        add = ArgValueCompleter::new(complete_image)
     )]
     image: Option<String>,
 
+    #[arg(
+        short = 'm',
+        long = "minimal",
+        help = "Only show user@host, OS, kernel, memory and uptime"
+    )]
+    minimal: bool,
+
+    #[arg(
+        long = "all",
+        conflicts_with = "minimal",
+        help = "Show every field uwufetch can detect, regardless of config"
+    )]
+    all: bool,
+
+    #[arg(
+        long = "screenshot-mode",
+        help = "Hide user, host, serials and session names behind placeholders and keep the layout fixed, for sharing screenshots"
+    )]
+    screenshot_mode: bool,
+
     #[arg(short = 'l', long = "list", help = "List all supported distributions")]
     list: bool,
 
+    #[arg(
+        long = "dump-uwu-map",
+        help = "Print the uwufication replacement tables, one table/from/to per line"
+    )]
+    dump_uwu_map: bool,
+
+    #[arg(
+        long = "validate-ascii",
+        value_name = "FILE",
+        help = "Check a logo for unknown tokens and its size against the recommended limits, and preview it"
+    )]
+    validate_ascii: Option<PathBuf>,
+
+    #[arg(
+        long = "json",
+        help = "Print the system info (or --list, --dump-uwu-map) as JSON instead of rendering it"
+    )]
+    json: bool,
+
+    #[arg(
+        long = "stdin-json",
+        help = "Render system info read as JSON from stdin instead of detecting it"
+    )]
+    stdin_json: bool,
+
+    #[arg(
+        long = "hosts",
+        value_delimiter = ',',
+        conflicts_with = "stdin_json",
+        help = "Render several machines at once, from ssh hosts or --json files"
+    )]
+    hosts: Vec<String>,
+
+    #[arg(
+        long = "stacked",
+        requires = "hosts",
+        help = "Stack --hosts vertically instead of side by side"
+    )]
+    stacked: bool,
+
+    #[arg(
+        long = "watch",
+        value_name = "SECONDS",
+        num_args = 0..=1,
+        default_missing_value = "1",
+        conflicts_with_all = ["stdin_json", "json", "hosts", "diff"],
+        help = "Redraw every SECONDS (default 1), reloading the config when it changes or on SIGUSR1"
+    )]
+    watch: Option<u64>,
+
     #[arg(short = 'r', long = "read-cache", help = "Read from cache file")]
     read_cache: bool,
 
     #[arg(short = 'w', long = "write-cache", help = "Write to cache file")]
     write_cache: bool,
 
+    #[arg(
+        long = "diff",
+        help = "Compare current system with the cached snapshot"
+    )]
+    diff: bool,
+
+    #[arg(long = "gen-config", help = "Write a commented default config file")]
+    gen_config: bool,
+
+    #[arg(
+        long = "force",
+        help = "Overwrite an existing config with --gen-config"
+    )]
+    force: bool,
+
+    #[arg(
+        long = "force-tty",
+        help = "Use the full layout even when not on a terminal"
+    )]
+    force_tty: bool,
+
+    #[arg(
+        long = "force-color",
+        help = "Keep colors even when not on a terminal, like CLICOLOR_FORCE=1 (for piping into lolcat)"
+    )]
+    force_color: bool,
+
     #[arg(short = 'V', long = "version", help = "Print version")]
     version: bool,
 }
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    #[command(about = "Show how the system changed across recorded runs")]
+    History,
+    #[command(
+        about = "Run every probe and report what each found, how long it took and which backend answered, along with the tools on PATH, terminal capabilities and the paths in use"
+    )]
+    Doctor,
+}
+
 fn main() -> io::Result<()> {
+    CompleteEnv::with_factory(Args::command).complete();
+
     let args = Args::parse();
 
+    let stdout_color = color::enabled(args.force_color, io::stdout().is_terminal());
+
+    if let Some(Command::History) = args.command {
+        return history::print_history(stdout_color);
+    }
+
+    if let Some(Command::Doctor) = args.command {
+        let (mut config, _, _) = load_config(&args)?;
+        config.color = stdout_color;
+        return doctor::print_doctor(&config, args.config.as_deref());
+    }
+
     if args.list {
-        list_distributions();
+        list_distributions(args.json);
+        return Ok(());
+    }
+
+    if args.dump_uwu_map {
+        dump_uwu_map(args.json);
+        return Ok(());
+    }
+
+    if let Some(path) = &args.validate_ascii {
+        let content = fs::read_to_string(path)?;
+        if !validate_ascii(path, &content, stdout_color) {
+            std::process::exit(1);
+        }
         return Ok(());
     }
 
@@ -55,84 +217,518 @@ fn main() -> io::Result<()> {
         return Ok(());
     }
 
-    let (mut config, distro_override, image_override) = config::Configuration::parse_config();
-    let mut user_info_opt = if args.read_cache {
-        cache::read_cache()
-    } else {
-        None
+    if args.gen_config {
+        let path = match args.config.as_deref() {
+            Some(p) => PathBuf::from(p),
+            None => config::Configuration::user_config_path()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))?,
+        };
+        if let Err(e) = config::Configuration::write_default_config(&path, args.force) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        println!("Wrote default config to {}", path.display());
+        return Ok(());
+    }
+
+    if args.config.as_deref() == Some("-") && (args.stdin_json || args.watch.is_some()) {
+        eprintln!("Error: -c - can't be combined with --stdin-json or --watch");
+        std::process::exit(1);
+    }
+
+    let (mut config, distro_override, image_override) = match load_config(&args) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("Error: couldn't read config {}", e);
+            std::process::exit(1);
+        }
     };
 
-    let cli_distro = args.distro;
-    let cli_image = args.image;
+    if args.diff {
+        let Some(cached) = cache::read_snapshot() else {
+            eprintln!("No cached snapshot found, run `uwufetch -w` first");
+            std::process::exit(1);
+        };
+        let mut current = info::SystemInfo::default();
+        current.populate(&config);
+        return diff::print_diff(&cached, &current, stdout_color);
+    }
 
-    let mut user_info = if let Some(mut info) = user_info_opt.take() {
-        if let Some(d) = cli_distro.clone().or_else(|| distro_override.clone()) {
-            info.os_name = d;
+    if !args.hosts.is_empty() {
+        let mut hosts = Vec::new();
+        for (host, result) in remote::fetch_hosts(&args.hosts) {
+            match result {
+                Ok(mut info) => {
+                    info.os_name = assets::resolve_logo(&info.os_name);
+                    // `--json` applied the filters already, on the machine
+                    // they were written for.
+                    if config.screenshot {
+                        filter::hide_private(&mut info);
+                    }
+                    uwufy::uwufy_all(&mut info);
+                    hosts.push(info);
+                }
+                Err(e) => eprintln!("Could not fetch {}: {}", host, e),
+            }
         }
-        if let Some(img) = cli_image.clone().or_else(|| image_override.clone()) {
-            if !img.is_empty() {
-                info.image_name = Some(img);
-                config.show_image = true;
+        if hosts.is_empty() {
+            std::process::exit(1);
+        }
+        config.color = stdout_color;
+        apply_theme(&mut config);
+        return display::print_hosts(&config, &mut hosts, args.stacked);
+    }
+
+    if let Some(interval) = args.watch {
+        return watch(&args, interval);
+    }
+
+    let loaded = (config, distro_override, image_override);
+    fetch_and_print(&args, loaded.clone(), true)?;
+    if !args.json {
+        print_tool_hint(&loaded.0);
+    }
+    Ok(())
+}
+
+type LoadedConfig = (config::Configuration, Option<String>, Option<String>);
+
+fn load_config(args: &Args) -> io::Result<LoadedConfig> {
+    let (mut config, distro_override, image_override) =
+        config::Configuration::parse_config(args.config.as_deref(), args.profile.as_deref())?;
+    if args.minimal {
+        config.apply_minimal_preset();
+    }
+    if args.all {
+        config.apply_all_preset();
+    }
+    config.screenshot = args.screenshot_mode;
+    Ok((config, distro_override, image_override))
+}
+
+/// Detects, renders and prints once. `record` controls whether the run
+/// touches the cache and history, which watch mode only does on its first
+/// frame.
+fn fetch_and_print(args: &Args, loaded: LoadedConfig, record: bool) -> io::Result<()> {
+    let (mut config, distro_override, image_override) = loaded;
+    let distro = args.distro.clone().or(distro_override);
+    let image = args.image.clone().or(image_override);
+
+    // Slow fields are only filled in live on a fresh run drawn to a
+    // terminal; everything else waits for them as before.
+    let interactive = args.force_tty || io::stdout().is_terminal();
+    config.color = color::enabled(args.force_color, interactive);
+    let progressive = config.progressive
+        && interactive
+        && config.color
+        && !config.plain
+        && !config.screenshot
+        && !args.json;
+    let mut slow = None;
+
+    // With `cache = auto` a fresh enough cache is read as if -r was given,
+    // and a run that had to detect everything writes it as if -w was.
+    // A run showing some other distro's logo mustn't store it as ours.
+    let auto_cache = config.auto_cache && distro.is_none() && !args.stdin_json;
+    let read_cache = args.read_cache
+        || (auto_cache && cache::cache_age().is_some_and(|age| age < config.cache_ttl));
+    let mut from_cache = false;
+
+    let mut user_info = if args.stdin_json {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        match serde_json::from_str::<info::SystemInfo>(&input) {
+            Ok(info) => info,
+            Err(e) => {
+                eprintln!("Invalid system info on stdin: {}", e);
+                std::process::exit(1);
             }
         }
+    } else if let Some(mut info) = read_cache
+        .then(|| cache::read_cache(config.memory_mode))
+        .flatten()
+    {
+        from_cache = true;
+        info.populate_live(&config);
+        // Pending updates have a cache of their own, with a TTL.
+        if config.show_updates {
+            info.updates = info::within(config.probe_timeout("updates"), info::detect_updates)
+                .unwrap_or_default();
+        }
         info
     } else {
         let mut info = info::SystemInfo::default();
-        if let Some(d) = cli_distro.clone().or_else(|| distro_override.clone()) {
-            info.os_name = d;
+        if let Some(d) = &distro {
+            info.os_name = d.clone();
         }
-        if let Some(img) = cli_image.clone().or_else(|| image_override.clone()) {
-            if !img.is_empty() {
-                info.image_name = Some(img);
-                config.show_image = true;
-            }
+        if progressive {
+            slow = Some(info::spawn_slow(&config));
+            info.populate_fast(&config);
+        } else {
+            info.populate(&config);
         }
-        info.populate(&config);
         info
     };
 
-    let has_image_cli = cli_image.is_some();
-    if has_image_cli {
+    let write_cache = args.write_cache || (auto_cache && !from_cache);
+
+    if let Some(d) = distro {
+        user_info.os_name = d;
+    }
+    if let Some(img) = image.filter(|img| !img.is_empty()) {
+        user_info.image_name = Some(img);
+        config.show_image = true;
+    }
+    if args.image.is_some() {
         config.show_image = true;
     }
 
-    if args.write_cache {
-        cache::write_cache(&user_info);
+    if let Some(slow) = slow {
+        apply_theme(&mut config);
+        if config.show_gpu {
+            user_info.gpu_models = vec![display::PENDING.to_string()];
+        }
+        if config.show_pkgs {
+            user_info.pkgman_name = display::PENDING.to_string();
+        }
+
+        let shown = |info: &info::SystemInfo| {
+            let mut shown = info.clone();
+            shown.os_name = assets::resolve_logo(&shown.os_name);
+            filter::apply_filters(&config, &mut shown);
+            uwufy::uwufy_all(&mut shown);
+            shown
+        };
+        let mut frame = display::LiveFrame::print(&config, &mut shown(&user_info))?;
+        for field in slow {
+            user_info.apply_slow(field);
+            frame.redraw(&config, &mut shown(&user_info))?;
+        }
+
+        if record {
+            record_run(&config, &mut user_info, write_cache);
+        }
+        return Ok(());
+    }
+
+    // Info read from stdin describes some other machine, so it must not
+    // touch this one's cache or history.
+    if record && !args.stdin_json {
+        record_run(&config, &mut user_info, write_cache);
+    }
+
+    user_info.os_name = assets::resolve_logo(&user_info.os_name);
+
+    filter::apply_filters(&config, &mut user_info);
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&user_info).unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    if !interactive {
+        config.plain = true;
+        config.show_image = false;
     }
 
+    apply_theme(&mut config);
+
     uwufy::uwufy_all(&mut user_info);
 
-    let lines_printed = if config.show_image {
-        display::print_image(&user_info)?
-    } else {
-        display::print_ascii(&user_info)?
+    display::print(&config, &mut user_info)?;
+    Ok(())
+}
+
+/// Names the tools that enabled fields went without, once, after the
+/// output, so a field left empty for want of one isn't a mystery.
+fn print_tool_hint(config: &config::Configuration) {
+    let missing = info::missing_tools();
+    if config.tool_hints && !missing.is_empty() {
+        eprintln!(
+            "hint: install {} to fill in the fields left empty (tool_hints = false hides this)",
+            missing.join(", ")
+        );
+    }
+}
+
+/// Updates the uptime record, cache and history with a finished fetch.
+fn record_run(config: &config::Configuration, info: &mut info::SystemInfo, write_cache: bool) {
+    // A slow probe that never reported leaves its placeholder behind,
+    // which mustn't be stored as what it found.
+    info.gpu_models.retain(|gpu| gpu != display::PENDING);
+    if info.pkgman_name == display::PENDING {
+        info.pkgman_name.clear();
+    }
+
+    if config.show_uptime_record {
+        info.uptime_record = cache::update_uptime_record(info.uptime);
+    }
+
+    if write_cache {
+        cache::write_cache(info);
+    }
+
+    if config.record_history {
+        history::append_history(info);
+    }
+}
+
+/// Redraws every `interval` seconds. The config, and with it the theme, is
+/// reloaded when its file changes or on SIGUSR1; a config that fails to
+/// load keeps the previous one on screen.
+fn watch(args: &Args, interval: u64) -> io::Result<()> {
+    let reload = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    signal_hook::flag::register(signal_hook::consts::SIGUSR1, Arc::clone(&reload))?;
+
+    let modified = || {
+        config::Configuration::config_file(args.config.as_deref())
+            .and_then(|path| fs::metadata(path).ok())
+            .and_then(|meta| meta.modified().ok())
+    };
+    let load = || -> io::Result<LoadedConfig> {
+        let mut loaded = load_config(args)?;
+        // Ask the terminal once per load rather than on every frame.
+        if loaded.0.theme == config::Theme::Auto {
+            loaded.0.theme = match theme::background_is_light() {
+                Some(true) => config::Theme::Light,
+                _ => config::Theme::Dark,
+            };
+        }
+        Ok(loaded)
     };
 
-    print!("\x1b[{}A", lines_printed);
+    let mut loaded = load()?;
+    let mut stamp = modified();
+    let mut first = true;
+    loop {
+        if reload.swap(false, Ordering::Relaxed) || modified() != stamp {
+            if let Ok(reloaded) = load() {
+                loaded = reloaded;
+            }
+            stamp = modified();
+        }
 
-    display::print_info(&config, &mut user_info)?;
+        {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(b"\x1b[H\x1b[2J")?;
+            stdout.flush()?;
+        }
+        info::reset_probes();
+        fetch_and_print(args, loaded.clone(), first)?;
+        // Once, rather than under every frame.
+        if first {
+            print_tool_hint(&loaded.0);
+        }
+        first = false;
 
-    let move_amount = 9i32 - lines_printed as i32;
-    if move_amount < 0 {
-        print!("\x1b[{}A", -move_amount);
-    } else if move_amount > 0 {
-        print!("\x1b[{}B", move_amount);
+        for _ in 0..interval.max(1) * 10 {
+            if reload.load(Ordering::Relaxed) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
     }
+}
 
-    Ok(())
+fn apply_theme(config: &mut config::Configuration) {
+    config.light = match config.theme {
+        config::Theme::Light => true,
+        config::Theme::Dark => false,
+        config::Theme::Auto => {
+            !config.plain
+                && config.color
+                && !config.screenshot
+                && theme::background_is_light().unwrap_or(false)
+        }
+    };
 }
 
-fn list_distributions() {
+fn list_distributions(json: bool) {
+    let logos = assets::logos();
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&logos).unwrap_or_default()
+        );
+        return;
+    }
+
     println!("uwufetch -d <options>");
     println!("  Available distributions:");
-    println!("    \x1b[34mArch linux \x1b[0mbased:");
-    println!("      \x1b[34march, arcolinux, \x1b[35martix, endeavouros \x1b[32mmanjaro, manjaro-arm, \x1b[34mxerolinux\n");
-    println!("    \x1b[31mDebian/\x1b[33mUbuntu \x1b[0mbased:");
-    println!("      \x1b[31mamogos, debian, deepin, \x1b[32mlinuxmint, neon, \x1b[34mpop, \x1b[31mraspbian \x1b[33mubuntu\n");
-    println!("    \x1b[31mBSD \x1b[0mbased:");
-    println!("      \x1b[31mfreebsd, \x1b[33mopenbsd, \x1b[32mm\x1b[33ma\x1b[31mc\x1b[38;5;201mo\x1b[34ms, \x1b[37mios\n");
-    println!("    \x1b[31mRHEL \x1b[0mbased:");
-    println!("      \x1b[34mfedora, \x1b[32mrocky\n");
-    println!("    \x1b[0mOther/spare distributions:");
-    println!("      \x1b[34malpine, \x1b[38;5;201mfemboyos, gentoo, \x1b[35mslackware, \x1b[37msolus, \x1b[32mvoid, opensuse-leap, android, \x1b[33mgnu, guix, \x1b[34mwindows, \x1b[37munknown\n");
+    for logo in &logos {
+        let mut line = format!("    {}", logo.name);
+        if let Some(uwu_name) = &logo.meta.uwu_name {
+            line.push_str(&format!(" - {}", uwu_name));
+        }
+        if !logo.meta.aliases.is_empty() {
+            line.push_str(&format!(" (aka {})", logo.meta.aliases.join(", ")));
+        }
+        if logo.sixel {
+            line.push_str(" [image]");
+        }
+        println!("{}", line);
+    }
+}
+
+/// Previews a logo and lists what's wrong with it. Sizes over the
+/// recommended limits are warnings; false if there were real problems.
+fn validate_ascii(path: &Path, content: &str, color: bool) -> bool {
+    let report = display::validate_ascii(content);
+
+    let preview = display::render_ascii(content.trim_end_matches('\n'));
+    if color {
+        println!("{}", preview);
+    } else {
+        println!("{}", display::strip_ansi(&preview));
+    }
+    println!();
+    println!(
+        "{}: {} columns by {} lines",
+        path.display(),
+        report.width,
+        report.height
+    );
+    for (line, problem) in &report.problems {
+        println!("line {}: {}", line, problem);
+    }
+    if report.width > display::LOGO_MAX_WIDTH {
+        println!(
+            "warning: wider than the recommended {} columns, it will run into the info",
+            display::LOGO_MAX_WIDTH
+        );
+    }
+    if report.height > display::LOGO_MAX_HEIGHT {
+        println!(
+            "warning: taller than the recommended {} lines",
+            display::LOGO_MAX_HEIGHT
+        );
+    }
+    if report.problems.is_empty() {
+        println!("ok");
+    }
+    report.problems.is_empty()
+}
+
+/// Tab-separated so shell scripts can split it with `cut` or `read`.
+fn dump_uwu_map(json: bool) {
+    let map = uwufy::uwu_map();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&map).unwrap_or_default());
+        return;
+    }
+
+    for (from, to) in &map.name {
+        println!("name\t{}\t{}", from, to);
+    }
+    let tables = [
+        ("kernel", map.kernel),
+        ("hardware", map.hardware),
+        ("pkgman", map.pkgman),
+        ("de", map.de),
+    ];
+    for (table, replacements) in tables {
+        for (from, to) in replacements {
+            println!("{}\t{}\t{}", table, from, to);
+        }
+    }
+}
+
+/// Logo names and aliases from the embedded assets, and the logos in the
+/// ascii/ of a config directory given with `-c`.
+fn logo_candidates() -> Vec<CompletionCandidate> {
+    let mut candidates = Vec::new();
+    for logo in assets::logos() {
+        for alias in &logo.meta.aliases {
+            candidates.push(
+                CompletionCandidate::new(alias)
+                    .help(Some(format!("alias for {}", logo.name).into())),
+            );
+        }
+        candidates.push(CompletionCandidate::new(logo.name));
+    }
+
+    if let Some(entries) =
+        completion_asset_dir().and_then(|dir| std::fs::read_dir(dir.join("ascii")).ok())
+    {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "txt") {
+                if let Some(stem) = path.file_stem() {
+                    candidates.push(
+                        CompletionCandidate::new(stem)
+                            .help(Some(format!("from {}", path.display()).into())),
+                    );
+                }
+            }
+        }
+    }
+    candidates
+}
+
+/// The config directory passed with `-c` on the command line being
+/// completed, if it is one. Completers aren't given the other arguments,
+/// so they're read back from our own.
+fn completion_asset_dir() -> Option<PathBuf> {
+    let args: Vec<String> = std::env::args().collect();
+    let config = args
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(i, arg)| match arg.as_str() {
+            "-c" | "--config" => args.get(i + 1).cloned(),
+            _ => arg
+                .strip_prefix("--config=")
+                .or_else(|| arg.strip_prefix("-c"))
+                .filter(|value| !value.is_empty())
+                .map(str::to_string),
+        })?;
+    let dir = PathBuf::from(config);
+    dir.is_dir().then_some(dir)
+}
+
+/// Image names from the embedded assets, the images/ of a config
+/// directory given with `-c` and the user's image directory, or file
+/// paths once the value looks like one.
+fn complete_image(current: &OsStr) -> Vec<CompletionCandidate> {
+    let prefix = current.to_string_lossy();
+    if prefix.contains(std::path::MAIN_SEPARATOR) || prefix.starts_with(['.', '~']) {
+        return PathCompleter::file().complete(current);
+    }
+
+    let mut names: Vec<String> = assets::logos()
+        .into_iter()
+        .filter(|logo| logo.sixel)
+        .map(|logo| logo.name)
+        .collect();
+
+    #[cfg(feature = "sixel")]
+    for entries in completion_asset_dir()
+        .map(|dir| dir.join("images"))
+        .into_iter()
+        .chain(config::Configuration::user_image_dir())
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+    {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "sixel") {
+                if let Some(stem) = path.file_stem() {
+                    names.push(stem.to_string_lossy().into_owned());
+                }
+            }
+        }
+    }
+
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter(|name| name.starts_with(prefix.as_ref()))
+        .map(CompletionCandidate::new)
+        .collect()
 }
@@ -0,0 +1,48 @@
+use crate::info::SystemInfo;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+
+/// Reads another machine's info for `--hosts`. An existing file is taken as
+/// the output of `uwufetch --json`; anything else is treated as an ssh
+/// destination that has uwufetch installed. Either way the info comes with
+/// that machine's filters already applied.
+pub fn fetch_host(host: &str) -> Result<SystemInfo, String> {
+    let json = if Path::new(host).is_file() {
+        fs::read_to_string(host).map_err(|e| e.to_string())?
+    } else {
+        let output = Command::new("ssh")
+            .args(["-o", "BatchMode=yes", "--", host, "uwufetch", "--json"])
+            .output()
+            .map_err(|e| format!("could not run ssh: {}", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(stderr.trim().to_string());
+        }
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    serde_json::from_str(&json).map_err(|e| format!("invalid system info: {}", e))
+}
+
+/// Fetches every host at once, since each one is mostly waiting on ssh.
+/// Results keep the order the hosts were given in.
+pub fn fetch_hosts(hosts: &[String]) -> Vec<(String, Result<SystemInfo, String>)> {
+    thread::scope(|s| {
+        let handles: Vec<_> = hosts
+            .iter()
+            .map(|host| s.spawn(move || fetch_host(host)))
+            .collect();
+        hosts
+            .iter()
+            .zip(handles)
+            .map(|(host, handle)| {
+                let result = handle
+                    .join()
+                    .unwrap_or_else(|_| Err("fetch panicked".to_string()));
+                (host.clone(), result)
+            })
+            .collect()
+    })
+}
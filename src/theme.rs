@@ -0,0 +1,138 @@
+use std::env;
+#[cfg(unix)]
+use std::fs::{File, OpenOptions};
+#[cfg(unix)]
+use std::io::{Read, Write};
+#[cfg(unix)]
+use std::process::Command;
+
+/// Whether the terminal has a light background, from COLORFGBG or by
+/// asking the terminal for its background color (OSC 11). `None` when
+/// neither says.
+pub fn background_is_light() -> Option<bool> {
+    // rxvt and Konsole export "fg;bg" as palette indexes.
+    if let Ok(colorfgbg) = env::var("COLORFGBG") {
+        if let Some(bg) = colorfgbg
+            .rsplit(';')
+            .next()
+            .and_then(|bg| bg.parse::<u8>().ok())
+        {
+            return Some(matches!(bg, 7 | 9..=15));
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        let tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .ok()?;
+        let saved = stty(&tty, &["-g"])?;
+        stty(&tty, &["raw", "-echo", "min", "0", "time", "1"])?;
+        let response = query_background(&tty);
+        let _ = stty(&tty, &[saved.trim()]);
+        return parse_osc11(&response?);
+    }
+
+    #[allow(unreachable_code)]
+    None
+}
+
+/// Columns in the terminal, from COLUMNS or `stty size`.
+pub fn terminal_width() -> Option<usize> {
+    if let Some(columns) = env::var("COLUMNS").ok().and_then(|c| c.parse().ok()) {
+        return Some(columns);
+    }
+
+    #[cfg(unix)]
+    {
+        let tty = File::open("/dev/tty").ok()?;
+        let size = stty(&tty, &["size"])?;
+        return size.split_whitespace().nth(1)?.parse().ok();
+    }
+
+    #[allow(unreachable_code)]
+    None
+}
+
+#[cfg(unix)]
+fn stty(tty: &File, args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(tty.try_clone().ok()?)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Sends OSC 11 followed by a primary device attributes request. Every
+/// terminal answers the latter, so its reply ends the wait early on
+/// terminals that ignore OSC 11; otherwise reads stop after 0.1s of quiet.
+#[cfg(unix)]
+fn query_background(mut tty: &File) -> Option<String> {
+    tty.write_all(b"\x1b]11;?\x07\x1b[c").ok()?;
+
+    let mut response = Vec::new();
+    let mut buf = [0u8; 64];
+    loop {
+        let n = tty.read(&mut buf).ok()?;
+        if n == 0 {
+            break;
+        }
+        response.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&response);
+        if text
+            .rfind("\x1b[?")
+            .is_some_and(|da| text[da..].ends_with('c'))
+        {
+            break;
+        }
+    }
+    Some(String::from_utf8_lossy(&response).into_owned())
+}
+
+/// Reads `ESC ] 11 ; rgb:RRRR/GGGG/BBBB` and judges its luminance.
+#[cfg(unix)]
+fn parse_osc11(response: &str) -> Option<bool> {
+    let rgb = &response[response.find("rgb:")? + 4..];
+    let end = rgb.find(['\x07', '\x1b']).unwrap_or(rgb.len());
+    let channels: Vec<f32> = rgb[..end]
+        .split('/')
+        .map(|hex| {
+            let max = 16f32.powi(hex.len() as i32) - 1.0;
+            u32::from_str_radix(hex, 16).ok().map(|v| v as f32 / max)
+        })
+        .collect::<Option<_>>()?;
+    let [r, g, b] = channels[..] else {
+        return None;
+    };
+    Some(0.2126 * r + 0.7152 * g + 0.0722 * b > 0.5)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::parse_osc11;
+
+    #[test]
+    fn judges_background_from_osc11() {
+        assert_eq!(parse_osc11("\x1b]11;rgb:ffff/ffff/ffff\x07"), Some(true));
+        assert_eq!(parse_osc11("\x1b]11;rgb:0000/0000/0000\x1b\\"), Some(false));
+        // Solarized light and dark, two hex digits a channel.
+        assert_eq!(parse_osc11("\x1b]11;rgb:fd/f6/e3\x07"), Some(true));
+        assert_eq!(parse_osc11("\x1b]11;rgb:00/2b/36\x07"), Some(false));
+        // Unterminated, as when the read stopped short of the end.
+        assert_eq!(parse_osc11("\x1b]11;rgb:eeee/eeee/eeee"), Some(true));
+    }
+
+    #[test]
+    fn rejects_malformed_osc11() {
+        assert_eq!(parse_osc11(""), None);
+        assert_eq!(parse_osc11("\x1b]11;?\x07"), None);
+        assert_eq!(parse_osc11("\x1b]11;rgb:ffff/ffff\x07"), None);
+        assert_eq!(parse_osc11("\x1b]11;rgb:ffff/ffff/ffff/ffff\x07"), None);
+        assert_eq!(parse_osc11("\x1b]11;rgb:ffff/zzzz/ffff\x07"), None);
+        assert_eq!(parse_osc11("\x1b]11;rgb:ffff//ffff\x07"), None);
+    }
+}
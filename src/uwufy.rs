@@ -1,87 +1,144 @@
+use serde::Serialize;
+
 pub fn uwu_name(os_name: &mut String) {
-    let uwufied = match os_name.as_str() {
-        "alpine" => "Nyalpine",
-        "amogos" => "AmogOwOS",
-        "android" => "Nyandroid",
-        "arch" => "Nyarch Linuwu",
-        "arcolinux" => "ArcOwO Linuwu",
-        "artix" => "Nyartix Linuwu",
-        "debian" => "Debinyan",
-        "devuan" => "Devunyan",
-        "deepin" => "Dewepyn",
-        "endeavouros" | "EndeavourOS" => "endeavOwO",
-        "fedora" => "Fedowa",
-        "femboyos" => "FemboyOWOS",
-        "gentoo" => "GentOwO",
-        "gnu" => "gnUwU",
-        "guix" => "gnUwU gUwUix",
-        "linuxmint" => "LinUWU Miwint",
-        "manjaro" => "Myanjawo",
-        "manjaro-arm" => "Myanjawo AWM",
-        "neon" => "KDE NeOwOn",
-        "nixos" => "nixOwOs",
-        "opensuse-leap" => "OwOpenSUSE Leap",
-        "opensuse-tumbleweed" => "OwOpenSUSE Tumbleweed",
-        "pop" => "PopOwOS",
-        "raspbian" => "RaspNyan",
-        "rocky" => "Wocky Linuwu",
-        "slackware" => "Swackwawe",
-        "solus" => "sOwOlus",
-        "ubuntu" => "Uwuntu",
-        "void" => "OwOid",
-        "xerolinux" => "xuwulinux",
-        "freebsd" => "FweeBSD",
-        "openbsd" => "OwOpenBSD",
-        "macos" => "macOwOS",
-        "ios" => "iOwOS",
-        "windows" => "WinyandOwOws",
-        _ => "unknown",
-    };
-
-    *os_name = uwufied.to_string();
+    *os_name = crate::assets::meta(os_name)
+        .uwu_name
+        .unwrap_or_else(|| "unknown".to_string());
 }
 
+/// Applied to the kernel string, in order.
+pub const KERNEL: &[(&str, &str)] = &[
+    ("Linux", "Linuwu"),
+    ("linux", "linuwu"),
+    ("alpine", "Nyalpine"),
+    ("amogos", "AmogOwOS"),
+    ("android", "Nyandroid"),
+    ("arch", "Nyarch Linuwu"),
+    ("artix", "Nyartix Linuwu"),
+    ("debian", "Debinyan"),
+    ("deepin", "Dewepyn"),
+    ("endeavouros", "endeavOwO"),
+    ("EndeavourOS", "endeavOwO"),
+    ("fedora", "Fedowa"),
+    ("femboyos", "FemboyOWOS"),
+    ("gentoo", "GentOwO"),
+    ("gnu", "gnUwU"),
+    ("guix", "gnUwU gUwUix"),
+    ("linuxmint", "LinUWU Miwint"),
+    ("manjaro", "Myanjawo"),
+    ("manjaro-arm", "Myanjawo AWM"),
+    ("neon", "KDE NeOwOn"),
+    ("nixos", "nixOwOs"),
+    ("opensuse-leap", "OwOpenSUSE Leap"),
+    ("opensuse-tumbleweed", "OwOpenSUSE Tumbleweed"),
+    ("pop", "PopOwOS"),
+    ("raspbian", "RaspNyan"),
+    ("rocky", "Wocky Linuwu"),
+    ("slackware", "Swackwawe"),
+    ("solus", "sOwOlus"),
+    ("ubuntu", "Uwuntu"),
+    ("void", "OwOid"),
+    ("xerolinux", "xuwulinux"),
+    ("freebsd", "FweeBSD"),
+    ("openbsd", "OwOpenBSD"),
+    ("macos", "macOwOS"),
+    ("ios", "iOwOS"),
+    ("windows", "WinyandOwOws"),
+];
+
+/// Applied to the host model, CPU and GPU names, in order.
+pub const HARDWARE: &[(&str, &str)] = &[
+    ("lenovo", "LenOwO"),
+    ("Lenovo", "LenOwO"),
+    ("cpu", "CPUwU"),
+    ("CPU", "CPUwU"),
+    ("core", "Cowe"),
+    ("Core", "Cowe"),
+    ("gpu", "GPUwU"),
+    ("GPU", "GPUwU"),
+    ("graphics", "Gwaphics"),
+    ("Graphics", "Gwaphics"),
+    ("corporation", "COwOpowation"),
+    ("Corporation", "COwOpowation"),
+    ("nvidia", "NyaVIDIA"),
+    ("NVIDIA", "NyaVIDIA"),
+    ("mobile", "Mwobile"),
+    ("Mobile", "Mwobile"),
+    ("intel", "Inteww"),
+    ("Intel", "Inteww"),
+    ("celeron", "Celewon"),
+    ("Celeron", "Celewon"),
+    ("radeon", "Radenyan"),
+    ("Radeon", "Radenyan"),
+    ("geforce", "GeFOwOce"),
+    ("GeForce", "GeFOwOce"),
+    ("raspberry", "Nyasberry"),
+    ("Raspberry", "Nyasberry"),
+    ("broadcom", "Bwoadcom"),
+    ("Broadcom", "Bwoadcom"),
+    ("motorola", "MotOwOwa"),
+    ("Motorola", "MotOwOwa"),
+    ("proliant", "ProLinyant"),
+    ("ProLiant", "ProLinyant"),
+    ("poweredge", "POwOwEdge"),
+    ("PowerEdge", "POwOwEdge"),
+    ("apple", "Nyapple"),
+    ("Apple", "Nyapple"),
+    ("electronic", "ElectrOwOnic"),
+    ("Electronic", "ElectrOwOnic"),
+    ("processor", "Pwocessow"),
+    ("Processor", "Pwocessow"),
+    ("microsoft", "MicOwOsoft"),
+    ("Microsoft", "MicOwOsoft"),
+    ("ryzen", "Wyzen"),
+    ("Ryzen", "Wyzen"),
+    ("advanced", "Adwanced"),
+    ("Advanced", "Adwanced"),
+    ("micro", "Micwo"),
+    ("Micro", "Micwo"),
+    ("devices", "Dewices"),
+    ("Devices", "Dewices"),
+    ("inc.", "Nyanc."),
+    ("Inc.", "Nyanc."),
+    ("lucienne", "Lucienyan"),
+    ("Lucienne", "Lucienyan"),
+    ("tuxedo", "TUWUXEDO"),
+    ("TUXEDO", "TUWUXEDO"),
+    ("aura", "Uwura"),
+    ("Aura", "Uwura"),
+];
+
+/// Applied to the package manager label, in order.
+pub const PKGMAN: &[(&str, &str)] = &[
+    ("brew-cask", "bwew-cawsk"),
+    ("brew-cellar", "bwew-cewwaw"),
+    ("emerge", "emewge"),
+    ("flatpak", "fwatpakkies"),
+    ("pacman", "pacnyan"),
+    ("port", "powt"),
+    ("snap", "snyap"),
+    ("scoop", "scowoop"),
+];
+
+/// Applied to the desktop environment name, in order.
+pub const DE: &[(&str, &str)] = &[
+    ("GNOME", "GNOWOME"),
+    ("KDE Plasma", "KDE Pwasma"),
+    ("XFCE", "XFCEwE"),
+    ("Cinnamon", "Cinnyamon"),
+    ("MATE", "MAWTE"),
+    ("LXQt", "LXQwT"),
+    ("Budgie", "Buwdgie"),
+    ("Deepin", "Dewepyn"),
+    ("Pantheon", "Panthewon"),
+    ("Unity", "Uwunity"),
+    ("Enlightenment", "Enwightenment"),
+    ("COSMIC", "COSMIWC"),
+    ("Aqua", "Aquwa"),
+];
+
 pub fn uwu_kernel(kernel: &mut String) {
-    let replacements = [
-        ("Linux", "Linuwu"),
-        ("linux", "linuwu"),
-        ("alpine", "Nyalpine"),
-        ("amogos", "AmogOwOS"),
-        ("android", "Nyandroid"),
-        ("arch", "Nyarch Linuwu"),
-        ("artix", "Nyartix Linuwu"),
-        ("debian", "Debinyan"),
-        ("deepin", "Dewepyn"),
-        ("endeavouros", "endeavOwO"),
-        ("EndeavourOS", "endeavOwO"),
-        ("fedora", "Fedowa"),
-        ("femboyos", "FemboyOWOS"),
-        ("gentoo", "GentOwO"),
-        ("gnu", "gnUwU"),
-        ("guix", "gnUwU gUwUix"),
-        ("linuxmint", "LinUWU Miwint"),
-        ("manjaro", "Myanjawo"),
-        ("manjaro-arm", "Myanjawo AWM"),
-        ("neon", "KDE NeOwOn"),
-        ("nixos", "nixOwOs"),
-        ("opensuse-leap", "OwOpenSUSE Leap"),
-        ("opensuse-tumbleweed", "OwOpenSUSE Tumbleweed"),
-        ("pop", "PopOwOS"),
-        ("raspbian", "RaspNyan"),
-        ("rocky", "Wocky Linuwu"),
-        ("slackware", "Swackwawe"),
-        ("solus", "sOwOlus"),
-        ("ubuntu", "Uwuntu"),
-        ("void", "OwOid"),
-        ("xerolinux", "xuwulinux"),
-        ("freebsd", "FweeBSD"),
-        ("openbsd", "OwOpenBSD"),
-        ("macos", "macOwOS"),
-        ("ios", "iOwOS"),
-        ("windows", "WinyandOwOws"),
-    ];
-
-    for (from, to) in &replacements {
+    for (from, to) in KERNEL {
         if kernel.contains(from) {
             *kernel = kernel.replace(from, to);
         }
@@ -89,89 +146,104 @@ pub fn uwu_kernel(kernel: &mut String) {
 }
 
 pub fn uwu_hw(hw: &mut String) {
-    let replacements = [
-        ("lenovo", "LenOwO"),
-        ("Lenovo", "LenOwO"),
-        ("cpu", "CPUwU"),
-        ("CPU", "CPUwU"),
-        ("core", "Cowe"),
-        ("Core", "Cowe"),
-        ("gpu", "GPUwU"),
-        ("GPU", "GPUwU"),
-        ("graphics", "Gwaphics"),
-        ("Graphics", "Gwaphics"),
-        ("corporation", "COwOpowation"),
-        ("Corporation", "COwOpowation"),
-        ("nvidia", "NyaVIDIA"),
-        ("NVIDIA", "NyaVIDIA"),
-        ("mobile", "Mwobile"),
-        ("Mobile", "Mwobile"),
-        ("intel", "Inteww"),
-        ("Intel", "Inteww"),
-        ("celeron", "Celewon"),
-        ("Celeron", "Celewon"),
-        ("radeon", "Radenyan"),
-        ("Radeon", "Radenyan"),
-        ("geforce", "GeFOwOce"),
-        ("GeForce", "GeFOwOce"),
-        ("raspberry", "Nyasberry"),
-        ("Raspberry", "Nyasberry"),
-        ("broadcom", "Bwoadcom"),
-        ("Broadcom", "Bwoadcom"),
-        ("motorola", "MotOwOwa"),
-        ("Motorola", "MotOwOwa"),
-        ("proliant", "ProLinyant"),
-        ("ProLiant", "ProLinyant"),
-        ("poweredge", "POwOwEdge"),
-        ("PowerEdge", "POwOwEdge"),
-        ("apple", "Nyapple"),
-        ("Apple", "Nyapple"),
-        ("electronic", "ElectrOwOnic"),
-        ("Electronic", "ElectrOwOnic"),
-        ("processor", "Pwocessow"),
-        ("Processor", "Pwocessow"),
-        ("microsoft", "MicOwOsoft"),
-        ("Microsoft", "MicOwOsoft"),
-        ("ryzen", "Wyzen"),
-        ("Ryzen", "Wyzen"),
-        ("advanced", "Adwanced"),
-        ("Advanced", "Adwanced"),
-        ("micro", "Micwo"),
-        ("Micro", "Micwo"),
-        ("devices", "Dewices"),
-        ("Devices", "Dewices"),
-        ("inc.", "Nyanc."),
-        ("Inc.", "Nyanc."),
-        ("lucienne", "Lucienyan"),
-        ("Lucienne", "Lucienyan"),
-        ("tuxedo", "TUWUXEDO"),
-        ("TUXEDO", "TUWUXEDO"),
-        ("aura", "Uwura"),
-        ("Aura", "Uwura"),
-    ];
-
-    for (from, to) in &replacements {
+    for (from, to) in HARDWARE {
         *hw = hw.replace(from, to);
     }
 }
 
 pub fn uwu_pkgman(pkgman: &mut String) {
-    let replacements = [
-        ("brew-cask", "bwew-cawsk"),
-        ("brew-cellar", "bwew-cewwaw"),
-        ("emerge", "emewge"),
-        ("flatpak", "fwatpakkies"),
-        ("pacman", "pacnyan"),
-        ("port", "powt"),
-        ("snap", "snyap"),
-        ("scoop", "scowoop"),
-    ];
-
-    for (from, to) in &replacements {
+    for (from, to) in PKGMAN {
         *pkgman = pkgman.replace(from, to);
     }
 }
 
+pub fn uwu_de(de: &mut String) {
+    for (from, to) in DE {
+        *de = de.replace(from, to);
+    }
+}
+
+/// Every replacement table, for tools outside uwufetch that want to
+/// uwufy the same way. See `--dump-uwu-map`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UwuMap {
+    /// Logo name to the OS name shown for it, from the logo sidecars.
+    pub name: Vec<(String, String)>,
+    pub kernel: &'static [(&'static str, &'static str)],
+    pub hardware: &'static [(&'static str, &'static str)],
+    pub pkgman: &'static [(&'static str, &'static str)],
+    pub de: &'static [(&'static str, &'static str)],
+}
+
+pub fn uwu_map() -> UwuMap {
+    let name = crate::assets::logos()
+        .into_iter()
+        .filter_map(|logo| Some((logo.name, logo.meta.uwu_name?)))
+        .collect();
+
+    UwuMap {
+        name,
+        kernel: KERNEL,
+        hardware: HARDWARE,
+        pkgman: PKGMAN,
+        de: DE,
+    }
+}
+
+/// Units left alone by `uwu_generic`, matched case-sensitively.
+const UNITS: &[&str] = &[
+    "B", "KB", "MB", "GB", "TB", "KiB", "MiB", "GiB", "TiB", "Hz", "kHz", "MHz", "GHz", "RPM", "W",
+    "mW", "V", "mV", "nm", "mm", "ms", "ns",
+];
+
+/// Uwufies arbitwawy text a word at a time: r and l become w, and n
+/// before a vowel becomes ny. Words with a digit in them (`i7`, `2.80GHz`,
+/// `x86`), units, and short all-caps words like the `TM` in `Core(TM)`
+/// or `CPU` are left alone.
+pub fn uwu_generic(text: &mut String) {
+    let mut out = String::with_capacity(text.len() + text.len() / 4);
+    let mut word = String::new();
+
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            word.push(c);
+        } else {
+            uwu_word(&word, &mut out);
+            word.clear();
+            out.push(c);
+        }
+    }
+    uwu_word(&word, &mut out);
+
+    *text = out;
+}
+
+fn uwu_word(word: &str, out: &mut String) {
+    let protected = word.chars().any(|c| c.is_ascii_digit())
+        || UNITS.contains(&word)
+        || (word.chars().count() <= 3 && word.chars().all(char::is_uppercase));
+    if protected {
+        out.push_str(word);
+        return;
+    }
+
+    let chars: Vec<char> = word.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        let next = chars.get(i + 1).copied();
+        match c {
+            'r' | 'l' => out.push('w'),
+            'R' | 'L' => out.push('W'),
+            'n' | 'N' if next.is_some_and(|n| "aeiouAEIOU".contains(n)) => {
+                out.push(c);
+                // Shout along with an all-caps word.
+                let shouting = c == 'N' && next.is_some_and(char::is_uppercase);
+                out.push(if shouting { 'Y' } else { 'y' });
+            }
+            _ => out.push(c),
+        }
+    }
+}
+
 pub fn uwufy_all(info: &mut crate::info::SystemInfo) {
     uwu_kernel(&mut info.kernel);
     uwu_hw(&mut info.cpu_model);
@@ -180,5 +252,51 @@ pub fn uwufy_all(info: &mut crate::info::SystemInfo) {
         uwu_hw(gpu);
     }
     uwu_pkgman(&mut info.pkgman_name);
+    uwu_de(&mut info.de);
+    if let Some(track) = &mut info.now_playing {
+        uwu_generic(&mut track.artist);
+        uwu_generic(&mut track.title);
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::uwu_generic;
+
+    fn uwu(text: &str) -> String {
+        let mut text = text.to_string();
+        uwu_generic(&mut text);
+        text
+    }
+
+    #[test]
+    fn replaces_r_l_and_n_before_vowels() {
+        assert_eq!(uwu("laurel@nova"), "wauwew@nyova");
+        assert_eq!(uwu("Nano"), "Nyanyo");
+        assert_eq!(uwu("NANO WORLD"), "NYANYO WOWWD");
+    }
+
+    #[test]
+    fn leaves_trademarks_and_model_numbers() {
+        assert_eq!(uwu("Intel(R) Core(TM) i7"), "Intew(R) Cowe(TM) i7");
+        assert_eq!(
+            uwu("11th Gen Intel(R) Core(TM) i7-1165G7 @ 2.80GHz"),
+            "11th Gen Intew(R) Cowe(TM) i7-1165G7 @ 2.80GHz"
+        );
+        assert_eq!(uwu("AMD Ryzen 7 5800X3D"), "AMD Wyzen 7 5800X3D");
+    }
+
+    #[test]
+    fn leaves_numbers_and_units() {
+        assert_eq!(uwu("16 GiB"), "16 GiB");
+        assert_eq!(uwu("1200 RPM, 3.5 GHz"), "1200 RPM, 3.5 GHz");
+        assert_eq!(uwu("x86_64"), "x86_64");
+        assert_eq!(uwu("6.9.3-arch1-1"), "6.9.3-arch1-1");
+    }
+
+    #[test]
+    fn keeps_non_ascii_and_empty_text() {
+        assert_eq!(uwu(""), "");
+        assert_eq!(uwu("naïve café"), "nyaïve café");
+    }
+}